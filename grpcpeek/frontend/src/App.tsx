@@ -484,6 +484,11 @@ function App() {
                   onSaveRequest={requestManager.handleSaveRequest}
                   onSendStreamMessage={requestManager.handleSendStreamMessage}
                   onFinishStreaming={requestManager.handleFinishStreaming}
+                  onOpenBenchmark={() => openModal('benchmark', {
+                    tab: requestManager.activeTab,
+                    onRun: requestManager.handleRunBenchmark,
+                    onStop: requestManager.handleStopBenchmark,
+                  })}
                   services={requestManager.services}
                 />
               ) : (
@@ -7,6 +7,7 @@ import { SettingsModal } from './workspace/SettingsModal'
 import { CreateWorkspaceModal } from './workspace/CreateWorkspaceModal'
 import { WorkspaceSettingsModal } from './workspace/WorkspaceSettingsModal'
 import { SaveRequestModal } from './workspace/SaveRequestModal'
+import { BenchmarkModal } from './workspace/BenchmarkModal'
 
 /**
  * Central modal renderer - handles all app-wide modals
@@ -106,6 +107,18 @@ export function ModalRenderer() {
           />
         ) : null
       })()}
+
+      {/* Benchmark Modal */}
+      {isModalOpen('benchmark') && (() => {
+        const props = getModalProps('benchmark')
+        return props ? (
+          <BenchmarkModal
+            isOpen={true}
+            {...props as any}
+            onClose={() => closeModal('benchmark')}
+          />
+        ) : null
+      })()}
     </>
   )
 }
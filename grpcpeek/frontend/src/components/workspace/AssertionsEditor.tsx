@@ -0,0 +1,160 @@
+import { Button, Input, Select } from '../ui'
+import type { Assertion } from '../../types/workspace'
+
+interface AssertionsEditorProps {
+  assertions: Assertion[]
+  onChange: (assertions: Assertion[]) => void
+}
+
+type AssertionType = Assertion['type']
+
+// Editable fields per assertion type. `json` fields take a JSON value; text that
+// isn't valid JSON is kept as a string.
+interface FieldSpec {
+  key: string
+  label: string
+  kind: 'text' | 'json' | 'number'
+  optional?: boolean
+}
+
+const FIELDS: Record<AssertionType, FieldSpec[]> = {
+  status: [{ key: 'code', label: 'Code or name, e.g. 0 or NOT_FOUND', kind: 'json' }],
+  jsonPath: [
+    { key: 'path', label: 'Path, e.g. $.user.id', kind: 'text' },
+    { key: 'equals', label: 'Equals (JSON)', kind: 'json' },
+  ],
+  jmesPath: [
+    { key: 'expression', label: 'Expression, e.g. length(items) > `0`', kind: 'text' },
+    { key: 'equals', label: 'Equals (JSON, optional)', kind: 'json', optional: true },
+  ],
+  regex: [
+    { key: 'pattern', label: 'Pattern', kind: 'text' },
+    { key: 'path', label: 'Path (optional)', kind: 'text', optional: true },
+  ],
+  header: [
+    { key: 'name', label: 'Header name', kind: 'text' },
+    { key: 'equals', label: 'Equals (optional)', kind: 'text', optional: true },
+    { key: 'pattern', label: 'Pattern (optional)', kind: 'text', optional: true },
+  ],
+  trailer: [
+    { key: 'name', label: 'Trailer name', kind: 'text' },
+    { key: 'equals', label: 'Equals (optional)', kind: 'text', optional: true },
+    { key: 'pattern', label: 'Pattern (optional)', kind: 'text', optional: true },
+  ],
+  messageCount: [
+    { key: 'min', label: 'Min', kind: 'number', optional: true },
+    { key: 'max', label: 'Max', kind: 'number', optional: true },
+  ],
+  latency: [{ key: 'maxMs', label: 'Max ms', kind: 'number' }],
+}
+
+const TYPE_LABELS: Record<AssertionType, string> = {
+  status: 'Status',
+  jsonPath: 'JSONPath',
+  jmesPath: 'JMESPath',
+  regex: 'Regex',
+  header: 'Header',
+  trailer: 'Trailer',
+  messageCount: 'Message count',
+  latency: 'Latency',
+}
+
+const DEFAULTS: Record<AssertionType, Assertion> = {
+  status: { type: 'status', code: 0 },
+  jsonPath: { type: 'jsonPath', path: '$', equals: null },
+  jmesPath: { type: 'jmesPath', expression: '' },
+  regex: { type: 'regex', pattern: '' },
+  header: { type: 'header', name: '' },
+  trailer: { type: 'trailer', name: '' },
+  messageCount: { type: 'messageCount', min: 1 },
+  latency: { type: 'latency', maxMs: 1000 },
+}
+
+function displayValue(value: unknown, kind: FieldSpec['kind']): string {
+  if (value === undefined) return ''
+  if (kind === 'json' && typeof value !== 'string') return JSON.stringify(value)
+  return String(value)
+}
+
+function parseValue(text: string, field: FieldSpec): unknown {
+  if (text === '' && field.optional) return undefined
+  if (field.kind === 'number') {
+    const n = Number(text)
+    return text === '' || Number.isNaN(n) ? undefined : n
+  }
+  if (field.kind === 'json') {
+    try {
+      return JSON.parse(text)
+    } catch {
+      return text
+    }
+  }
+  return text
+}
+
+// Checks run against the response after every call; results show in the response's Tests tab.
+export function AssertionsEditor({ assertions, onChange }: AssertionsEditorProps) {
+  const update = (index: number, assertion: Assertion) => {
+    onChange(assertions.map((a, i) => (i === index ? assertion : a)))
+  }
+
+  return (
+    <div className="flex-1 space-y-3 overflow-y-auto">
+      {assertions.length === 0 && (
+        <p className="text-xs text-muted-foreground">
+          No assertions. Add checks on the status, response body, metadata, message count or latency;
+          they run after every call.
+        </p>
+      )}
+
+      {assertions.map((assertion, index) => (
+        <div key={index} className="flex items-start gap-2 rounded-lg border border-border/50 p-2">
+          <Select
+            value={assertion.type}
+            onChange={(e) => update(index, DEFAULTS[e.target.value as AssertionType])}
+            className="h-8 w-36 shrink-0 text-xs"
+            aria-label="Assertion type"
+          >
+            {(Object.keys(TYPE_LABELS) as AssertionType[]).map((type) => (
+              <option key={type} value={type}>{TYPE_LABELS[type]}</option>
+            ))}
+          </Select>
+          <div className="flex flex-1 flex-wrap gap-2">
+            {FIELDS[assertion.type].map((field) => (
+              <Input
+                key={field.key}
+                value={displayValue((assertion as Record<string, unknown>)[field.key], field.kind)}
+                placeholder={field.label}
+                title={field.label}
+                type={field.kind === 'number' ? 'number' : 'text'}
+                onChange={(e) => {
+                  const next: Record<string, unknown> = { ...assertion, [field.key]: parseValue(e.target.value, field) }
+                  if (next[field.key] === undefined) delete next[field.key]
+                  update(index, next as Assertion)
+                }}
+                className="h-8 min-w-[8rem] flex-1 font-mono text-xs"
+              />
+            ))}
+          </div>
+          <Button
+            variant="ghost"
+            size="sm"
+            onClick={() => onChange(assertions.filter((_, i) => i !== index))}
+            className="h-8 px-2 text-xs text-muted-foreground hover:text-red-500"
+            title="Remove assertion"
+          >
+            ✕
+          </Button>
+        </div>
+      ))}
+
+      <Button
+        variant="secondary"
+        size="sm"
+        onClick={() => onChange([...assertions, DEFAULTS.status])}
+      >
+        + Add Assertion
+      </Button>
+    </div>
+  )
+}
@@ -0,0 +1,250 @@
+/**
+ * BenchmarkModal - Load test the active request
+ *
+ * Features:
+ * - Concurrency and total call count or duration
+ * - Live progress from "benchmark-progress" events
+ * - Latency percentiles, histogram, status codes and error samples
+ * - Stop a running benchmark and keep the report so far
+ */
+
+import { useEffect, useState } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { Button, Card, Input, Label, Select } from '../ui'
+import type { BenchmarkOptions, BenchmarkProgress, BenchmarkReport, RequestTab } from '../../types/workspace'
+
+interface BenchmarkModalProps {
+  isOpen: boolean
+  onClose: () => void
+  tab: RequestTab
+  onRun: (options: BenchmarkOptions) => Promise<BenchmarkReport | null>
+  onStop: (tabId: string) => Promise<void>
+}
+
+function formatMs(ms: number): string {
+  return ms >= 100 ? `${ms.toFixed(0)} ms` : `${ms.toFixed(2)} ms`
+}
+
+export function BenchmarkModal({ isOpen, onClose, tab, onRun, onStop }: BenchmarkModalProps) {
+  const [concurrency, setConcurrency] = useState(10)
+  const [limit, setLimit] = useState<'total' | 'duration'>('total')
+  const [total, setTotal] = useState(200)
+  const [durationSeconds, setDurationSeconds] = useState(10)
+  const [isRunning, setIsRunning] = useState(false)
+  const [progress, setProgress] = useState<BenchmarkProgress | null>(null)
+  const [report, setReport] = useState<BenchmarkReport | null>(null)
+
+  useEffect(() => {
+    let isMounted = true
+    let unlistenFn: (() => void) | null = null
+
+    listen<{ tabId: string; progress: BenchmarkProgress }>('benchmark-progress', (event) => {
+      if (event.payload.tabId === tab.id) {
+        setProgress(event.payload.progress)
+      }
+    }).then((unlisten) => {
+      // If the effect was cleaned up before listen() resolved, unlisten immediately.
+      if (isMounted) {
+        unlistenFn = unlisten
+      } else {
+        unlisten()
+      }
+    })
+
+    return () => {
+      isMounted = false
+      unlistenFn?.()
+    }
+  }, [tab.id])
+
+  if (!isOpen) return null
+
+  const handleRun = async () => {
+    setIsRunning(true)
+    setProgress(null)
+    setReport(null)
+    const result = await onRun({
+      concurrency,
+      ...(limit === 'total' ? { total } : { durationMs: durationSeconds * 1000 }),
+    })
+    setReport(result)
+    setIsRunning(false)
+  }
+
+  // A running benchmark keeps going in the backend; stop it rather than orphan it.
+  const handleClose = () => {
+    if (isRunning) {
+      onStop(tab.id)
+    }
+    onClose()
+  }
+
+  const stats = report
+    ? { completed: report.count, errors: report.errors, requestsPerSecond: report.requestsPerSecond, latency: report.latency }
+    : progress
+  const histogramPeak = Math.max(1, ...(report?.histogram.map(b => b.count) || []))
+
+  return (
+    <div
+      className="fixed inset-0 z-50 flex items-center justify-center bg-black/50 p-4 animate-in fade-in"
+      onClick={handleClose}
+    >
+      <Card
+        className="flex max-h-[90vh] w-full max-w-2xl flex-col animate-in zoom-in-95"
+        onClick={(e) => e.stopPropagation()}
+      >
+        {/* Header */}
+        <div className="flex items-center justify-between border-b border-border/50 p-6">
+          <div>
+            <h2 className="text-xl font-semibold">Benchmark</h2>
+            <p className="text-sm text-muted-foreground">
+              {tab.service}.{tab.method} — use {'{{iteration.index}}'}, {'{{iteration.uuid}}'} and the like for per-call values
+            </p>
+          </div>
+          <button
+            onClick={handleClose}
+            className="rounded-lg p-2 text-muted-foreground transition hover:bg-surface-muted hover:text-foreground"
+          >
+            <svg className="h-5 w-5" fill="none" viewBox="0 0 24 24" stroke="currentColor">
+              <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M6 18L18 6M6 6l12 12" />
+            </svg>
+          </button>
+        </div>
+
+        <div className="flex-1 space-y-5 overflow-y-auto p-6">
+          {/* Options */}
+          <div className="grid grid-cols-3 gap-3">
+            <div className="space-y-1.5">
+              <Label htmlFor="benchmark-concurrency">Concurrency</Label>
+              <Input
+                id="benchmark-concurrency"
+                type="number"
+                min={1}
+                value={concurrency}
+                onChange={(e) => setConcurrency(Math.max(1, parseInt(e.target.value, 10) || 1))}
+                disabled={isRunning}
+              />
+            </div>
+            <div className="space-y-1.5">
+              <Label htmlFor="benchmark-limit">Stop after</Label>
+              <Select
+                id="benchmark-limit"
+                value={limit}
+                onChange={(e) => setLimit(e.target.value as 'total' | 'duration')}
+                disabled={isRunning}
+              >
+                <option value="total">Call count</option>
+                <option value="duration">Duration</option>
+              </Select>
+            </div>
+            <div className="space-y-1.5">
+              <Label htmlFor="benchmark-amount">{limit === 'total' ? 'Calls' : 'Seconds'}</Label>
+              <Input
+                id="benchmark-amount"
+                type="number"
+                min={1}
+                value={limit === 'total' ? total : durationSeconds}
+                onChange={(e) => {
+                  const value = Math.max(1, parseInt(e.target.value, 10) || 1)
+                  if (limit === 'total') {
+                    setTotal(value)
+                  } else {
+                    setDurationSeconds(value)
+                  }
+                }}
+                disabled={isRunning}
+              />
+            </div>
+          </div>
+
+          {/* Live progress, then the final numbers */}
+          {stats && (
+            <div className="grid grid-cols-4 gap-3 text-center">
+              {[
+                ['Calls', stats.completed.toString()],
+                ['Errors', stats.errors.toString()],
+                ['Req/s', stats.requestsPerSecond.toFixed(1)],
+                ['Mean', formatMs(stats.latency.meanMs)],
+                ['p50', formatMs(stats.latency.p50Ms)],
+                ['p90', formatMs(stats.latency.p90Ms)],
+                ['p99', formatMs(stats.latency.p99Ms)],
+                ['Max', formatMs(stats.latency.maxMs)],
+              ].map(([label, value]) => (
+                <div key={label} className="rounded-lg border border-border/60 bg-surface-muted/30 px-3 py-2">
+                  <div className="text-[11px] uppercase tracking-wide text-muted-foreground">{label}</div>
+                  <div className="font-mono text-sm font-semibold">{value}</div>
+                </div>
+              ))}
+            </div>
+          )}
+
+          {report && (
+            <>
+              {report.stopped && (
+                <p className="text-xs text-muted-foreground">Stopped early; the numbers cover the calls made.</p>
+              )}
+
+              {report.histogram.length > 0 && (
+                <div className="space-y-1">
+                  <h3 className="text-xs font-semibold text-muted-foreground">Latency histogram</h3>
+                  {report.histogram.map((bucket) => (
+                    <div key={bucket.upToMs} className="flex items-center gap-2 font-mono text-xs">
+                      <span className="w-24 shrink-0 text-right text-muted-foreground">≤ {formatMs(bucket.upToMs)}</span>
+                      <div className="h-3 flex-1 rounded bg-surface-muted">
+                        <div
+                          className="h-3 rounded bg-primary/70"
+                          style={{ width: `${(bucket.count / histogramPeak) * 100}%` }}
+                        />
+                      </div>
+                      <span className="w-12 shrink-0">{bucket.count}</span>
+                    </div>
+                  ))}
+                </div>
+              )}
+
+              <div className="space-y-1">
+                <h3 className="text-xs font-semibold text-muted-foreground">Status codes</h3>
+                <div className="flex flex-wrap gap-2 font-mono text-xs">
+                  {Object.entries(report.statusCodes).map(([status, count]) => (
+                    <span
+                      key={status}
+                      className={`rounded px-2 py-0.5 ${status === 'OK' ? 'bg-green-500/15' : 'bg-red-500/15'}`}
+                    >
+                      {status}: {count}
+                    </span>
+                  ))}
+                </div>
+              </div>
+
+              {report.errorSamples.length > 0 && (
+                <div className="space-y-1">
+                  <h3 className="text-xs font-semibold text-muted-foreground">Error samples</h3>
+                  <ul className="space-y-1 font-mono text-xs">
+                    {report.errorSamples.map((sample) => (
+                      <li key={`${sample.status}-${sample.index}`} className="break-all">
+                        <span className="text-muted-foreground">#{sample.index}</span> {sample.status}: {sample.message}
+                      </li>
+                    ))}
+                  </ul>
+                </div>
+              )}
+            </>
+          )}
+        </div>
+
+        {/* Footer */}
+        <div className="flex justify-end gap-2 border-t border-border/50 p-6">
+          {isRunning ? (
+            <Button variant="secondary" onClick={() => onStop(tab.id)}>
+              Stop
+            </Button>
+          ) : (
+            <Button onClick={handleRun}>
+              {report ? 'Run Again' : 'Run Benchmark'}
+            </Button>
+          )}
+        </div>
+      </Card>
+    </div>
+  )
+}
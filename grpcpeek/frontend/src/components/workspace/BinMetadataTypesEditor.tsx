@@ -0,0 +1,77 @@
+import { useState } from 'react'
+import { Button, Input, Label } from '../ui'
+
+interface BinMetadataTypesEditorProps {
+  types: Record<string, string>
+  onChange: (types: Record<string, string>) => void
+}
+
+// Response headers and trailers ending in -bin are always shown as base64 and hex;
+// a message type named here also decodes them, e.g. x-trace-bin → trace.v1.Span.
+// Mount with a key per tab so rows reset when the tab changes.
+export function BinMetadataTypesEditor({ types, onChange }: BinMetadataTypesEditorProps) {
+  const [rows, setRows] = useState(() => Object.entries(types).map(([name, type]) => ({ name, type })))
+
+  const update = (next: { name: string; type: string }[]) => {
+    setRows(next)
+    onChange(
+      next.reduce<Record<string, string>>((acc, row) => {
+        const name = row.name.trim()
+        if (name && row.type.trim()) {
+          acc[name] = row.type.trim()
+        }
+        return acc
+      }, {})
+    )
+  }
+
+  return (
+    <div className="space-y-3">
+      <div className="flex items-center justify-between">
+        <Label>Decode Binary Response Metadata</Label>
+        <Button
+          variant="secondary"
+          size="sm"
+          onClick={() => setRows([...rows, { name: '', type: '' }])}
+          className="h-6 px-2 text-xs"
+        >
+          + Add Type
+        </Button>
+      </div>
+
+      {rows.length === 0 ? (
+        <p className="text-xs text-muted-foreground">
+          -bin response headers and trailers show as base64 and hex. Name a message type for a header to decode it too.
+        </p>
+      ) : (
+        <div className="space-y-2">
+          {rows.map((row, index) => (
+            <div key={index} className="flex gap-2">
+              <Input
+                value={row.name}
+                onChange={(e) => update(rows.map((r, i) => (i === index ? { ...r, name: e.target.value } : r)))}
+                placeholder="Header name, e.g. x-trace-bin"
+                className="flex-1 text-xs"
+              />
+              <Input
+                value={row.type}
+                onChange={(e) => update(rows.map((r, i) => (i === index ? { ...r, type: e.target.value } : r)))}
+                placeholder="Message type, e.g. trace.v1.Span"
+                className="flex-1 font-mono text-xs"
+              />
+              <Button
+                variant="ghost"
+                size="sm"
+                onClick={() => update(rows.filter((_, i) => i !== index))}
+                className="h-9 w-9 p-0"
+                title="Remove type"
+              >
+                ✕
+              </Button>
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  )
+}
@@ -4,6 +4,8 @@ import type { RequestTab, AuthConfig, TlsConfig, Workspace, VariableContext, Ser
 import { VariableIndicator } from './VariableIndicator'
 import { VariableHighlightedTextarea } from './VariableHighlightedTextarea'
 import { ClientStreamingEditor } from './ClientStreamingEditor'
+import { AssertionsEditor } from './AssertionsEditor'
+import { BinMetadataTypesEditor } from './BinMetadataTypesEditor'
 import { open } from '@tauri-apps/plugin-dialog'
 
 interface RequestEditorProps {
@@ -17,6 +19,7 @@ interface RequestEditorProps {
   onSaveRequest: () => void
   onSendStreamMessage: (messageId: string) => void
   onFinishStreaming: () => void
+  onOpenBenchmark: () => void
   services: Service[]
 }
 
@@ -31,9 +34,10 @@ export function RequestEditor({
   onSaveRequest,
   onSendStreamMessage,
   onFinishStreaming,
+  onOpenBenchmark,
   services,
 }: RequestEditorProps) {
-  const [activeSection, setActiveSection] = useState<'body' | 'metadata' | 'auth' | 'tls'>('body')
+  const [activeSection, setActiveSection] = useState<'body' | 'metadata' | 'auth' | 'tls' | 'tests'>('body')
   
   // Check if this is a client streaming method
   const isClientStreaming = tab.methodType === 'client_streaming' || tab.methodType === 'bidirectional_streaming'
@@ -283,6 +287,18 @@ export function RequestEditor({
           >
             {isGenerating ? 'Resetting...' : 'Reset to Sample'}
           </Button>
+          <label
+            className="flex cursor-pointer items-center gap-2 text-xs text-muted-foreground"
+            title="Also return the field-number / wire-type view of the response, with fields missing from the schema highlighted"
+          >
+            <input
+              type="checkbox"
+              checked={tab.includeRaw || false}
+              onChange={(e) => onUpdate({ includeRaw: e.target.checked })}
+              className="h-3.5 w-3.5 rounded border-border"
+            />
+            Wire view
+          </label>
         </div>
         
         {/* Action Buttons */}
@@ -307,6 +323,15 @@ export function RequestEditor({
           >
             Save
           </Button>
+          <Button
+            variant="secondary"
+            onClick={onOpenBenchmark}
+            disabled={tab.isLoading || !tab.service || !tab.method}
+            title="Call this method repeatedly and measure latency and throughput"
+            className="px-4"
+          >
+            Benchmark
+          </Button>
         </div>
       </div>
 
@@ -361,6 +386,16 @@ export function RequestEditor({
           <svg width="10" height="10" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round" className="inline ml-0.5"><rect x="3" y="11" width="18" height="11" rx="2" ry="2"/><path d="M7 11V7a5 5 0 0 1 10 0v4"/></svg>
         )}
         </button>
+        <button
+          onClick={() => setActiveSection('tests')}
+          className={`flex-1 rounded px-3 py-1.5 text-xs font-medium transition-all ${
+            activeSection === 'tests'
+              ? 'bg-surface text-foreground shadow-sm'
+              : 'text-muted-foreground hover:text-foreground'
+          }`}
+        >
+          Tests {tab.assertions && tab.assertions.length > 0 ? `(${tab.assertions.length})` : ''}
+        </button>
       </div>
 
       {/* Section content */}
@@ -415,6 +450,13 @@ export function RequestEditor({
           </>
         )}
 
+        {activeSection === 'tests' && (
+          <AssertionsEditor
+            assertions={tab.assertions || []}
+            onChange={(assertions) => onUpdate({ assertions, isDirty: true })}
+          />
+        )}
+
         {activeSection === 'metadata' && (
           <div className="space-y-4">
             {/* Environment-level metadata (inherited, read-only) */}
@@ -533,6 +575,12 @@ export function RequestEditor({
                 </p>
               )}
             </div>
+
+            <BinMetadataTypesEditor
+              key={tab.id}
+              types={tab.binMetadataTypes || {}}
+              onChange={(binMetadataTypes) => onUpdate({ binMetadataTypes, isDirty: true })}
+            />
           </div>
         )}
 
@@ -1,8 +1,9 @@
 interface ResponseMetadataProps {
   metadata: Record<string, unknown>
+  title?: string
 }
 
-export function ResponseMetadata({ metadata }: ResponseMetadataProps) {
+export function ResponseMetadata({ metadata, title = 'Metadata' }: ResponseMetadataProps) {
   // Filter out null/undefined values
   const entries = Object.entries(metadata).filter(([_, value]) => value !== undefined && value !== null)
   const responseCount = Number(metadata.response_count ?? 0)
@@ -34,7 +35,7 @@ export function ResponseMetadata({ metadata }: ResponseMetadataProps) {
   return (
     <div className="rounded-lg border border-border/50 bg-surface-muted/30 overflow-hidden">
       <div className="flex items-center gap-3 px-4 py-2.5 border-b border-border/30 bg-surface-muted/50">
-        <span className="text-sm font-medium text-muted-foreground">Metadata</span>
+        <span className="text-sm font-medium text-muted-foreground">{title}</span>
         {getStatusBadge()}
         {responseCount > 0 && (
           <span className="text-xs text-muted-foreground">
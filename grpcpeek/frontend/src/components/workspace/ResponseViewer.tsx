@@ -4,8 +4,9 @@ import { invoke } from '@tauri-apps/api/core'
 import { Card, Button } from '../ui'
 import { ResponseMetadata } from './ResponseMetadata'
 import { StreamingResponsePanel } from './StreamingResponsePanel'
+import { WireView } from './WireView'
 import { useToast } from '../../contexts/ToastContext'
-import type { RequestTab } from '../../types/workspace'
+import type { RequestTab, RawField } from '../../types/workspace'
 
 const LARGE_RESPONSE_THRESHOLD = 500000
 const LARGE_RESPONSE_PREVIEW_LIMIT = 12000
@@ -34,7 +35,7 @@ interface ResponseViewerProps {
 
 export function ResponseViewer({ tab, onClearStreaming }: ResponseViewerProps) {
   const [showRaw, setShowRaw] = useState(false)
-  const [activeTab, setActiveTab] = useState<'body' | 'metadata'>('body')
+  const [activeTab, setActiveTab] = useState<'body' | 'wire' | 'metadata' | 'tests'>('body')
   const [allowLargeResponseRender, setAllowLargeResponseRender] = useState(false)
   const [confirmUnsafeRender, setConfirmUnsafeRender] = useState(false)
   const { showToast } = useToast()
@@ -108,6 +109,16 @@ export function ResponseViewer({ tab, onClearStreaming }: ResponseViewerProps) {
     ...tab.responseMetadata,
   }
 
+  // Wire trees arrive per event for streams, or as one tree (or one per message) on the result
+  const wireMessages: RawField[][] = tab.streamingMessages.length > 0
+    ? tab.streamingMessages.flatMap(m => (m.raw ? [m.raw] : []))
+    : Array.isArray(tab.rawResponse) && tab.rawResponse.length > 0 && Array.isArray(tab.rawResponse[0])
+      ? (tab.rawResponse as RawField[][])
+      : tab.rawResponse ? [tab.rawResponse as RawField[]] : []
+  const unknownFields = tab.streamingMessages.length > 0
+    ? tab.streamingMessages.flatMap(m => (m.unknownFields || []).map(path => `[${m.index}].${path}`))
+    : tab.unknownFields || []
+
   const copyToClipboard = async () => {
     const contentToCopy = shouldBlockLargeRender ? responsePreview : responseContent
     const successMessage = shouldBlockLargeRender ? 'Response preview copied to clipboard' : 'Response copied to clipboard'
@@ -116,7 +127,8 @@ export function ResponseViewer({ tab, onClearStreaming }: ResponseViewerProps) {
   }
 
   const copyMetadata = async () => {
-    await copyText(JSON.stringify(metadataPayload, null, 2), 'Metadata copied to clipboard', 'Failed to copy metadata')
+    const payload = { ...metadataPayload, trailers: tab.responseTrailers || {} }
+    await copyText(JSON.stringify(payload, null, 2), 'Metadata copied to clipboard', 'Failed to copy metadata')
   }
 
   const downloadResponse = async () => {
@@ -237,6 +249,30 @@ export function ResponseViewer({ tab, onClearStreaming }: ResponseViewerProps) {
         >
           Body
         </button>
+        {wireMessages.length > 0 && (
+          <button
+            onClick={() => setActiveTab('wire')}
+            className={`flex-1 rounded px-3 py-1.5 text-xs font-medium transition-all ${
+              activeTab === 'wire'
+                ? 'bg-surface text-foreground shadow-sm'
+                : 'text-muted-foreground hover:text-foreground'
+            }`}
+          >
+            Wire
+          </button>
+        )}
+        {tab.assertionResults && tab.assertionResults.length > 0 && (
+          <button
+            onClick={() => setActiveTab('tests')}
+            className={`flex-1 rounded px-3 py-1.5 text-xs font-medium transition-all ${
+              activeTab === 'tests'
+                ? 'bg-surface text-foreground shadow-sm'
+                : 'text-muted-foreground hover:text-foreground'
+            }`}
+          >
+            Tests ({tab.assertionResults.filter(r => r.passed).length}/{tab.assertionResults.length})
+          </button>
+        )}
         <button
           onClick={() => setActiveTab('metadata')}
           className={`flex-1 rounded px-3 py-1.5 text-xs font-medium transition-all ${
@@ -253,6 +289,17 @@ export function ResponseViewer({ tab, onClearStreaming }: ResponseViewerProps) {
         {/* Body Tab */}
         {activeTab === 'body' && (
           <>
+            {unknownFields.length > 0 && (
+              <div className="rounded-lg border border-amber-500/40 bg-amber-500/10 px-4 py-2 text-xs text-amber-800 dark:text-amber-200">
+                <span className="font-semibold">
+                  {unknownFields.length === 1 ? '1 field' : `${unknownFields.length} fields`} not in the schema:
+                </span>{' '}
+                <span className="font-mono">{unknownFields.join(', ')}</span>
+                {wireMessages.length === 0 && (
+                  <span className="text-muted-foreground"> — enable "Wire view" on the request to inspect them.</span>
+                )}
+              </div>
+            )}
             {(() => {
               // Priority 1: Check if tab has streaming messages (real-time streaming from events)
               if (tab.streamingMessages.length > 0) {
@@ -425,6 +472,30 @@ export function ResponseViewer({ tab, onClearStreaming }: ResponseViewerProps) {
                         </div>
                       </div>
                       
+                      {/* Rich details decoded from grpc-status-details-bin */}
+                      {tab.errorDetails && tab.errorDetails.length > 0 && (
+                        <div>
+                          <h4 className="text-xs font-semibold uppercase tracking-wide text-red-700 dark:text-red-300 mb-2">
+                            Status Details
+                          </h4>
+                          <div className="space-y-2">
+                            {tab.errorDetails.map((detail, index) => {
+                              const { '@type': typeUrl, ...fields } = detail
+                              return (
+                                <div key={index} className="rounded border border-red-500/20 bg-red-950/5 dark:bg-red-500/5 p-3">
+                                  <div className="mb-1 font-mono text-xs font-semibold text-red-700 dark:text-red-300">
+                                    {typeof typeUrl === 'string' ? typeUrl.split('/').pop() : 'Detail'}
+                                  </div>
+                                  <pre className="font-mono text-xs text-red-800 dark:text-red-200 whitespace-pre-wrap break-words">
+                                    {JSON.stringify(fields, null, 2)}
+                                  </pre>
+                                </div>
+                              )
+                            })}
+                          </div>
+                        </div>
+                      )}
+
                       {/* Troubleshooting hints - optional, helpful suggestions */}
                       {troubleshootingHints && troubleshootingHints.length > 0 && (
                         <div>
@@ -490,6 +561,47 @@ export function ResponseViewer({ tab, onClearStreaming }: ResponseViewerProps) {
           </>
         )}
 
+        {/* Wire Tab */}
+        {activeTab === 'wire' && (
+          <div className="flex min-h-0 flex-1 flex-col gap-3 overflow-y-auto">
+            {tab.decodeError && (
+              <p className="rounded-lg border border-red-500/40 bg-red-500/10 px-4 py-2 text-xs text-red-700 dark:text-red-300">
+                Response did not decode against the schema: {tab.decodeError}
+              </p>
+            )}
+            {wireMessages.map((fields, index) => (
+              <div key={index} className="rounded-lg border border-border/60 bg-surface p-3">
+                {wireMessages.length > 1 && (
+                  <h3 className="mb-2 text-xs font-semibold text-muted-foreground">Message {index + 1}</h3>
+                )}
+                <WireView fields={fields} />
+              </div>
+            ))}
+          </div>
+        )}
+
+        {/* Tests Tab */}
+        {activeTab === 'tests' && tab.assertionResults && (
+          <ul className="flex min-h-0 flex-1 flex-col gap-2 overflow-y-auto">
+            {tab.assertionResults.map((result, index) => (
+              <li
+                key={index}
+                className={`rounded-lg border px-4 py-2 text-xs ${
+                  result.passed
+                    ? 'border-green-500/40 bg-green-500/10'
+                    : 'border-red-500/40 bg-red-500/10'
+                }`}
+              >
+                <div className="flex items-center gap-2">
+                  <span className="font-semibold">{result.passed ? 'PASS' : 'FAIL'}</span>
+                  <span className="font-mono text-muted-foreground">{result.assertion.type}</span>
+                </div>
+                <p className="mt-1 break-all font-mono">{result.message}</p>
+              </li>
+            ))}
+          </ul>
+        )}
+
         {/* Metadata Tab */}
         {activeTab === 'metadata' && (
           <div className="flex min-h-0 flex-1 flex-col gap-3 overflow-hidden">
@@ -498,8 +610,11 @@ export function ResponseViewer({ tab, onClearStreaming }: ResponseViewerProps) {
                 Copy Metadata
               </Button>
             </div>
-            <div className="flex-1 overflow-y-auto">
+            <div className="flex-1 space-y-3 overflow-y-auto">
               <ResponseMetadata metadata={metadataPayload} />
+              {tab.responseTrailers && Object.keys(tab.responseTrailers).length > 0 && (
+                <ResponseMetadata title="Trailers" metadata={tab.responseTrailers} />
+              )}
             </div>
           </div>
         )}
@@ -0,0 +1,62 @@
+import type { RawField } from '../../types/workspace'
+
+interface WireViewProps {
+  fields: RawField[]
+}
+
+function describeValue(field: RawField): string {
+  const value = field.value
+  switch (field.wireType) {
+    case 'varint':
+      return value.int === value.uint
+        ? `${value.uint}  (sint ${value.sint})`
+        : `${value.uint}  (int ${value.int}, sint ${value.sint})`
+    case 'fixed32':
+      return `${value.uint}  (int ${value.int}, float ${value.float})`
+    case 'fixed64':
+      return `${value.uint}  (int ${value.int}, double ${value.double})`
+    case 'len':
+      return typeof value.string === 'string'
+        ? `${JSON.stringify(value.string)}  (${value.length} bytes)`
+        : `${value.length} bytes  ${value.hex}`
+    default:
+      return ''
+  }
+}
+
+// Field-number / wire-type tree of a message. Fields the descriptor doesn't define
+// are highlighted.
+export function WireView({ fields }: WireViewProps) {
+  if (fields.length === 0) {
+    return <p className="font-mono text-xs text-muted-foreground">(empty message)</p>
+  }
+
+  return (
+    <ul className="space-y-0.5 font-mono text-xs">
+      {fields.map((field, index) => (
+        <li key={index}>
+          <div
+            className={`flex items-baseline gap-2 rounded px-1.5 py-0.5 ${
+              field.unknown ? 'bg-amber-500/15 text-amber-800 dark:text-amber-200' : 'text-foreground'
+            }`}
+            title={field.unknown ? 'Not defined in the schema' : undefined}
+          >
+            <span className="font-semibold">#{field.number}</span>
+            <span className="text-muted-foreground">{field.wireType}</span>
+            <span className="break-all">{describeValue(field)}</span>
+            {field.unknown && (
+              <span className="ml-auto shrink-0 rounded bg-amber-500/20 px-1.5 text-[10px] font-semibold uppercase tracking-wide">
+                unknown
+              </span>
+            )}
+          </div>
+          {field.message && field.message.length > 0 && (
+            <div className="ml-4 border-l border-border/40 pl-2">
+              <WireView fields={field.message} />
+            </div>
+          )}
+        </li>
+      ))}
+    </ul>
+  )
+}
@@ -10,6 +10,7 @@ export type ModalType =
   | 'createWorkspace'
   | 'workspaceSettings'
   | 'saveRequest'
+  | 'benchmark'
 
 interface ModalContextType {
   openModal: (type: ModalType, props?: Record<string, any>) => void
@@ -2,7 +2,7 @@ import { useState, useMemo, useCallback, useEffect, type ChangeEvent } from 'rea
 import { flushSync } from 'react-dom'
 import { invoke } from '@tauri-apps/api/core'
 import { listen } from '@tauri-apps/api/event'
-import type { Service, SavedRequest, RequestTab, HistoryEntry, Workspace, VariableContext, StreamMessage } from '../types/workspace'
+import type { Service, SavedRequest, RequestTab, HistoryEntry, Workspace, VariableContext, StreamMessage, RawField, BenchmarkOptions, BenchmarkReport, AssertionResult } from '../types/workspace'
 import {
   saveWorkspace,
   addToHistory as addToHistoryV2,
@@ -50,10 +50,23 @@ export interface UseRequestManagerReturn {
   handleSendStreamMessage: (messageId: string) => Promise<void>
   handleFinishStreaming: () => Promise<void>
 
+  // Benchmark operations
+  handleRunBenchmark: (options: BenchmarkOptions) => Promise<BenchmarkReport | null>
+  handleStopBenchmark: (tabId: string) => Promise<void>
+
   // Sample generation state
   setIsGenerating: React.Dispatch<React.SetStateAction<boolean>>
 }
 
+// Numeric status code for a result's grpc_status: the code the server sent, or for
+// connection errors (reported by category name) the closest code
+function statusCode(grpcStatus: unknown): number {
+  const code = typeof grpcStatus === 'string' && /^\d+$/.test(grpcStatus.trim()) ? parseInt(grpcStatus) : NaN
+  if (!Number.isNaN(code)) return code
+  if (grpcStatus === 'DEADLINE_EXCEEDED') return 4
+  return grpcStatus === 'UNAVAILABLE' ? 14 : 13
+}
+
 /**
  * Custom hook to manage request tabs, gRPC calls, and saved requests
  */
@@ -103,12 +116,19 @@ export function useRequestManager(
 
     const setup = async () => {
       try {
-        const unlisten = await listen<{ tabId: string; index: number; data: any; timestamp: string }>(
+        const unlisten = await listen<{
+          tabId: string
+          index: number
+          data: any
+          timestamp: string
+          raw?: RawField[] | null
+          unknown_fields?: string[]
+        }>(
           'grpc-stream-message',
           (event) => {
             if (!isMounted) return
 
-            const { tabId, index, data, timestamp } = event.payload
+            const { tabId, index, data, timestamp, raw, unknown_fields } = event.payload
 
             flushSync(() => {
               setTabs((prevTabs) =>
@@ -119,6 +139,8 @@ export function useRequestManager(
                       timestamp,
                       data,
                       index,
+                      raw,
+                      unknownFields: unknown_fields,
                     }
                     return {
                       ...tab,
@@ -365,6 +387,9 @@ export function useRequestManager(
           metadata: metadataResult.resolved,
           auth: effectiveAuth,
           tlsConfig: effectiveTls,
+          includeRaw: activeTab.includeRaw,
+          assertions: activeTab.assertions,
+          binMetadataTypes: activeTab.binMetadataTypes,
         })
         
         updateActiveTab({
@@ -382,6 +407,12 @@ export function useRequestManager(
       isLoading: true, 
       response: '', 
       status: null,
+      responseTrailers: {},
+      errorDetails: [],
+      rawResponse: null,
+      unknownFields: [],
+      decodeError: null,
+      assertionResults: undefined,
       isStreaming: isStreamingMethod,
       streamingMessages: [] // Clear previous streaming messages
     })
@@ -466,6 +497,9 @@ export function useRequestManager(
           metadata: metadataResult.resolved,
           auth: effectiveAuth,
           tlsConfig: effectiveTls,
+          assertions: activeTab.assertions,
+          binMetadataTypes: activeTab.binMetadataTypes,
+          includeRaw: activeTab.includeRaw,
         }
       )
 
@@ -478,7 +512,7 @@ export function useRequestManager(
       // Check if this is an error response
       if (result.status === 'error') {
         const errorStatus = { 
-          code: result.grpc_status === 'UNAVAILABLE' ? 14 : 13, 
+          code: statusCode(result.grpc_status), 
           message: result.error || result.grpc_message 
         };
 
@@ -486,10 +520,14 @@ export function useRequestManager(
         updateActiveTab({
           response: result.error || result.grpc_message || 'Unknown error',
           responseMetadata: { 
+            ...(result.response_metadata || {}),
             error_category: result.error_category,
             troubleshooting_hints: result.troubleshooting_hints || []
           },
+          responseTrailers: result.response_trailers || {},
+          errorDetails: result.error_details || [],
           status: errorStatus,
+          assertionResults: result.assertions,
           duration,
           responseSize,
           isLoading: false,
@@ -524,7 +562,12 @@ export function useRequestManager(
       updateActiveTab({
         response: formattedResponse,
         responseMetadata: result.response_metadata || {}, 
-        status: { code: parseInt(result.grpc_status), message: result.grpc_message || 'OK' },
+        responseTrailers: result.response_trailers || {},
+        status: { code: statusCode(result.grpc_status), message: result.grpc_message || 'OK' },
+        rawResponse: result.raw_response ?? null,
+        unknownFields: result.unknown_fields || [],
+        decodeError: result.decode_error ?? null,
+        assertionResults: result.assertions,
         duration,
         responseSize,
         isLoading: false,
@@ -566,17 +609,21 @@ export function useRequestManager(
         const errorJson = JSON.parse(errorMessage)
         if (errorJson.status === 'error') {
           errorStatus = { 
-            code: errorJson.grpc_status === 'UNAVAILABLE' ? 14 : 13, 
+            code: statusCode(errorJson.grpc_status), 
             message: errorJson.error || errorJson.grpc_message 
           }
           // Structured error response
           updateActiveTab({
             response: errorJson.error || errorJson.grpc_message || 'Unknown error',
             responseMetadata: { 
+              ...(errorJson.response_metadata || {}),
               error_category: errorJson.error_category,
               troubleshooting_hints: errorJson.troubleshooting_hints || []
             },
+            responseTrailers: errorJson.response_trailers || {},
+            errorDetails: errorJson.error_details || [],
             status: errorStatus,
+            assertionResults: errorJson.assertions,
             duration,
             responseSize,
             isLoading: false,
@@ -746,6 +793,8 @@ export function useRequestManager(
             requestBody: request.requestBody,
             metadata: request.metadata,
             auth: isHistory ? { type: 'none' } : request.auth,
+            assertions: isHistory ? t.assertions : (request as SavedRequest).assertions,
+            binMetadataTypes: isHistory ? t.binMetadataTypes : (request as SavedRequest).binMetadataTypes,
             // Only restore response details if it's a history item
             response: isHistory ? ((request as HistoryEntry).errorResponse || '') : '',
             responseMetadata: isHistory ? ((request as HistoryEntry).responseMetadata || {}) : {},
@@ -772,6 +821,8 @@ export function useRequestManager(
         requestBody: request.requestBody,
         metadata: request.metadata,
         auth: isHistory ? { type: 'none' } : request.auth,
+        assertions: isHistory ? undefined : (request as SavedRequest).assertions,
+        binMetadataTypes: isHistory ? undefined : (request as SavedRequest).binMetadataTypes,
         response: isHistory ? ((request as HistoryEntry).errorResponse || '') : '',
         responseMetadata: isHistory ? ((request as HistoryEntry).responseMetadata || {}) : {},
         streamingMessages: [],
@@ -1024,17 +1075,90 @@ export function useRequestManager(
         streamConnectionOpen: false,
         isLoading: false,
         response: formattedResponse,
-        status: { code: parseInt(result.grpc_status), message: result.grpc_message || 'OK' },
+        responseMetadata: result.response_metadata || {},
+        responseTrailers: result.response_trailers || {},
+        errorDetails: result.error_details || [],
+        status: { code: statusCode(result.grpc_status), message: result.grpc_message || 'OK' },
+        rawResponse: result.raw_response ?? null,
+        unknownFields: result.unknown_fields || [],
+        decodeError: result.decode_error ?? null,
+        assertionResults: result.assertions,
       })
       
       showToast('Stream closed, response received', 'success')
     } catch (error) {
       console.error('Error finishing stream:', error)
-      updateActiveTab({ isLoading: false })
+      // Connection failures still carry assertion results (a status assertion may expect them)
+      let assertionResults: AssertionResult[] | undefined
+      try {
+        assertionResults = JSON.parse(String(error)).assertions
+      } catch {
+        // Not JSON, nothing was checked
+      }
+      updateActiveTab({ isLoading: false, assertionResults })
       showToast(`Error closing stream: ${error}`, 'error')
     }
   }, [activeTab, updateActiveTab, showToast])
 
+  const handleRunBenchmark = useCallback(async (options: BenchmarkOptions): Promise<BenchmarkReport | null> => {
+    if (!activeTab || !activeTab.service || !activeTab.method) return null
+
+    const selectedEnv = workspace.environments.find(env => env.id === activeTab.selectedEnvironmentId)
+    const host = activeTab.requestHost || selectedEnv?.host || 'localhost'
+    const port = activeTab.requestPort || selectedEnv?.port || 50051
+    const variableContext: VariableContext = {
+      environmentVariables: selectedEnv?.variables || [],
+      globalVariables: workspace.globals || [],
+    }
+
+    // Client streams send every message of the tab per call, as a JSON array
+    const isClientStreamingMethod = activeTab.methodType === 'client_streaming' || activeTab.methodType === 'bidirectional_streaming'
+    const requestBody = isClientStreamingMethod
+      ? `[${(activeTab.clientStreamingMessages || []).map(m => m.body).join(',')}]`
+      : activeTab.requestBody
+
+    // {{iteration.*}} placeholders are left alone here; the backend fills them in per call
+    const bodyResult = resolveVariables(requestBody, variableContext)
+    const metadataResult = resolveMetadataVariables({
+      ...(activeTab.disableEnvironmentMetadata ? {} : (selectedEnv?.metadata || {})),
+      ...(activeTab.metadata || {}),
+    }, variableContext)
+
+    const allUnresolved = [...bodyResult.unresolvedVars, ...metadataResult.unresolved]
+    if (allUnresolved.length > 0) {
+      const unresolvedList = allUnresolved.map(v => v.placeholder).join(', ')
+      showToast(`Warning: Unresolved variables: ${unresolvedList}`, 'info')
+    }
+
+    try {
+      const reportString = await invoke<string>('run_benchmark', {
+        tabId: activeTab.id,
+        service: activeTab.service,
+        method: activeTab.method,
+        requestData: bodyResult.resolved,
+        endpoint: `http://${host}:${port}`,
+        protoContent: protoContent || undefined,
+        importPaths: protoContent ? undefined : workspace.importPaths.filter(ip => ip.enabled),
+        metadata: metadataResult.resolved,
+        auth: activeTab.auth.type !== 'none' ? activeTab.auth : (selectedEnv?.auth || { type: 'none' }),
+        tlsConfig: activeTab.tls || selectedEnv?.tls || { enabled: false },
+        benchmark: options,
+      })
+      return JSON.parse(reportString) as BenchmarkReport
+    } catch (error) {
+      showToast(`Benchmark failed: ${error}`, 'error')
+      return null
+    }
+  }, [activeTab, workspace, protoContent, showToast])
+
+  const handleStopBenchmark = useCallback(async (tabId: string) => {
+    try {
+      await invoke('stop_benchmark', { tabId })
+    } catch (error) {
+      showToast(`Failed to stop benchmark: ${error}`, 'error')
+    }
+  }, [showToast])
+
   return {
     // State
     services,
@@ -1071,6 +1195,10 @@ export function useRequestManager(
     handleSendStreamMessage,
     handleFinishStreaming,
 
+    // Benchmark operations
+    handleRunBenchmark,
+    handleStopBenchmark,
+
     // Sample generation state
     setIsGenerating,
   }
@@ -535,6 +535,8 @@ export function createSavedRequest(
     requestBody: tab.requestBody,
     metadata: tab.metadata,
     auth: tab.auth,
+    assertions: tab.assertions,
+    binMetadataTypes: tab.binMetadataTypes,
     createdAt: now,
     updatedAt: now,
   }
@@ -123,10 +123,32 @@ export interface SavedRequest {
   requestBody: string  // JSON string
   metadata: Record<string, string>
   auth: AuthConfig  // Can override environment auth
+  assertions?: Assertion[]  // Checked after each call and by the collection runner
+  binMetadataTypes?: Record<string, string>  // Message type to decode each -bin response header as
   createdAt: string
   updatedAt: string
 }
 
+// Paths address the response body: `$` is the message, or the array of messages for streams
+export type Assertion =
+  | { type: 'status'; code: number | string }  // 5 or 'NOT_FOUND'
+  | { type: 'jsonPath'; path: string; equals: unknown }
+  // JMESPath subset: no slices, multi-select lists/hashes or &expr; functions: length, contains,
+  // starts_with, ends_with, keys, values, type, to_string, to_number
+  | { type: 'jmesPath'; expression: string; equals?: unknown }  // Without equals: must be truthy
+  | { type: 'regex'; path?: string; pattern: string }
+  | { type: 'header'; name: string; equals?: string; pattern?: string }
+  | { type: 'trailer'; name: string; equals?: string; pattern?: string }
+  | { type: 'messageCount'; min?: number; max?: number }
+  | { type: 'latency'; maxMs: number }
+
+// Returned in the call result as `assertions`, alongside `assertions_passed`
+export interface AssertionResult {
+  assertion: Assertion
+  passed: boolean
+  message: string
+}
+
 // ============================================================================
 // Request Tabs (Active Requests in Editor)
 // ============================================================================
@@ -145,6 +167,9 @@ export interface RequestTab {
   disableEnvironmentMetadata?: boolean  // Don't inherit metadata from environment
   auth: AuthConfig  // Can override environment auth
   tls?: TlsConfig  // Can override environment TLS config
+  assertions?: Assertion[]  // Checked against the response after the call
+  includeRaw?: boolean  // Ask for the schema-less wire view of every response message
+  binMetadataTypes?: Record<string, string>  // Message type to decode each -bin response header as
   
   // Environment and endpoint configuration (tab-level)
   selectedEnvironmentId?: string | null  // Which environment this tab uses
@@ -153,11 +178,17 @@ export interface RequestTab {
   
   // Response data
   response: string
-  responseMetadata: Record<string, any>  // gRPC metadata/trailers and structured error hints
+  responseMetadata: Record<string, any>  // gRPC response headers and structured error hints
+  responseTrailers?: Record<string, any>  // gRPC trailers, sent after the last message
+  errorDetails?: Record<string, any>[]  // Decoded grpc-status-details-bin entries, each with its "@type"
   streamingMessages: StreamMessage[]
   status: GrpcStatus | null
   duration: number | null  // ms
   responseSize: number | null  // bytes
+  rawResponse?: RawField[] | RawField[][] | null  // Wire view: one tree, or one per message for streams
+  unknownFields?: string[]  // Paths of fields the descriptor doesn't define, e.g. "items[2].9"
+  decodeError?: string | null  // Set when the response didn't decode against the descriptor
+  assertionResults?: AssertionResult[]  // Results of `assertions` for the last call
   
   // UI state
   isStreaming: boolean
@@ -182,6 +213,60 @@ export interface StreamMessage {
   data: any  // Parsed JSON
   index: number  // Message number in stream
   size?: number  // bytes
+  raw?: RawField[] | null  // Wire view, when requested or when decoding failed
+  unknownFields?: string[]
+}
+
+// One field as it appears on the wire, decoded without a schema
+export interface RawField {
+  number: number
+  wireType: 'varint' | 'fixed64' | 'len' | 'group' | 'fixed32'
+  unknown: boolean  // Not defined by the descriptor used to decode the message
+  value: Record<string, unknown>  // Readings of the value: uint/int/sint, float/double, length/hex/string
+  message: RawField[] | null  // Group fields, or a length-delimited value that parses as a message
+}
+
+// ============================================================================
+// Benchmark (run_benchmark / stop_benchmark)
+// ============================================================================
+
+// The body and metadata may use {{iteration.index}}, {{iteration.worker}},
+// {{iteration.uuid}}, {{iteration.random}} and {{iteration.timestamp}}
+export interface BenchmarkOptions {
+  concurrency?: number  // Calls in flight at once, default 1
+  total?: number  // Stop after this many calls
+  durationMs?: number  // Stop starting calls after this long
+}
+
+export interface LatencySummary {
+  minMs: number
+  meanMs: number
+  p50Ms: number
+  p90Ms: number
+  p99Ms: number
+  maxMs: number
+}
+
+// Payload of the "benchmark-progress" event: { tabId, progress }
+export interface BenchmarkProgress {
+  completed: number
+  errors: number
+  elapsedMs: number
+  requestsPerSecond: number
+  latency: LatencySummary
+}
+
+export interface BenchmarkReport {
+  count: number
+  ok: number
+  errors: number
+  totalMs: number
+  requestsPerSecond: number
+  latency: LatencySummary
+  histogram: Array<{ upToMs: number; count: number }>
+  statusCodes: Record<string, number>  // e.g. { OK: 990, UNAVAILABLE: 10 }
+  errorSamples: Array<{ index: number; status: string; message: string }>
+  stopped: boolean  // Stopped early with stop_benchmark
 }
 
 // ============================================================================
@@ -256,6 +341,7 @@ export interface VariableContext {
 export interface ProtoParseResult {
   success: boolean
   services: Service[]
+  documentation: Record<string, string>  // Comments keyed by full name (pkg.Msg, pkg.Msg.field, pkg.Enum.VALUE)
   errors: ProtoParseError[]
   warnings: string[]
 }
@@ -0,0 +1,238 @@
+use base64::{engine::general_purpose, Engine as _};
+use prost::Message as ProstMessage;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, ReflectMessage, SerializeOptions};
+use serde_json::Value;
+
+const ANY_TYPE: &str = "google.protobuf.Any";
+
+/// Type URL prefix of the placeholders that stand in for unresolved `Any` values
+/// while a message is serialized. The `.invalid` domain can't clash with real URLs.
+const PLACEHOLDER_PREFIX: &str = "unresolved.grpcpeek.invalid/";
+
+/// JSON for an `Any` whose type isn't known: the type URL and the base64 payload.
+pub fn unresolved(type_url: &str, value: &[u8]) -> Value {
+    serde_json::json!({
+        "@type": type_url,
+        "value": general_purpose::STANDARD.encode(value),
+        "unresolved": true,
+    })
+}
+
+/// Serialize a message as proto3 JSON. `Any` values are resolved against the
+/// message's pool; those whose type it doesn't define are rendered by `unresolved`
+/// rather than failing the whole message.
+pub fn to_json(msg: &DynamicMessage, options: &SerializeOptions) -> Result<Value, String> {
+    if msg.descriptor().full_name() == ANY_TYPE {
+        if let Ok(any) = msg.transcode_to::<prost_types::Any>() {
+            if payload(&any, msg.descriptor().parent_pool()).is_none() {
+                return Ok(unresolved(&any.type_url, &any.value));
+            }
+        }
+    }
+
+    let mut msg = msg.clone();
+    let mut detached = Vec::new();
+    let root = msg.descriptor();
+    detach_unresolved(&mut msg, &root, &mut detached);
+
+    let mut json = msg
+        .serialize_with_options(serde_json::value::Serializer, options)
+        .map_err(|e| format!("Failed to serialize response: {}", e))?;
+    if !detached.is_empty() {
+        reattach(&mut json, &mut detached);
+    }
+    Ok(json)
+}
+
+/// Swap every unresolved `Any` under `msg` for a placeholder naming its index in
+/// `detached`, descending into the payloads of resolved ones at any depth. `parent`
+/// is the nearest enclosing message, which the placeholders pack.
+fn detach_unresolved(msg: &mut DynamicMessage, parent: &MessageDescriptor, detached: &mut Vec<Value>) {
+    if msg.descriptor().full_name() == ANY_TYPE {
+        detach_in_payload(msg, parent, detached);
+        return;
+    }
+    let parent = msg.descriptor();
+    for (_, value) in msg.fields_mut() {
+        match value {
+            prost_reflect::Value::Message(m) => detach_value(m, &parent, detached),
+            prost_reflect::Value::List(items) => {
+                for item in items {
+                    if let prost_reflect::Value::Message(m) = item {
+                        detach_value(m, &parent, detached);
+                    }
+                }
+            }
+            prost_reflect::Value::Map(entries) => {
+                for entry in entries.values_mut() {
+                    if let prost_reflect::Value::Message(m) = entry {
+                        detach_value(m, &parent, detached);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn detach_value(msg: &mut DynamicMessage, parent: &MessageDescriptor, detached: &mut Vec<Value>) {
+    if msg.descriptor().full_name() == ANY_TYPE {
+        if let Ok(any) = msg.transcode_to::<prost_types::Any>() {
+            if payload(&any, msg.descriptor().parent_pool()).is_none() {
+                let Some(target) = placeholder_type(parent) else {
+                    return;
+                };
+                // The placeholder packs an empty instance of `target`, which is in the
+                // pool and serializes without error.
+                let placeholder = format!("{}{}/{}", PLACEHOLDER_PREFIX, detached.len(), target.full_name());
+                detached.push(unresolved(&any.type_url, &any.value));
+                msg.clear();
+                msg.set_field_by_name("type_url", prost_reflect::Value::String(placeholder));
+                return;
+            }
+        }
+    }
+    detach_unresolved(msg, parent, detached);
+}
+
+/// Detach the unresolved `Any` values inside a resolved `Any`'s payload, which may
+/// itself be an `Any`, and re-pack the payload if any were found.
+fn detach_in_payload(msg: &mut DynamicMessage, parent: &MessageDescriptor, detached: &mut Vec<Value>) {
+    let Ok(any) = msg.transcode_to::<prost_types::Any>() else {
+        return;
+    };
+    let Some(mut payload) = payload(&any, msg.descriptor().parent_pool()) else {
+        return;
+    };
+    let before = detached.len();
+    detach_value(&mut payload, parent, detached);
+    if detached.len() > before {
+        msg.set_field_by_name("value", prost_reflect::Value::Bytes(payload.encode_to_vec().into()));
+    }
+}
+
+/// The message a placeholder packs: `parent`, or when that is an `Any` itself (an
+/// `Any` packed straight into another), some other message in the pool.
+fn placeholder_type(parent: &MessageDescriptor) -> Option<MessageDescriptor> {
+    if parent.full_name() != ANY_TYPE {
+        return Some(parent.clone());
+    }
+    parent
+        .parent_pool()
+        .all_messages()
+        .find(|m| !m.full_name().starts_with("google.protobuf.") && !m.is_map_entry())
+}
+
+/// Decode an `Any` payload, if its type is in `pool`.
+fn payload(any: &prost_types::Any, pool: &DescriptorPool) -> Option<DynamicMessage> {
+    let (_, name) = any.type_url.rsplit_once('/')?;
+    let desc = pool.get_message_by_name(name)?;
+    DynamicMessage::decode(desc, any.value.as_slice()).ok()
+}
+
+fn reattach(json: &mut Value, detached: &mut [Value]) {
+    match json {
+        Value::Object(obj) => {
+            let index = obj
+                .get("@type")
+                .and_then(Value::as_str)
+                .and_then(|t| t.strip_prefix(PLACEHOLDER_PREFIX))
+                .and_then(|rest| rest.split('/').next())
+                .and_then(|i| i.parse::<usize>().ok());
+            match index.and_then(|i| detached.get_mut(i)) {
+                Some(original) => *json = std::mem::take(original),
+                None => obj.values_mut().for_each(|v| reattach(v, detached)),
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| reattach(v, detached)),
+        _ => {}
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto_parser::compile_single_file;
+
+    #[test]
+    fn test_unresolved_any_renders_as_base64() {
+        let proto = r#"
+syntax = "proto3";
+package any.v1;
+import "google/protobuf/any.proto";
+service Svc { rpc Do (Envelope) returns (Envelope); }
+message Known { string name = 1; }
+message Envelope { google.protobuf.Any first = 1; repeated google.protobuf.Any rest = 2; }
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let desc = pool.get_message_by_name("any.v1.Envelope").unwrap();
+
+        let known = prost_types::Any {
+            type_url: "type.googleapis.com/any.v1.Known".to_string(),
+            value: vec![0x0a, 0x02, b'h', b'i'],
+        };
+        let missing = prost_types::Any {
+            type_url: "type.googleapis.com/other.v1.Missing".to_string(),
+            value: vec![0x08, 0x01],
+        };
+        let mut envelope = DynamicMessage::new(desc);
+        let any_desc = pool.get_message_by_name(ANY_TYPE).unwrap();
+        let to_value = |any: &prost_types::Any| {
+            let mut m = DynamicMessage::new(any_desc.clone());
+            m.transcode_from(any).unwrap();
+            prost_reflect::Value::Message(m)
+        };
+        envelope.set_field_by_name("first", to_value(&missing));
+        envelope.set_field_by_name("rest", prost_reflect::Value::List(vec![to_value(&known), to_value(&missing)]));
+
+        let json = to_json(&envelope, &SerializeOptions::new()).unwrap();
+        assert_eq!(json["first"], unresolved(&missing.type_url, &missing.value));
+        assert_eq!(json["rest"][0], serde_json::json!({"@type": known.type_url, "name": "hi"}));
+        assert_eq!(json["rest"][1]["value"], "CAE=");
+        assert_eq!(json["rest"][1]["unresolved"], true);
+    }
+
+    #[test]
+    fn test_unresolved_any_nested_in_any() {
+        let proto = r#"
+syntax = "proto3";
+package any.v1;
+import "google/protobuf/any.proto";
+service Svc { rpc Do (Wrapper) returns (Wrapper); }
+message Wrapper { google.protobuf.Any inner = 1; }
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let wrapper_desc = pool.get_message_by_name("any.v1.Wrapper").unwrap();
+        let any_desc = pool.get_message_by_name(ANY_TYPE).unwrap();
+        let pack = |type_url: &str, value: Vec<u8>| {
+            let mut m = DynamicMessage::new(any_desc.clone());
+            m.transcode_from(&prost_types::Any { type_url: type_url.to_string(), value }).unwrap();
+            m
+        };
+        let missing = pack("type.googleapis.com/other.v1.Missing", vec![0x08, 0x01]);
+        let expected = unresolved("type.googleapis.com/other.v1.Missing", &[0x08, 0x01]);
+
+        // Any -> Wrapper -> unresolved Any
+        let mut wrapper = DynamicMessage::new(wrapper_desc);
+        wrapper.set_field_by_name("inner", prost_reflect::Value::Message(missing.clone()));
+        let top = pack("type.googleapis.com/any.v1.Wrapper", wrapper.encode_to_vec());
+        let json = to_json(&top, &SerializeOptions::new()).unwrap();
+        assert_eq!(json["@type"], "type.googleapis.com/any.v1.Wrapper");
+        assert_eq!(json["inner"], expected);
+
+        // Any -> Any -> unresolved, at the top level and inside a field
+        let top = pack("type.googleapis.com/google.protobuf.Any", missing.encode_to_vec());
+        let json = to_json(&top, &SerializeOptions::new()).unwrap();
+        assert_eq!(json["@type"], "type.googleapis.com/google.protobuf.Any");
+        assert_eq!(json["value"], expected);
+
+        let mut outer = DynamicMessage::new(pool.get_message_by_name("any.v1.Wrapper").unwrap());
+        outer.set_field_by_name("inner", prost_reflect::Value::Message(top));
+        let json = to_json(&outer, &SerializeOptions::new()).unwrap();
+        assert_eq!(json["inner"]["value"], expected);
+    }
+}
@@ -0,0 +1,389 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// gRPC status code names, indexed by code.
+const STATUS_NAMES: [&str; 17] = [
+    "OK",
+    "CANCELLED",
+    "UNKNOWN",
+    "INVALID_ARGUMENT",
+    "DEADLINE_EXCEEDED",
+    "NOT_FOUND",
+    "ALREADY_EXISTS",
+    "PERMISSION_DENIED",
+    "RESOURCE_EXHAUSTED",
+    "FAILED_PRECONDITION",
+    "ABORTED",
+    "OUT_OF_RANGE",
+    "UNIMPLEMENTED",
+    "INTERNAL",
+    "UNAVAILABLE",
+    "DATA_LOSS",
+    "UNAUTHENTICATED",
+];
+
+/// A check run against a call result. Paths and expressions address the response
+/// body: `$` / `@` is the response message, or the array of messages for streaming
+/// calls.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Assertion {
+    /// The call ends with this gRPC status, as a code (`5`) or name (`"NOT_FOUND"`).
+    Status { code: Value },
+    /// The value at a JSONPath equals `equals`. Paths with wildcards select an array
+    /// of every match.
+    JsonPath { path: String, equals: Value },
+    /// A JMESPath expression equals `equals` or, without it, is truthy
+    /// (e.g. `length(items) > \`0\``).
+    JmesPath { expression: String, equals: Option<Value> },
+    /// The value at `path` (strings as-is, anything else as JSON), or the whole
+    /// response when there's no path, matches a regular expression.
+    Regex { path: Option<String>, pattern: String },
+    /// A response header is present and, if given, equals `equals` and matches
+    /// `pattern`. `-bin` values are compared as base64.
+    Header { name: String, equals: Option<String>, pattern: Option<String> },
+    /// As `Header`, for a trailer.
+    Trailer { name: String, equals: Option<String>, pattern: Option<String> },
+    /// The number of response messages is within the bounds.
+    MessageCount { min: Option<u64>, max: Option<u64> },
+    /// The call completes within `maxMs` milliseconds, by its timing.
+    #[serde(rename_all = "camelCase")]
+    Latency { max_ms: f64 },
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionResult {
+    pub assertion: Assertion,
+    pub passed: bool,
+    /// What was expected and what was found.
+    pub message: String,
+}
+
+/// Evaluate assertions against a call result (the JSON `call_grpc_method` returns).
+pub fn evaluate(assertions: &[Assertion], result: &Value) -> Vec<AssertionResult> {
+    assertions
+        .iter()
+        .map(|assertion| {
+            let (passed, message) = match check(assertion, result) {
+                Ok(message) => (true, message),
+                Err(message) => (false, message),
+            };
+            AssertionResult { assertion: assertion.clone(), passed, message }
+        })
+        .collect()
+}
+
+/// Evaluate assertions and add them to the result as `assertions`, with
+/// `assertions_passed` set when all of them pass.
+pub fn attach(result: &mut Value, assertions: &[Assertion]) {
+    let results = evaluate(assertions, result);
+    result["assertions_passed"] = Value::Bool(results.iter().all(|r| r.passed));
+    result["assertions"] = serde_json::to_value(results).unwrap_or_default();
+}
+
+fn check(assertion: &Assertion, result: &Value) -> Result<String, String> {
+    let response = &result["response"];
+    match assertion {
+        Assertion::Status { code } => {
+            let expected = status_code(code).ok_or_else(|| format!("Unknown gRPC status {}", code))?;
+            let actual = status_code(&result["grpc_status"]);
+            if actual == Some(expected) {
+                Ok(format!("status is {}", status_label(expected)))
+            } else {
+                Err(format!(
+                    "expected status {}, got {}",
+                    status_label(expected),
+                    actual.map(status_label).unwrap_or_else(|| result["grpc_status"].to_string())
+                ))
+            }
+        }
+        Assertion::JsonPath { path, equals } => {
+            let actual = select_one(response, path)?;
+            if actual == *equals {
+                Ok(format!("{} is {}", path, equals))
+            } else {
+                Err(format!("expected {} to be {}, got {}", path, equals, actual))
+            }
+        }
+        Assertion::JmesPath { expression, equals } => {
+            let actual = crate::jmespath::search(expression, response)?;
+            match equals {
+                Some(expected) if actual == *expected => Ok(format!("{} is {}", expression, expected)),
+                Some(expected) => Err(format!("expected {} to be {}, got {}", expression, expected, actual)),
+                None if crate::jmespath::is_truthy(&actual) => Ok(format!("{} holds", expression)),
+                None => Err(format!("expected {} to hold, got {}", expression, actual)),
+            }
+        }
+        Assertion::Regex { path, pattern } => {
+            let re = Regex::new(pattern).map_err(|e| format!("Invalid regex '{}': {}", pattern, e))?;
+            let value = match path {
+                Some(path) => select_one(response, path)?,
+                None => response.clone(),
+            };
+            let text = match &value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            let target = path.as_deref().unwrap_or("response");
+            if re.is_match(&text) {
+                Ok(format!("{} matches /{}/", target, pattern))
+            } else {
+                Err(format!("expected {} to match /{}/, got {}", target, pattern, value))
+            }
+        }
+        Assertion::Header { name, equals, pattern } => {
+            check_metadata("header", &result["response_metadata"], name, equals.as_deref(), pattern.as_deref())
+        }
+        Assertion::Trailer { name, equals, pattern } => {
+            check_metadata("trailer", &result["response_trailers"], name, equals.as_deref(), pattern.as_deref())
+        }
+        Assertion::MessageCount { min, max } => {
+            let count = message_count(result);
+            let bounds = match (min, max) {
+                (Some(min), Some(max)) => format!("between {} and {}", min, max),
+                (Some(min), None) => format!("at least {}", min),
+                (None, Some(max)) => format!("at most {}", max),
+                (None, None) => "any number of".to_string(),
+            };
+            if min.is_some_and(|min| count < min) || max.is_some_and(|max| count > max) {
+                Err(format!("expected {} messages, got {}", bounds, count))
+            } else {
+                Ok(format!("{} messages ({})", count, bounds))
+            }
+        }
+        Assertion::Latency { max_ms } => {
+            let elapsed_ms = result["timing"]["total_ms"]
+                .as_f64()
+                .ok_or_else(|| "the call has no timing; it failed before a response".to_string())?;
+            if elapsed_ms <= *max_ms {
+                Ok(format!("took {:.1} ms (limit {} ms)", elapsed_ms, max_ms))
+            } else {
+                Err(format!("took {:.1} ms, over the {} ms limit", elapsed_ms, max_ms))
+            }
+        }
+    }
+}
+
+fn check_metadata(
+    kind: &str,
+    map: &Value,
+    name: &str,
+    equals: Option<&str>,
+    pattern: Option<&str>,
+) -> Result<String, String> {
+    let value = map
+        .as_object()
+        .and_then(|m| m.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)))
+        .map(|(_, v)| metadata_text(v))
+        .ok_or_else(|| format!("{} '{}' is missing", kind, name))?;
+    if let Some(expected) = equals.filter(|e| *e != value) {
+        return Err(format!("expected {} '{}' to be \"{}\", got \"{}\"", kind, name, expected, value));
+    }
+    if let Some(pattern) = pattern {
+        let re = Regex::new(pattern).map_err(|e| format!("Invalid regex '{}': {}", pattern, e))?;
+        if !re.is_match(&value) {
+            return Err(format!("expected {} '{}' to match /{}/, got \"{}\"", kind, name, pattern, value));
+        }
+    }
+    Ok(format!("{} '{}' is \"{}\"", kind, name, value))
+}
+
+/// Text of a rendered metadata value (see `metadata::to_json`): strings as-is,
+/// binary values as base64, repeated binary values joined with ", ".
+fn metadata_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(metadata_text).collect::<Vec<_>>().join(", "),
+        Value::Object(obj) => obj
+            .get("base64")
+            .or_else(|| obj.get("raw"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Messages received: `message_count` when the result has it, else one for a
+/// response and none without.
+fn message_count(result: &Value) -> u64 {
+    result["message_count"].as_u64().unwrap_or_else(|| match &result["response"] {
+        Value::Null => 0,
+        Value::Array(items) if result["is_streaming"] == true => items.len() as u64,
+        _ => 1,
+    })
+}
+
+/// Parse a status given as a code or a name, in any case.
+pub fn status_code(value: &Value) -> Option<usize> {
+    match value {
+        Value::Number(n) => n.as_u64().map(|n| n as usize).filter(|&n| n < STATUS_NAMES.len()),
+        Value::String(s) => {
+            let s = s.trim();
+            s.parse::<usize>()
+                .ok()
+                .filter(|&n| n < STATUS_NAMES.len())
+                .or_else(|| STATUS_NAMES.iter().position(|name| name.eq_ignore_ascii_case(s)))
+        }
+        _ => None,
+    }
+}
+
+/// The name of a status code, e.g. `NOT_FOUND` for 5.
+pub fn status_name(code: usize) -> &'static str {
+    STATUS_NAMES.get(code).copied().unwrap_or("UNKNOWN")
+}
+
+fn status_label(code: usize) -> String {
+    format!("{} ({})", STATUS_NAMES[code], code)
+}
+
+// ---------------------------------------------------------------------------
+// JSONPath
+// ---------------------------------------------------------------------------
+
+/// The subset of JSONPath used by assertions: `$`, `.name`, `['name']`, `[0]`,
+/// `[-1]` and the `*` / `[*]` wildcard.
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Wildcard,
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    let invalid = |why: &str| format!("Invalid JSONPath '{}': {}", path, why);
+    let rest = path.trim();
+    let mut rest = rest.strip_prefix('$').unwrap_or(rest);
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let name = &after[..end];
+            segments.push(match name {
+                "" => return Err(invalid("empty name")),
+                "*" => Segment::Wildcard,
+                name => Segment::Key(name.to_string()),
+            });
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(|| invalid("unclosed '['"))?;
+            let inner = after[..end].trim();
+            segments.push(if inner == "*" {
+                Segment::Wildcard
+            } else if let Some(quoted) = inner
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+            {
+                Segment::Key(quoted.to_string())
+            } else {
+                Segment::Index(inner.parse().map_err(|_| invalid("index must be a number"))?)
+            });
+            rest = &after[end + 1..];
+        } else if segments.is_empty() && !path.trim().starts_with('$') {
+            // A bare leading name (`user.id`) is read as `$.user.id`.
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            segments.push(Segment::Key(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            return Err(invalid("expected '.' or '['"));
+        }
+    }
+    Ok(segments)
+}
+
+/// Every value the path selects.
+pub fn select<'a>(root: &'a Value, path: &str) -> Result<Vec<&'a Value>, String> {
+    let mut current = vec![root];
+    for segment in parse_path(path)? {
+        current = current
+            .into_iter()
+            .flat_map(|value| -> Vec<&Value> {
+                match (&segment, value) {
+                    (Segment::Key(key), Value::Object(obj)) => obj.get(key).into_iter().collect(),
+                    (Segment::Index(i), Value::Array(items)) => {
+                        let i = if *i < 0 { items.len() as i64 + i } else { *i };
+                        usize::try_from(i).ok().and_then(|i| items.get(i)).into_iter().collect()
+                    }
+                    (Segment::Wildcard, Value::Array(items)) => items.iter().collect(),
+                    (Segment::Wildcard, Value::Object(obj)) => obj.values().collect(),
+                    _ => Vec::new(),
+                }
+            })
+            .collect();
+    }
+    Ok(current)
+}
+
+/// The value a path selects: the single match, an array of matches for wildcard
+/// paths, or an error when nothing matches.
+fn select_one(root: &Value, path: &str) -> Result<Value, String> {
+    let wildcard = parse_path(path)?.contains(&Segment::Wildcard);
+    let matches = select(root, path)?;
+    match matches.as_slice() {
+        _ if wildcard => Ok(Value::Array(matches.into_iter().cloned().collect())),
+        [] => Err(format!("{} matched nothing in the response", path)),
+        [value] => Ok((*value).clone()),
+        _ => Ok(Value::Array(matches.into_iter().cloned().collect())),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_evaluate_assertions() {
+        let result = json!({
+            "grpc_status": "5",
+            "response": {"user": {"id": "42", "tags": ["a", "b"]}, "items": [{"n": 1}, {"n": 2}]},
+            "message_count": 1,
+            "response_metadata": {"content-type": "application/grpc", "trace-bin": {"base64": "AQI=", "hex": "0102"}},
+            "response_trailers": {"grpc-status": "5"},
+            "timing": {"total_ms": 150.0},
+        });
+        let assertions: Vec<Assertion> = serde_json::from_value(json!([
+            {"type": "status", "code": "NOT_FOUND"},
+            {"type": "status", "code": 0},
+            {"type": "jsonPath", "path": "$.user.id", "equals": "42"},
+            {"type": "jsonPath", "path": "$.items[*].n", "equals": [1, 2]},
+            {"type": "jsonPath", "path": "user['tags'][-1]", "equals": "b"},
+            {"type": "jsonPath", "path": "$.missing", "equals": null},
+            {"type": "regex", "path": "$.user.id", "pattern": "^\\d+$"},
+            {"type": "latency", "maxMs": 100},
+            {"type": "jmesPath", "expression": "items[?n > `1`].n", "equals": [2]},
+            {"type": "jmesPath", "expression": "length(user.tags) == `3`"},
+            {"type": "header", "name": "Content-Type", "pattern": "^application/grpc"},
+            {"type": "header", "name": "trace-bin", "equals": "AQI="},
+            {"type": "trailer", "name": "grpc-message"},
+            {"type": "messageCount", "min": 1, "max": 1},
+            {"type": "messageCount", "min": 2},
+        ]))
+        .unwrap();
+
+        let passed: Vec<bool> = evaluate(&assertions, &result).iter().map(|r| r.passed).collect();
+        assert_eq!(
+            passed,
+            [true, false, true, true, true, false, true, false, true, false, true, true, false, true, false]
+        );
+
+        let failed = &evaluate(&assertions[1..2], &result)[0];
+        assert_eq!(failed.message, "expected status OK (0), got NOT_FOUND (5)");
+        assert_eq!(status_code(&json!("unavailable")), Some(14));
+        assert!(parse_path("$.a[").is_err());
+
+        let mut result = result;
+        attach(&mut result, &assertions[..1]);
+        assert_eq!(result["assertions_passed"], true);
+        assert_eq!(result["assertions"][0]["message"], "status is NOT_FOUND (5)");
+    }
+}
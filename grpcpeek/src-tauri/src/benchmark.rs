@@ -0,0 +1,618 @@
+use crate::assertions;
+use crate::connector::Connector;
+use crate::engine::{self, Call};
+use crate::message_format::MessageFormat;
+use hyper::{Body, Client};
+use lazy_static::lazy_static;
+use rand::Rng;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// How often `Benchmark::run` reports progress.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Distinct errors kept in a report.
+const MAX_ERROR_SAMPLES: usize = 10;
+
+/// Buckets in a report's latency histogram, evenly spaced between min and max.
+const HISTOGRAM_BUCKETS: usize = 10;
+
+/// Names accepted in `{{iteration.name}}` placeholders.
+const VARIABLES: [&str; 5] = ["index", "worker", "uuid", "random", "timestamp"];
+
+lazy_static! {
+    static ref ITERATION_PATTERN: Regex = Regex::new(r"\{\{iteration\.([a-zA-Z0-9_]+)\}\}").unwrap();
+}
+
+// ---------------------------------------------------------------------------
+// Options and reports
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkOptions {
+    /// Calls kept in flight at once. Defaults to 1.
+    pub concurrency: Option<usize>,
+    /// Stop after this many calls.
+    pub total: Option<u64>,
+    /// Stop starting calls after this long. With `total` too, whichever comes first.
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencySummary {
+    pub min_ms: f64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    /// Upper bound of the bucket; the lower bound is the previous bucket's.
+    pub up_to_ms: f64,
+    pub count: u64,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorSample {
+    /// The iteration that failed, as in `{{iteration.index}}`.
+    pub index: u64,
+    pub status: String,
+    pub message: String,
+}
+
+/// Sent while a benchmark runs.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkProgress {
+    pub completed: u64,
+    pub errors: u64,
+    pub elapsed_ms: f64,
+    pub requests_per_second: f64,
+    pub latency: LatencySummary,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkReport {
+    pub count: u64,
+    pub ok: u64,
+    pub errors: u64,
+    pub total_ms: f64,
+    pub requests_per_second: f64,
+    pub latency: LatencySummary,
+    pub histogram: Vec<HistogramBucket>,
+    /// Calls per status name, e.g. `{"OK": 990, "UNAVAILABLE": 10}`.
+    pub status_codes: BTreeMap<String, u64>,
+    pub error_samples: Vec<ErrorSample>,
+    /// Stopped before reaching its count or duration.
+    pub stopped: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Iteration variables
+// ---------------------------------------------------------------------------
+
+/// Values for the `{{iteration.*}}` placeholders of one call. Environment and global
+/// variables are resolved before the benchmark starts; these change per call.
+struct Iteration {
+    index: u64,
+    worker: usize,
+}
+
+impl Iteration {
+    fn render(&self, text: &str) -> String {
+        ITERATION_PATTERN
+            .replace_all(text, |caps: &regex::Captures| match &caps[1] {
+                "index" => self.index.to_string(),
+                "worker" => self.worker.to_string(),
+                "uuid" => uuid::Uuid::new_v4().to_string(),
+                // Non-negative and small enough for an int32 field.
+                "random" => rand::thread_rng().gen_range(0..=i32::MAX).to_string(),
+                "timestamp" => chrono::Utc::now().timestamp_millis().to_string(),
+                _ => caps[0].to_string(),
+            })
+            .into_owned()
+    }
+}
+
+fn check_placeholders(text: &str) -> Result<(), String> {
+    match ITERATION_PATTERN
+        .captures_iter(text)
+        .find(|caps| !VARIABLES.contains(&&caps[1]))
+    {
+        Some(caps) => Err(format!(
+            "Unknown variable '{}'. Iteration variables: {}",
+            &caps[0],
+            VARIABLES.join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Running
+// ---------------------------------------------------------------------------
+
+/// A method called repeatedly by concurrent workers over one shared client.
+pub struct Benchmark {
+    call: Call,
+    client: Client<Connector, Body>,
+    body: String,
+    request_format: MessageFormat,
+    concurrency: usize,
+    options: BenchmarkOptions,
+    /// The encoded request frames, built once when the body has no placeholders.
+    fixed: Option<Vec<Vec<u8>>>,
+    /// Whether the call's metadata has placeholders to fill in per call.
+    templated_metadata: bool,
+    stop: Arc<AtomicBool>,
+}
+
+impl Benchmark {
+    /// Check the options and the request template. The first iteration's request is
+    /// decoded here so a bad body fails before any call is made.
+    pub fn new(
+        call: Call,
+        client: Client<Connector, Body>,
+        body: &str,
+        request_format: MessageFormat,
+        options: BenchmarkOptions,
+    ) -> Result<Self, String> {
+        if options.total.is_none() && options.duration_ms.is_none() {
+            return Err("A benchmark needs a total call count or a duration".to_string());
+        }
+        let concurrency = options.concurrency.unwrap_or(1);
+        if concurrency == 0 {
+            return Err("Concurrency must be at least 1".to_string());
+        }
+
+        check_placeholders(body)?;
+        let metadata: Vec<&String> = call.options.metadata.iter().flatten().map(|(_, v)| v).collect();
+        for value in &metadata {
+            check_placeholders(value)?;
+        }
+        let templated_metadata = metadata.iter().any(|v| ITERATION_PATTERN.is_match(v));
+
+        let first = encode(&call, request_format, &Iteration { index: 0, worker: 0 }.render(body))?;
+        Ok(Benchmark {
+            fixed: (!ITERATION_PATTERN.is_match(body)).then_some(first),
+            call,
+            client,
+            body: body.to_string(),
+            request_format,
+            concurrency,
+            options,
+            templated_metadata,
+            stop: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Set to stop starting calls. Calls in flight finish and are counted.
+    pub fn stop_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop)
+    }
+
+    /// Run to the configured count or duration, reporting progress every
+    /// `PROGRESS_INTERVAL`. Failed calls are counted, not returned as errors.
+    pub async fn run(self, mut on_progress: impl FnMut(&BenchmarkProgress)) -> BenchmarkReport {
+        let bench = Arc::new(self);
+        let started = Instant::now();
+        let deadline = bench.options.duration_ms.map(|ms| started + Duration::from_millis(ms));
+        let next = Arc::new(AtomicU64::new(0));
+        let (samples_tx, mut samples_rx) = mpsc::unbounded_channel();
+
+        for worker in 0..bench.concurrency {
+            let (bench, next, samples_tx) = (Arc::clone(&bench), Arc::clone(&next), samples_tx.clone());
+            tokio::spawn(async move {
+                while let Some(index) = bench.next_index(&next, deadline) {
+                    let call_started = Instant::now();
+                    let outcome = bench.call_once(&Iteration { index, worker }).await;
+                    let latency_ms = call_started.elapsed().as_secs_f64() * 1000.0;
+                    if samples_tx.send(Sample::new(index, latency_ms, outcome)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        // The channel closes once every worker is done.
+        drop(samples_tx);
+
+        let mut stats = Stats::default();
+        let mut ticker = tokio::time::interval(PROGRESS_INTERVAL);
+        loop {
+            tokio::select! {
+                sample = samples_rx.recv() => match sample {
+                    Some(sample) => stats.add(sample),
+                    None => break,
+                },
+                _ = ticker.tick() => on_progress(&stats.progress(started.elapsed())),
+            }
+        }
+        stats.report(started.elapsed(), bench.stop.load(Ordering::Relaxed))
+    }
+
+    fn next_index(&self, next: &AtomicU64, deadline: Option<Instant>) -> Option<u64> {
+        if self.stop.load(Ordering::Relaxed) || deadline.is_some_and(|d| Instant::now() >= d) {
+            return None;
+        }
+        let index = next.fetch_add(1, Ordering::Relaxed);
+        match self.options.total {
+            Some(total) if index >= total => None,
+            _ => Some(index),
+        }
+    }
+
+    /// One call through the engine's lightweight path: responses are drained, not
+    /// decoded, so the latency measured is the server's plus the network's.
+    async fn call_once(&self, iteration: &Iteration) -> Result<(String, String), String> {
+        let frames = match &self.fixed {
+            Some(frames) => Cow::Borrowed(frames),
+            None => Cow::Owned(encode(&self.call, self.request_format, &iteration.render(&self.body))?),
+        };
+        let templated;
+        let call = if self.templated_metadata {
+            let mut call = self.call.clone();
+            if let Some(metadata) = call.options.metadata.as_mut() {
+                metadata.iter_mut().for_each(|(_, v)| *v = iteration.render(v));
+            }
+            templated = call;
+            &templated
+        } else {
+            &self.call
+        };
+        call.status(&self.client, &frames).await
+    }
+}
+
+/// The encoded request frames for one call: one, or several for client streams.
+fn encode(call: &Call, format: MessageFormat, body: &str) -> Result<Vec<Vec<u8>>, String> {
+    let messages = if call.method.is_client_streaming() {
+        engine::split_messages(body, format)?
+    } else {
+        vec![body.to_string()]
+    };
+    messages
+        .iter()
+        .map(|msg| call.encode(&format.decode(call.method.input(), msg)?))
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Statistics
+// ---------------------------------------------------------------------------
+
+/// The outcome of one call.
+struct Sample {
+    index: u64,
+    latency_ms: f64,
+    status: &'static str,
+    /// The status message of a failed call.
+    error: Option<String>,
+}
+
+impl Sample {
+    /// `outcome` is the call's status code and message. Connection failures come back
+    /// as JSON errors carrying a status; other errors count as UNKNOWN.
+    fn new(index: u64, latency_ms: f64, outcome: Result<(String, String), String>) -> Self {
+        let (status, message) = outcome.unwrap_or_else(|e| match serde_json::from_str::<Value>(&e) {
+            Ok(error @ Value::Object(_)) => (
+                error["grpc_status"].as_str().unwrap_or("UNKNOWN").to_string(),
+                error["grpc_message"].as_str().unwrap_or_default().to_string(),
+            ),
+            _ => ("UNKNOWN".to_string(), e),
+        });
+        let code = assertions::status_code(&Value::String(status)).unwrap_or(2);
+        Sample {
+            index,
+            latency_ms,
+            status: assertions::status_name(code),
+            error: (code != 0).then_some(message),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Stats {
+    latencies: Vec<f64>,
+    errors: u64,
+    status_codes: BTreeMap<String, u64>,
+    error_samples: Vec<ErrorSample>,
+}
+
+impl Stats {
+    fn add(&mut self, sample: Sample) {
+        self.latencies.push(sample.latency_ms);
+        *self.status_codes.entry(sample.status.to_string()).or_default() += 1;
+        let Some(message) = sample.error else {
+            return;
+        };
+        self.errors += 1;
+        let seen = self
+            .error_samples
+            .iter()
+            .any(|e| e.status == sample.status && e.message == message);
+        if !seen && self.error_samples.len() < MAX_ERROR_SAMPLES {
+            self.error_samples.push(ErrorSample {
+                index: sample.index,
+                status: sample.status.to_string(),
+                message,
+            });
+        }
+    }
+
+    fn progress(&mut self, elapsed: Duration) -> BenchmarkProgress {
+        BenchmarkProgress {
+            completed: self.latencies.len() as u64,
+            errors: self.errors,
+            elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+            requests_per_second: self.latencies.len() as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+            latency: self.latency(),
+        }
+    }
+
+    /// Sorts the latencies; later calls on mostly sorted data are cheap.
+    fn latency(&mut self) -> LatencySummary {
+        self.latencies.sort_by(f64::total_cmp);
+        let sorted = &self.latencies;
+        let (Some(&min_ms), Some(&max_ms)) = (sorted.first(), sorted.last()) else {
+            return LatencySummary::default();
+        };
+        // Nearest-rank percentile.
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+        LatencySummary {
+            min_ms,
+            mean_ms: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p50_ms: percentile(50.0),
+            p90_ms: percentile(90.0),
+            p99_ms: percentile(99.0),
+            max_ms,
+        }
+    }
+
+    fn histogram(&self, latency: &LatencySummary) -> Vec<HistogramBucket> {
+        if self.latencies.is_empty() {
+            return Vec::new();
+        }
+        let width = (latency.max_ms - latency.min_ms) / HISTOGRAM_BUCKETS as f64;
+        if width == 0.0 {
+            return vec![HistogramBucket { up_to_ms: latency.max_ms, count: self.latencies.len() as u64 }];
+        }
+        let mut buckets: Vec<HistogramBucket> = (1..=HISTOGRAM_BUCKETS)
+            .map(|i| HistogramBucket { up_to_ms: latency.min_ms + width * i as f64, count: 0 })
+            .collect();
+        for &ms in &self.latencies {
+            let i = ((ms - latency.min_ms) / width) as usize;
+            buckets[i.min(HISTOGRAM_BUCKETS - 1)].count += 1;
+        }
+        buckets
+    }
+
+    fn report(mut self, elapsed: Duration, stopped: bool) -> BenchmarkReport {
+        let progress = self.progress(elapsed);
+        BenchmarkReport {
+            count: progress.completed,
+            ok: progress.completed - progress.errors,
+            errors: progress.errors,
+            total_ms: progress.elapsed_ms,
+            requests_per_second: progress.requests_per_second,
+            histogram: self.histogram(&progress.latency),
+            latency: progress.latency,
+            status_codes: self.status_codes,
+            error_samples: self.error_samples,
+            stopped,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::CallOptions;
+    use crate::proto_parser::compile_single_file;
+    use crate::test_server;
+    use prost_reflect::DynamicMessage;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Mutex;
+
+    const PROTO: &str = r#"
+syntax = "proto3";
+package bench.v1;
+service Users { rpc Get (GetUser) returns (GetUser); }
+message GetUser { string name = 1; }
+"#;
+
+    /// A benchmark of `Users.Get` against `addr`.
+    fn benchmark(addr: std::net::SocketAddr, body: &str, options: BenchmarkOptions) -> Result<Benchmark, String> {
+        let pool = Arc::new(compile_single_file(PROTO).unwrap());
+        let call = Call::new(pool, &addr.to_string(), "bench.v1.Users", "Get", CallOptions::default())?;
+        let client = call.connect()?;
+        Benchmark::new(call, client, body, MessageFormat::default(), options)
+    }
+
+    #[test]
+    fn test_percentiles_and_report() {
+        let mut stats = Stats::default();
+        for index in 0..100 {
+            let outcome = match index {
+                7 | 8 => Ok(("5".to_string(), "no such user".to_string())),
+                9 => Err(engine::format_connection_error(engine::ConnectionError::Transport("connection refused"), "localhost:1", "pkg.S", "M")),
+                _ => Ok(("0".to_string(), String::new())),
+            };
+            stats.add(Sample::new(index, (100 - index) as f64, outcome));
+        }
+        let report = stats.report(Duration::from_secs(2), false);
+
+        assert_eq!((report.count, report.ok, report.errors), (100, 97, 3));
+        assert_eq!(report.requests_per_second, 50.0);
+        assert_eq!((report.latency.min_ms, report.latency.max_ms), (1.0, 100.0));
+        assert_eq!((report.latency.p50_ms, report.latency.p90_ms, report.latency.p99_ms), (50.0, 90.0, 99.0));
+        assert_eq!(report.latency.mean_ms, 50.5);
+        assert_eq!(report.histogram.len(), HISTOGRAM_BUCKETS);
+        assert_eq!(report.histogram.iter().map(|b| b.count).sum::<u64>(), 100);
+        assert_eq!(report.histogram[0].count, 10);
+        assert_eq!(
+            report.status_codes,
+            BTreeMap::from([("OK".to_string(), 97), ("NOT_FOUND".to_string(), 2), ("UNAVAILABLE".to_string(), 1)])
+        );
+        // Repeats of the same error are sampled once.
+        assert_eq!(report.error_samples.len(), 2);
+        assert_eq!(report.error_samples[0], ErrorSample {
+            index: 7,
+            status: "NOT_FOUND".to_string(),
+            message: "no such user".to_string(),
+        });
+    }
+
+    #[test]
+    fn test_percentiles_of_few_samples() {
+        let mut stats = Stats::default();
+        assert_eq!(stats.latency(), LatencySummary::default());
+        assert!(stats.histogram(&LatencySummary::default()).is_empty());
+
+        // Nearest rank never interpolates: with two samples p50 is the lower one.
+        for (index, ms) in [(0, 30.0), (1, 10.0)] {
+            stats.add(Sample::new(index, ms, Ok(("0".to_string(), String::new()))));
+        }
+        let latency = stats.latency();
+        assert_eq!((latency.p50_ms, latency.p90_ms, latency.p99_ms), (10.0, 30.0, 30.0));
+        assert_eq!(latency.mean_ms, 20.0);
+
+        // Identical latencies land in a single bucket.
+        let mut stats = Stats::default();
+        for index in 0..3 {
+            stats.add(Sample::new(index, 5.0, Ok(("0".to_string(), String::new()))));
+        }
+        let latency = stats.latency();
+        assert_eq!(stats.histogram(&latency), vec![HistogramBucket { up_to_ms: 5.0, count: 3 }]);
+    }
+
+    #[test]
+    fn test_iteration_variables() {
+        let iteration = Iteration { index: 42, worker: 3 };
+        let body = r#"{"id": "u-{{iteration.index}}", "shard": {{iteration.worker}}}"#;
+        assert_eq!(iteration.render(body), r#"{"id": "u-42", "shard": 3}"#);
+
+        let uuid = uuid::Uuid::parse_str(&iteration.render("{{iteration.uuid}}")).unwrap();
+        assert_eq!(uuid.get_version_num(), 4);
+        assert_ne!(uuid.to_string(), iteration.render("{{iteration.uuid}}"));
+        assert!(iteration.render("{{iteration.random}}").parse::<i32>().unwrap() >= 0);
+        assert!(iteration.render("{{iteration.timestamp}}").parse::<i64>().is_ok());
+
+        assert!(check_placeholders("{{iteration.index}} {{env.host}}").is_ok());
+        let err = check_placeholders("{{iteration.count}}").unwrap_err();
+        assert!(err.contains("{{iteration.count}}"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_iteration_variables_reach_the_server() {
+        let pool = compile_single_file(PROTO).unwrap();
+        let desc = pool.get_message_by_name("bench.v1.GetUser").unwrap();
+        let names = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&names);
+        let addr = test_server::serve(move |req| {
+            let (desc, seen) = (desc.clone(), Arc::clone(&seen));
+            async move {
+                for msg in test_server::read_messages(req).await {
+                    let msg = DynamicMessage::decode(desc.clone(), msg.as_slice()).unwrap();
+                    let name = msg.get_field_by_name("name").unwrap().as_str().unwrap().to_string();
+                    seen.lock().unwrap().push(name);
+                }
+                test_server::grpc_response(vec![Vec::new()], 0, "")
+            }
+        });
+
+        let options = BenchmarkOptions { total: Some(3), ..Default::default() };
+        let report = benchmark(addr, r#"{"name": "u-{{iteration.index}}"}"#, options).unwrap().run(|_| {}).await;
+
+        assert_eq!((report.count, report.ok), (3, 3));
+        let mut names = names.lock().unwrap().clone();
+        names.sort();
+        assert_eq!(names, ["u-0", "u-1", "u-2"]);
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (current, highest) = (Arc::clone(&in_flight), Arc::clone(&peak));
+        let addr = test_server::serve(move |_req| {
+            let (current, highest) = (Arc::clone(&current), Arc::clone(&highest));
+            async move {
+                let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                highest.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                current.fetch_sub(1, Ordering::SeqCst);
+                test_server::grpc_response(Vec::new(), 5, "no such user")
+            }
+        });
+
+        let options = BenchmarkOptions { concurrency: Some(3), total: Some(12), ..Default::default() };
+        let report = benchmark(addr, r#"{"name": "a"}"#, options).unwrap().run(|_| {}).await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!((report.count, report.errors), (12, 12));
+        assert_eq!(report.status_codes, BTreeMap::from([("NOT_FOUND".to_string(), 12)]));
+        assert_eq!(report.error_samples.len(), 1, "one distinct error");
+        assert!(!report.stopped);
+    }
+
+    #[tokio::test]
+    async fn test_stop() {
+        let addr = test_server::serve(|_req| async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            test_server::grpc_response(vec![Vec::new()], 0, "")
+        });
+
+        let options = BenchmarkOptions { concurrency: Some(2), duration_ms: Some(60_000), ..Default::default() };
+        let bench = benchmark(addr, r#"{"name": "a"}"#, options).unwrap();
+        let stop = bench.stop_handle();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            stop.store(true, Ordering::Relaxed);
+        });
+
+        let started = Instant::now();
+        let mut progress_events = 0;
+        let report = bench.run(|_| progress_events += 1).await;
+
+        assert!(started.elapsed() < Duration::from_secs(10), "stopping ends the run");
+        assert!(report.stopped);
+        assert!(report.count > 0 && report.count == report.ok);
+        assert!(progress_events > 0);
+    }
+
+    #[test]
+    fn test_rejects_bad_options() {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 1));
+        let body = r#"{"name": "a"}"#;
+
+        let err = benchmark(addr, body, BenchmarkOptions::default()).err().unwrap();
+        assert!(err.contains("total call count or a duration"), "{}", err);
+        let options = BenchmarkOptions { concurrency: Some(0), total: Some(1), ..Default::default() };
+        assert!(benchmark(addr, body, options).is_err());
+        let options = BenchmarkOptions { total: Some(1), ..Default::default() };
+        assert!(benchmark(addr, r#"{"name": "{{iteration.nope}}"}"#, options.clone()).is_err());
+        assert!(benchmark(addr, r#"{"nickname": "a"}"#, options).is_err(), "the body is checked up front");
+    }
+}
@@ -0,0 +1,674 @@
+use grpcpeek_lib::assertions::{self, Assertion};
+use grpcpeek_lib::benchmark::{Benchmark, BenchmarkOptions};
+use grpcpeek_lib::compression::Encoding;
+use grpcpeek_lib::engine::{self, Call, CallOptions, TlsConfig};
+use grpcpeek_lib::grpc_web::{Transport, TransportConfig};
+use grpcpeek_lib::message_format::{JsonOptions, MessageFormat};
+use grpcpeek_lib::{proto_parser, runner, schema};
+use prost_reflect::DescriptorPool;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+const USAGE: &str = "\
+Usage: grpcpeek-cli <command> [options]
+
+Commands:
+  list                       List services and their methods
+  describe <name>            Describe a service, method, message or enum
+  call <method>              Make a unary or server-streaming call
+  stream <method>            Make a client- or bidi-streaming call
+  run <workspace.json>       Run a workspace's saved collections and check their
+                             assertions
+  bench <method>             Load-test a method and report latency percentiles,
+                             throughput and status counts
+
+Methods are written `pkg.Service/Method` or `pkg.Service.Method`.
+
+Options:
+  -I, --import-path <path>   .proto directory or file, or descriptor set (repeatable).
+                             Without one, descriptors are fetched by server reflection.
+  -e, --endpoint <address>   Server address, e.g. localhost:50051 or unix:/tmp/app.sock
+  -H, --header <key: value>  Request metadata (repeatable)
+  -d, --data <body>          Request body, `@file` to read a file or `-` for stdin.
+                             For `stream`, a JSON array or one message per line;
+                             stdin is read when omitted.
+      --tls                  Connect with TLS
+      --ca <file>            CA certificate to verify the server with (implies --tls)
+      --cert <file>          Client certificate (implies --tls)
+      --key <file>           Client private key
+      --insecure             Skip server certificate verification (implies --tls)
+      --timeout <ms>         Call deadline
+      --compression <name>   Request compression: identity, gzip, deflate or zstd
+      --transport <name>     grpc (default), grpc-web or grpc-web-text
+      --authority <host>     :authority for unix: endpoints
+      --request-format <f>   json (default), text, base64 or hex
+      --response-format <f>  json (default), text, base64 or hex
+      --emit-defaults        Include fields holding their default value
+      --proto-field-names    Use field names as written in the .proto
+      --enums-as-ints        Write enums as numbers
+      --int64-as-numbers     Write 64-bit integers as JSON numbers
+      --raw                  Attach the schema-less wire view of each response
+      --bin-type <key=type>  Decode `-bin` response metadata `key` as message `type`
+                             (repeatable)
+      --assert <json>        Check the result of `call`, e.g. '{\"type\":\"status\",\"code\":0}'
+                             (repeatable). The call fails unless every check passes.
+
+Run options:
+      --workspace <name>     Workspace to run when the file holds several
+      --env <name>           Environment to run against
+      --collection <name>    Only run this collection
+      --junit <file>         Write a JUnit XML report
+      --report <file>        Write the JSON report to a file as well as stdout
+      --bail                 Stop at the first failing request
+                             -e and --timeout apply to every request.
+
+Bench options:
+  -c, --concurrency <n>      Calls in flight at once (default 1)
+  -n, --total <n>            Number of calls to make
+      --duration <time>      How long to keep calling, e.g. 500ms, 30s or 2m
+                             The body and headers may use {{iteration.index}},
+                             {{iteration.worker}}, {{iteration.uuid}},
+                             {{iteration.random}} and {{iteration.timestamp}}.
+
+  -h, --help                 Show this help
+
+Results are printed as JSON. The exit code is 0 when the call returns OK (for `run`,
+when every request passes; for `bench`, when no call fails), 1 when it fails and 2
+for usage errors.";
+
+// ---------------------------------------------------------------------------
+// Arguments
+// ---------------------------------------------------------------------------
+
+#[derive(Default)]
+struct Args {
+    command: String,
+    target: Option<String>,
+    import_paths: Vec<String>,
+    endpoint: Option<String>,
+    /// Request metadata in order given; a key may repeat.
+    headers: Vec<(String, String)>,
+    data: Option<String>,
+    tls: TlsConfig,
+    timeout_ms: Option<u64>,
+    compression: Option<String>,
+    transport: TransportConfig,
+    request_format: Option<String>,
+    response_format: Option<String>,
+    json_options: JsonOptions,
+    include_raw: bool,
+    bin_metadata_types: HashMap<String, String>,
+    assertions: Vec<Assertion>,
+    run: RunArgs,
+    bench: BenchmarkOptions,
+}
+
+#[derive(Default)]
+struct RunArgs {
+    workspace: Option<String>,
+    environment: Option<String>,
+    collection: Option<String>,
+    junit: Option<String>,
+    report: Option<String>,
+    bail: bool,
+}
+
+fn parse_args(raw: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut positional = Vec::new();
+    let mut raw = raw.into_iter();
+
+    while let Some(arg) = raw.next() {
+        // `--name=value` is accepted as well as `--name value`.
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline
+                .clone()
+                .or_else(|| raw.next())
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => args.command = "help".to_string(),
+            "-I" | "--import-path" => args.import_paths.push(value(&flag)?),
+            "-e" | "--endpoint" => args.endpoint = Some(value(&flag)?),
+            "-H" | "--header" => {
+                let header = value(&flag)?;
+                let (key, val) = header
+                    .split_once(':')
+                    .ok_or_else(|| format!("Invalid header '{}': expected 'key: value'", header))?;
+                args.headers.push((key.trim().to_string(), val.trim().to_string()));
+            }
+            "-d" | "--data" => args.data = Some(value(&flag)?),
+            "--tls" => args.tls.enabled = true,
+            "--ca" => {
+                args.tls.server_ca_cert_path = Some(value(&flag)?);
+                args.tls.enabled = true;
+            }
+            "--cert" => {
+                args.tls.client_cert_path = Some(value(&flag)?);
+                args.tls.enabled = true;
+            }
+            "--key" => args.tls.client_key_path = Some(value(&flag)?),
+            "--insecure" => {
+                args.tls.insecure_skip_verify = Some(true);
+                args.tls.enabled = true;
+            }
+            "--timeout" => {
+                let ms = value(&flag)?;
+                args.timeout_ms = Some(ms.parse().map_err(|_| format!("Invalid timeout '{}'", ms))?);
+            }
+            "--compression" => args.compression = Some(value(&flag)?),
+            "--transport" => args.transport.mode = Some(value(&flag)?),
+            "--authority" => args.transport.authority = Some(value(&flag)?),
+            "--request-format" => args.request_format = Some(value(&flag)?),
+            "--response-format" => args.response_format = Some(value(&flag)?),
+            "--emit-defaults" => args.json_options.emit_defaults = true,
+            "--proto-field-names" => args.json_options.proto_field_names = true,
+            "--enums-as-ints" => args.json_options.enums_as_ints = true,
+            "--int64-as-numbers" => args.json_options.int64_as_numbers = true,
+            "--raw" => args.include_raw = true,
+            "--bin-type" => {
+                let pair = value(&flag)?;
+                let (key, type_name) = pair
+                    .split_once('=')
+                    .ok_or_else(|| format!("Invalid --bin-type '{}': expected 'key=pkg.Message'", pair))?;
+                args.bin_metadata_types.insert(key.trim().to_string(), type_name.trim().to_string());
+            }
+            "--assert" => {
+                let json = value(&flag)?;
+                let assertion =
+                    serde_json::from_str(&json).map_err(|e| format!("Invalid assertion '{}': {}", json, e))?;
+                args.assertions.push(assertion);
+            }
+            "--workspace" => args.run.workspace = Some(value(&flag)?),
+            "--env" => args.run.environment = Some(value(&flag)?),
+            "--collection" => args.run.collection = Some(value(&flag)?),
+            "--junit" => args.run.junit = Some(value(&flag)?),
+            "--report" => args.run.report = Some(value(&flag)?),
+            "--bail" => args.run.bail = true,
+            "-c" | "--concurrency" => {
+                let n = value(&flag)?;
+                args.bench.concurrency = Some(n.parse().map_err(|_| format!("Invalid concurrency '{}'", n))?);
+            }
+            "-n" | "--total" => {
+                let n = value(&flag)?;
+                args.bench.total = Some(n.parse().map_err(|_| format!("Invalid total '{}'", n))?);
+            }
+            "--duration" => args.bench.duration_ms = Some(parse_duration(&value(&flag)?)?),
+            other if other.starts_with('-') && other != "-" => return Err(format!("Unknown option '{}'", other)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    if args.command.is_empty() {
+        args.command = positional.next().unwrap_or_else(|| "help".to_string());
+    }
+    if !["help", "list", "describe", "call", "stream", "run", "bench"].contains(&args.command.as_str()) {
+        return Err(format!("Unknown command '{}'", args.command));
+    }
+    args.target = positional.next();
+    if let Some(extra) = positional.next() {
+        return Err(format!("Unexpected argument '{}'", extra));
+    }
+    match (&args.tls.client_cert_path, &args.tls.client_key_path) {
+        (Some(_), None) => return Err("--cert needs --key".to_string()),
+        (None, Some(_)) => return Err("--key needs --cert".to_string()),
+        _ => {}
+    }
+    Ok(args)
+}
+
+impl Args {
+    fn target(&self, what: &str) -> Result<&str, String> {
+        self.target
+            .as_deref()
+            .ok_or_else(|| format!("`{}` needs a {}", self.command, what))
+    }
+
+    fn endpoint(&self) -> Result<&str, String> {
+        self.endpoint
+            .as_deref()
+            .ok_or_else(|| format!("`{}` needs --endpoint", self.command))
+    }
+
+    fn call_options(&self) -> Result<CallOptions, String> {
+        Ok(CallOptions {
+            metadata: Some(self.headers.clone()).filter(|h| !h.is_empty()),
+            auth: None,
+            tls: Some(self.tls.clone()).filter(|t| t.enabled),
+            timeout_ms: self.timeout_ms,
+            encoding: self
+                .compression
+                .as_deref()
+                .map(Encoding::parse)
+                .transpose()?
+                .unwrap_or_default(),
+            transport_config: Some(self.transport.clone()),
+            response_format: self
+                .response_format
+                .as_deref()
+                .map(MessageFormat::parse)
+                .transpose()?
+                .unwrap_or_default()
+                .with_json_options(Some(self.json_options)),
+            include_raw: self.include_raw,
+            bin_metadata_types: Some(self.bin_metadata_types.clone()).filter(|t| !t.is_empty()),
+        })
+    }
+
+    fn request_format(&self) -> Result<MessageFormat, String> {
+        Ok(self
+            .request_format
+            .as_deref()
+            .map(MessageFormat::parse)
+            .transpose()?
+            .unwrap_or_default())
+    }
+
+    /// The request body as given by --data: inline, `@file` or `-` for stdin.
+    fn data(&self) -> Result<Option<String>, String> {
+        match self.data.as_deref() {
+            None => Ok(None),
+            Some("-") => read_stdin().map(Some),
+            Some(data) => match data.strip_prefix('@') {
+                Some(path) => std::fs::read_to_string(path)
+                    .map(Some)
+                    .map_err(|e| format!("Failed to read {}: {}", path, e)),
+                None => Ok(Some(data.to_string())),
+            },
+        }
+    }
+}
+
+fn read_stdin() -> Result<String, String> {
+    std::io::read_to_string(std::io::stdin()).map_err(|e| format!("Failed to read stdin: {}", e))
+}
+
+/// Milliseconds in `500ms`, `30s` or `2m`. A bare number is milliseconds.
+fn parse_duration(text: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid duration '{}': expected e.g. 500ms, 30s or 2m", text);
+    let (number, scale) = if let Some(n) = text.strip_suffix("ms") {
+        (n, 1)
+    } else if let Some(n) = text.strip_suffix('s') {
+        (n, 1000)
+    } else if let Some(n) = text.strip_suffix('m') {
+        (n, 60_000)
+    } else {
+        (text, 1)
+    };
+    let n: u64 = number.trim().parse().map_err(|_| invalid())?;
+    n.checked_mul(scale).ok_or_else(invalid)
+}
+
+/// Split `pkg.Service/Method` or `pkg.Service.Method`.
+fn split_method(name: &str) -> Result<(&str, &str), String> {
+    let name = name.trim_start_matches('.');
+    name.rsplit_once('/')
+        .or_else(|| name.rsplit_once('.'))
+        .filter(|(service, method)| !service.is_empty() && !method.is_empty())
+        .ok_or_else(|| format!("Invalid method '{}': expected pkg.Service/Method", name))
+}
+
+// ---------------------------------------------------------------------------
+// Descriptors
+// ---------------------------------------------------------------------------
+
+/// Compile the import paths, or fetch the server's descriptors by reflection when
+/// there are none.
+async fn load_pool(args: &Args) -> Result<Arc<DescriptorPool>, String> {
+    if args.import_paths.is_empty() {
+        let endpoint = args.endpoint().map_err(|_| "Give --import-path, or --endpoint to use reflection".to_string())?;
+        let options = args.call_options()?;
+        let http2 = engine::http2_preference(Transport::Grpc, None);
+        let client = engine::build_client(&engine::clean_endpoint(endpoint), options.tls.as_ref(), http2)?;
+        let (pool, warnings) = engine::reflect(&client, endpoint, &options).await?;
+        for warning in warnings {
+            eprintln!("warning: {}", warning);
+        }
+        return Ok(Arc::new(pool));
+    }
+
+    let paths: Vec<proto_parser::ImportPath> = args
+        .import_paths
+        .iter()
+        .enumerate()
+        .map(|(i, path)| proto_parser::ImportPath {
+            id: i.to_string(),
+            path: path.clone(),
+            path_type: if Path::new(path).is_dir() { "directory" } else { "file" }.to_string(),
+            enabled: true,
+        })
+        .collect();
+    proto_parser::compile_descriptor_pool(&paths).map(Arc::new)
+}
+
+fn describe(pool: &DescriptorPool, name: &str) -> Result<Value, String> {
+    let name = name.trim_start_matches('.');
+    let to_json = |v: Result<Value, serde_json::Error>| v.map_err(|e| e.to_string());
+
+    if let Some(service) = pool.get_service_by_name(name) {
+        let services = proto_parser::parse_descriptor_pool(pool, Vec::new()).services;
+        let found = services.into_iter().find(|s| s.name == service.full_name());
+        return to_json(serde_json::to_value(found));
+    }
+    if let Some(message) = pool.get_message_by_name(name) {
+        return to_json(serde_json::to_value(schema::describe_message(&message)));
+    }
+    if let Some(e) = pool.get_enum_by_name(name) {
+        let values: Vec<Value> = e
+            .values()
+            .map(|v| serde_json::json!({ "name": v.name(), "number": v.number() }))
+            .collect();
+        return Ok(serde_json::json!({ "name": e.full_name(), "values": values }));
+    }
+
+    let (service, method) = split_method(name)?;
+    let method = engine::find_service(pool, service)?
+        .methods()
+        .find(|m| m.name() == method)
+        .ok_or_else(|| format!("'{}' is not a service, method, message or enum", name))?;
+    Ok(serde_json::json!({
+        "name": method.full_name(),
+        "isClientStreaming": method.is_client_streaming(),
+        "isServerStreaming": method.is_server_streaming(),
+        "input": schema::describe_message(&method.input()),
+        "output": schema::describe_message(&method.output()),
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// Commands
+// ---------------------------------------------------------------------------
+
+/// Run the command and return the JSON to print, and whether it succeeded.
+async fn run(args: &Args) -> Result<(Value, bool), String> {
+    match args.command.as_str() {
+        "list" => {
+            let pool = load_pool(args).await?;
+            let result = proto_parser::parse_descriptor_pool(&pool, Vec::new());
+            Ok((serde_json::to_value(result.services).map_err(|e| e.to_string())?, true))
+        }
+        "describe" => {
+            let name = args.target("service, method, message or enum name")?;
+            let pool = load_pool(args).await?;
+            Ok((describe(&pool, name)?, true))
+        }
+        "call" => {
+            let (service, method) = split_method(args.target("method")?)?;
+            let request_format = args.request_format()?;
+            let data = args.data()?.unwrap_or_else(|| match request_format {
+                MessageFormat::Json(_) => "{}".to_string(),
+                _ => String::new(),
+            });
+
+            let pool = load_pool(args).await?;
+            let call = Call::new(pool, args.endpoint()?, service, method, args.call_options()?)?;
+            if call.method.is_client_streaming() {
+                return Err(format!("{} is client-streaming; use `stream`", call.method.full_name()));
+            }
+            let request = request_format.decode(call.method.input(), &data)?;
+            let request_echo = match request_format {
+                MessageFormat::Json(_) => serde_json::from_str(&data).unwrap_or(Value::String(data)),
+                _ => Value::String(data),
+            };
+
+            let client = call.connect()?;
+            let mut result = call.unary(&client, &request, request_echo, |_, _| {}).await?;
+            if args.assertions.is_empty() {
+                let ok = result["grpc_status"] == "0";
+                return Ok((result, ok));
+            }
+            assertions::attach(&mut result, &args.assertions);
+            let ok = result["assertions_passed"] == true;
+            Ok((result, ok))
+        }
+        "stream" => {
+            let (service, method) = split_method(args.target("method")?)?;
+            let request_format = args.request_format()?;
+            let data = match args.data()? {
+                Some(data) => data,
+                None => read_stdin()?,
+            };
+
+            let pool = load_pool(args).await?;
+            let call = Call::new(pool, args.endpoint()?, service, method, args.call_options()?)?;
+            if !call.method.is_client_streaming() {
+                return Err(format!("{} is not client-streaming; use `call`", call.method.full_name()));
+            }
+
+            // Every message is checked before the stream is opened.
+            let (frames_tx, frames_rx) = mpsc::unbounded_channel();
+            for body in engine::split_messages(&data, request_format)? {
+                let msg = request_format.decode(call.method.input(), &body)?;
+                let _ = frames_tx.send(call.encode(&msg)?);
+            }
+            drop(frames_tx);
+
+            let client = call.connect()?;
+            let mut responses = Vec::new();
+            let mut result = call
+                .stream(&client, frames_rx, |_, inspected| responses.push(inspected.data.clone()))
+                .await?;
+            if call.method.is_server_streaming() {
+                result["responses"] = Value::Array(responses);
+            }
+            let ok = result["grpc_status"] == "0";
+            Ok((result, ok))
+        }
+        "run" => {
+            let path = args.target("workspace file")?;
+            let json = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            let workspace = runner::load_workspace(&json, args.run.workspace.as_deref())?;
+            let options = runner::RunOptions {
+                environment: args.run.environment.clone(),
+                collection: args.run.collection.clone(),
+                endpoint: args.endpoint.clone(),
+                timeout_ms: args.timeout_ms,
+                bail: args.run.bail,
+            };
+            let report = runner::run(&workspace, &options).await?;
+
+            if let Some(path) = &args.run.junit {
+                std::fs::write(path, runner::junit_xml(&report))
+                    .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+            }
+            let json = serde_json::to_value(&report).map_err(|e| e.to_string())?;
+            if let Some(path) = &args.run.report {
+                std::fs::write(path, serde_json::to_string_pretty(&json).unwrap_or_default())
+                    .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+            }
+            Ok((json, report.failed == 0))
+        }
+        "bench" => {
+            let (service, method) = split_method(args.target("method")?)?;
+            let request_format = args.request_format()?;
+            let data = args.data()?.unwrap_or_else(|| match request_format {
+                MessageFormat::Json(_) => "{}".to_string(),
+                _ => String::new(),
+            });
+
+            let pool = load_pool(args).await?;
+            let call = Call::new(pool, args.endpoint()?, service, method, args.call_options()?)?;
+            let client = call.connect()?;
+            let bench = Benchmark::new(call, client, &data, request_format, args.bench.clone())?;
+
+            // Progress goes to stderr, and only to a terminal, so stdout stays JSON.
+            let interactive = std::io::stderr().is_terminal();
+            let report = bench
+                .run(|progress| {
+                    if interactive {
+                        eprint!(
+                            "\r{} calls, {} errors, {:.0} calls/s, p99 {:.1} ms  ",
+                            progress.completed, progress.errors, progress.requests_per_second, progress.latency.p99_ms
+                        );
+                    }
+                })
+                .await;
+            if interactive {
+                eprintln!();
+            }
+            let ok = report.errors == 0;
+            Ok((serde_json::to_value(&report).map_err(|e| e.to_string())?, ok))
+        }
+        _ => unreachable!("commands are checked by parse_args"),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) if args.command != "help" => args,
+        Ok(_) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&args).await {
+        Ok((result, ok)) => {
+            print_json(&result);
+            if ok {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(e) => {
+            match error_result(&e) {
+                Some(result) => print_json(&result),
+                None => eprintln!("error: {}", e),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Connection failures come back as a JSON result rather than a message; they are
+/// printed like any other result.
+fn error_result(error: &str) -> Option<Value> {
+    serde_json::from_str(error).ok().filter(Value::is_object)
+}
+
+fn render_json(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+fn print_json(value: &Value) {
+    // A closed pipe (`| head`) isn't an error worth reporting.
+    let _ = writeln!(std::io::stdout(), "{}", render_json(value));
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_call_arguments() {
+        let argv = [
+            "call",
+            "pkg.v1.Greeter/SayHello",
+            "-e",
+            "localhost:50051",
+            "-H",
+            "x-user: alice",
+            "--data={\"name\":\"a\"}",
+            "--ca",
+            "ca.pem",
+            "--emit-defaults",
+            "--assert",
+            "{\"type\": \"messageCount\", \"max\": 1}",
+        ];
+        let args = parse_args(argv.iter().map(|s| s.to_string())).unwrap();
+        assert_eq!(args.command, "call");
+        assert_eq!(split_method(args.target.as_deref().unwrap()).unwrap(), ("pkg.v1.Greeter", "SayHello"));
+        assert_eq!(args.headers, [("x-user".to_string(), "alice".to_string())]);
+        assert_eq!(args.data.as_deref(), Some("{\"name\":\"a\"}"));
+        assert!(args.tls.enabled && args.json_options.emit_defaults);
+        assert_eq!(args.assertions, [Assertion::MessageCount { min: None, max: Some(1) }]);
+
+        assert_eq!(split_method(".pkg.v1.Greeter.SayHello").unwrap(), ("pkg.v1.Greeter", "SayHello"));
+        let bench = parse_args(["bench", "pkg.S/M", "-c", "8", "--duration=2m"].iter().map(|s| s.to_string())).unwrap();
+        assert_eq!((bench.bench.concurrency, bench.bench.duration_ms), (Some(8), Some(120_000)));
+        assert!(parse_duration("10x").is_err());
+        assert_eq!(engine::split_messages("[{\"a\":1}, {}]", MessageFormat::default()).unwrap(), ["{\"a\":1}", "{}"]);
+    }
+
+    fn parse(argv: &[&str]) -> Result<Args, String> {
+        parse_args(argv.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_repeated_headers_are_kept() {
+        let args = parse(&["call", "pkg.S/M", "-H", "x-tag: a", "--header=x-tag: b", "-H", "x-id:1"]).unwrap();
+        let pairs = |list: &[(&str, &str)]| -> Vec<(String, String)> {
+            list.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+        assert_eq!(args.headers, pairs(&[("x-tag", "a"), ("x-tag", "b"), ("x-id", "1")]));
+        assert_eq!(args.call_options().unwrap().metadata, Some(args.headers.clone()));
+        assert_eq!(parse(&["list"]).unwrap().call_options().unwrap().metadata, None);
+
+        let typed = parse(&["call", "--bin-type", "x-trace-bin=trace.v1.Span"]).unwrap();
+        let types = typed.call_options().unwrap().bin_metadata_types.unwrap();
+        assert_eq!(types["x-trace-bin"], "trace.v1.Span");
+    }
+
+    #[test]
+    fn test_argument_errors() {
+        let error = |argv: &[&str]| parse(argv).err().unwrap_or_else(|| panic!("{:?} should fail", argv));
+
+        assert_eq!(error(&["list", "--bogus"]), "Unknown option '--bogus'");
+        assert_eq!(error(&["frobnicate"]), "Unknown command 'frobnicate'");
+        assert_eq!(error(&["describe", "a", "b"]), "Unexpected argument 'b'");
+        assert_eq!(error(&["call", "pkg.S/M", "-e"]), "-e needs a value");
+        assert_eq!(error(&["call", "-H", "no-colon"]), "Invalid header 'no-colon': expected 'key: value'");
+        assert_eq!(error(&["call", "--timeout", "soon"]), "Invalid timeout 'soon'");
+        assert!(error(&["call", "--assert", "{\"type\": \"nope\"}"]).starts_with("Invalid assertion"));
+        assert_eq!(error(&["bench", "-c", "many"]), "Invalid concurrency 'many'");
+        assert_eq!(error(&["call", "--bin-type", "x-trace-bin"]), "Invalid --bin-type 'x-trace-bin': expected 'key=pkg.Message'");
+
+        // A client certificate is only usable with its key, and the reverse.
+        assert_eq!(error(&["call", "--cert", "client.pem"]), "--cert needs --key");
+        assert_eq!(error(&["call", "--key", "client.key"]), "--key needs --cert");
+        let mtls = parse(&["call", "--cert", "client.pem", "--key", "client.key"]).unwrap();
+        assert!(mtls.tls.enabled);
+
+        // Arguments that parse but are missing for the command fail when it runs.
+        let bare = parse(&["call"]).unwrap();
+        assert_eq!(bare.target("method").unwrap_err(), "`call` needs a method");
+        assert_eq!(bare.endpoint().unwrap_err(), "`call` needs --endpoint");
+        assert!(parse(&["call", "--compression", "lz4"]).unwrap().call_options().is_err());
+    }
+
+    #[test]
+    fn test_output() {
+        let result = serde_json::json!({ "grpc_status": "0", "response": { "name": "a" } });
+        assert_eq!(
+            render_json(&result),
+            "{\n  \"grpc_status\": \"0\",\n  \"response\": {\n    \"name\": \"a\"\n  }\n}"
+        );
+
+        // Connection failures print as results; other errors go to stderr as text.
+        let refused = engine::format_connection_error(engine::ConnectionError::Transport("connection refused"), "localhost:1", "pkg.S", "M");
+        assert_eq!(error_result(&refused).unwrap()["error_category"], "Connection Refused");
+        assert_eq!(error_result("Invalid method 'x': expected pkg.Service/Method"), None);
+        assert_eq!(error_result("\"quoted\""), None);
+    }
+}
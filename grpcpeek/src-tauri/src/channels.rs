@@ -0,0 +1,140 @@
+use crate::connector::Connector;
+use hyper::{Body, Client};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// A reusable client for one endpoint + TLS config + HTTP version. hyper keeps the
+/// underlying connections alive between calls, so only the first call to an
+/// endpoint pays for DNS, TCP and TLS.
+struct Channel {
+    id: u64,
+    client: Client<Connector, Body>,
+    endpoint: String,
+    tls: bool,
+    protocol: &'static str,
+    created_at: chrono::DateTime<chrono::Utc>,
+    last_used_at: chrono::DateTime<chrono::Utc>,
+    call_count: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelInfo {
+    pub id: u64,
+    pub endpoint: String,
+    pub tls: bool,
+    /// "h2", "http/1.1" or "auto" (negotiated per connection over ALPN).
+    pub protocol: &'static str,
+    pub created_at: String,
+    pub last_used_at: String,
+    pub call_count: u64,
+}
+
+#[derive(Default)]
+pub struct ChannelPool {
+    channels: Mutex<HashMap<String, Channel>>,
+    next_id: AtomicU64,
+}
+
+impl ChannelPool {
+    /// Return the pooled client for `key`, creating it with `connect` on first use.
+    /// `key` must capture everything that affects the connection (endpoint, TLS
+    /// settings, HTTP version).
+    pub fn get_or_connect(
+        &self,
+        key: String,
+        endpoint: &str,
+        tls: bool,
+        protocol: &'static str,
+        connect: impl FnOnce() -> Result<Client<Connector, Body>, String>,
+    ) -> Result<Client<Connector, Body>, String> {
+        let mut channels = self.channels.lock().unwrap_or_else(|p| p.into_inner());
+        let now = chrono::Utc::now();
+        if let Some(channel) = channels.get_mut(&key) {
+            channel.last_used_at = now;
+            channel.call_count += 1;
+            return Ok(channel.client.clone());
+        }
+
+        let client = connect()?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        channels.insert(
+            key,
+            Channel {
+                id,
+                client: client.clone(),
+                endpoint: endpoint.to_string(),
+                tls,
+                protocol,
+                created_at: now,
+                last_used_at: now,
+                call_count: 1,
+            },
+        );
+        Ok(client)
+    }
+
+    pub fn list(&self) -> Vec<ChannelInfo> {
+        let channels = self.channels.lock().unwrap_or_else(|p| p.into_inner());
+        let mut infos: Vec<ChannelInfo> = channels
+            .values()
+            .map(|c| ChannelInfo {
+                id: c.id,
+                endpoint: c.endpoint.clone(),
+                tls: c.tls,
+                protocol: c.protocol,
+                created_at: c.created_at.to_rfc3339(),
+                last_used_at: c.last_used_at.to_rfc3339(),
+                call_count: c.call_count,
+            })
+            .collect();
+        infos.sort_by_key(|c| c.id);
+        infos
+    }
+
+    /// Drop a channel's client. Idle connections close immediately; calls still in
+    /// flight keep their connection until they finish. Returns false if unknown.
+    pub fn close(&self, id: u64) -> bool {
+        let mut channels = self.channels.lock().unwrap_or_else(|p| p.into_inner());
+        let before = channels.len();
+        channels.retain(|_, c| c.id != id);
+        channels.len() != before
+    }
+
+    /// Drop every channel, returning how many were closed.
+    pub fn close_all(&self) -> usize {
+        let mut channels = self.channels.lock().unwrap_or_else(|p| p.into_inner());
+        let count = channels.len();
+        channels.clear();
+        count
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_channels_are_reused_until_closed() {
+        let pool = ChannelPool::default();
+        let connect = || Ok(Client::builder().build(Connector::unix("/tmp/none.sock")?));
+
+        pool.get_or_connect("a".into(), "unix:/tmp/none.sock", false, "h2", connect).unwrap();
+        pool.get_or_connect("a".into(), "unix:/tmp/none.sock", false, "h2", || Err("reconnected".into()))
+            .unwrap();
+        let listed = pool.list();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].call_count, 2);
+
+        assert!(pool.close(listed[0].id));
+        assert!(!pool.close(listed[0].id));
+        assert!(pool.list().is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use std::io::{Read, Write};
+
+/// Value sent in `grpc-accept-encoding` so servers may compress their responses.
+pub const ACCEPT_ENCODING: &str = "identity,gzip,deflate,zstd";
+
+/// Largest message a compressed frame may expand to. Guards against frames that
+/// decompress to gigabytes.
+const MAX_DECOMPRESSED: usize = 64 * 1024 * 1024;
+
+/// Message encodings from the gRPC compression spec that gRPCpeek can read and write.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Identity,
+    Gzip,
+    /// zlib-wrapped deflate (RFC 1950), as used by gRPC implementations.
+    Deflate,
+    Zstd,
+}
+
+impl Encoding {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "" | "identity" | "none" => Ok(Encoding::Identity),
+            "gzip" => Ok(Encoding::Gzip),
+            "deflate" => Ok(Encoding::Deflate),
+            "zstd" => Ok(Encoding::Zstd),
+            other => Err(format!(
+                "Unsupported compression '{}'. Supported: identity, gzip, deflate, zstd",
+                other
+            )),
+        }
+    }
+
+    /// Encoding declared by a response's `grpc-encoding` header (identity when absent).
+    pub fn from_headers(headers: &http::HeaderMap) -> Result<Self, String> {
+        match headers.get("grpc-encoding").and_then(|v| v.to_str().ok()) {
+            Some(name) => Self::parse(name),
+            None => Ok(Encoding::Identity),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Identity => "identity",
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+            Encoding::Zstd => "zstd",
+        }
+    }
+}
+
+pub fn compress(encoding: Encoding, data: &[u8]) -> Result<Vec<u8>, String> {
+    let result = match encoding {
+        Encoding::Identity => return Ok(data.to_vec()),
+        Encoding::Gzip => {
+            let mut enc = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            enc.write_all(data).and_then(|_| enc.finish())
+        }
+        Encoding::Deflate => {
+            let mut enc = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            enc.write_all(data).and_then(|_| enc.finish())
+        }
+        Encoding::Zstd => zstd::stream::encode_all(data, 0),
+    };
+    result.map_err(|e| format!("Failed to {}-compress message: {}", encoding.as_str(), e))
+}
+
+pub fn decompress(encoding: Encoding, data: &[u8]) -> Result<Vec<u8>, String> {
+    decompress_limited(encoding, data, MAX_DECOMPRESSED)
+}
+
+/// `decompress`, failing once the output grows past `limit` bytes.
+fn decompress_limited(encoding: Encoding, data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    let cap = limit as u64 + 1;
+    let mut out = Vec::new();
+    let result = match encoding {
+        Encoding::Identity => return Ok(data.to_vec()),
+        Encoding::Gzip => GzDecoder::new(data).take(cap).read_to_end(&mut out),
+        Encoding::Deflate => ZlibDecoder::new(data).take(cap).read_to_end(&mut out),
+        Encoding::Zstd => zstd::stream::read::Decoder::new(data).and_then(|d| d.take(cap).read_to_end(&mut out)),
+    };
+    result.map_err(|e| format!("Failed to {}-decompress message: {}", encoding.as_str(), e))?;
+    if out.len() > limit {
+        return Err(format!(
+            "Decompressed {} message exceeds the {} byte limit",
+            encoding.as_str(),
+            limit
+        ));
+    }
+    Ok(out)
+}
+
+/// Return a frame's message bytes, decompressing them when the frame's
+/// compressed flag is set. `encoding` is the response's `grpc-encoding`.
+pub fn decode_frame(compressed: bool, data: bytes::Bytes, encoding: Encoding) -> Result<bytes::Bytes, String> {
+    if !compressed {
+        return Ok(data);
+    }
+    if encoding == Encoding::Identity {
+        return Err("Received a compressed message but the server sent no grpc-encoding".to_string());
+    }
+    decompress(encoding, &data).map(bytes::Bytes::from)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_all_encodings() {
+        let payload = b"hello hello hello hello grpc".repeat(10);
+        for enc in [Encoding::Identity, Encoding::Gzip, Encoding::Deflate, Encoding::Zstd] {
+            let compressed = compress(enc, &payload).unwrap();
+            assert_eq!(decompress(enc, &compressed).unwrap(), payload, "{:?}", enc);
+        }
+    }
+
+    #[test]
+    fn test_decompression_is_capped() {
+        let payload = vec![0u8; 4096];
+        for enc in [Encoding::Gzip, Encoding::Deflate, Encoding::Zstd] {
+            let compressed = compress(enc, &payload).unwrap();
+            assert_eq!(decompress_limited(enc, &compressed, 4096).unwrap(), payload, "{:?}", enc);
+            assert_eq!(
+                decompress_limited(enc, &compressed, 4095).unwrap_err(),
+                format!("Decompressed {} message exceeds the 4095 byte limit", enc.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_compressed_frame_requires_encoding() {
+        let data = bytes::Bytes::from_static(b"\x08\x01");
+        assert_eq!(decode_frame(false, data.clone(), Encoding::Identity).unwrap(), data);
+        assert!(decode_frame(true, data, Encoding::Identity).is_err());
+        assert!(Encoding::parse("snappy").is_err());
+    }
+}
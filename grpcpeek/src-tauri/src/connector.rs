@@ -0,0 +1,445 @@
+use futures::stream::{FuturesUnordered, StreamExt};
+use hyper::client::connect::{Connected, Connection};
+use hyper::service::Service;
+use hyper::Uri;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+
+/// `:authority` sent on Unix socket calls when none is configured.
+pub const DEFAULT_UDS_AUTHORITY: &str = "localhost";
+
+/// How long to wait on one address before giving up on it.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long an attempt gets before the next address is tried alongside it
+/// (Happy Eyeballs, RFC 8305).
+const ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Socket path of a `unix:path` or `unix:///absolute/path` endpoint, or `None` for
+/// TCP endpoints.
+pub fn unix_socket_path(endpoint: &str) -> Option<&str> {
+    let rest = endpoint.strip_prefix("unix:")?;
+    Some(rest.strip_prefix("//").unwrap_or(rest))
+}
+
+/// How long each phase of establishing a connection took. hyper copies this into
+/// the extensions of every response sent over the connection, which is how a call
+/// learns whether it opened a new connection or reused a pooled one.
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectTimings {
+    /// When the connection finished being established.
+    pub established_at: Instant,
+    /// `None` when the host was an IP literal or a Unix socket.
+    pub dns: Option<Duration>,
+    pub connect: Duration,
+    /// `None` for cleartext connections.
+    pub tls: Option<Duration>,
+}
+
+/// Connects either over TCP (optionally TLS) or to a Unix domain socket, so one
+/// client type serves every endpoint. Resolution, connect and handshake are done
+/// here rather than by hyper's connectors so each phase can be timed.
+///
+/// For Unix sockets the request URI only supplies `:authority` and path; the
+/// connection always goes to the socket.
+#[derive(Clone)]
+pub enum Connector {
+    Tcp(Option<Arc<rustls::ClientConfig>>),
+    #[cfg(unix)]
+    Unix(Arc<std::path::PathBuf>),
+}
+
+impl Connector {
+    /// TCP connector, doing a TLS handshake first when `tls` is set.
+    pub fn tcp(tls: Option<rustls::ClientConfig>) -> Self {
+        Connector::Tcp(tls.map(Arc::new))
+    }
+
+    #[cfg(unix)]
+    pub fn unix(path: &str) -> Result<Self, String> {
+        Ok(Connector::Unix(Arc::new(path.into())))
+    }
+
+    #[cfg(not(unix))]
+    pub fn unix(_path: &str) -> Result<Self, String> {
+        Err("Unix domain socket endpoints are not supported on this platform".to_string())
+    }
+}
+
+impl Service<Uri> for Connector {
+    type Response = Stream;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Stream, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        match self {
+            Connector::Tcp(tls) => Box::pin(connect_tcp(uri, tls.clone())),
+            #[cfg(unix)]
+            Connector::Unix(path) => {
+                let path = Arc::clone(path);
+                Box::pin(async move {
+                    let started = Instant::now();
+                    let stream = tokio::net::UnixStream::connect(path.as_path())
+                        .await
+                        .map_err(|e| format!("unix socket connect error ({}): {}", path.display(), e))?;
+                    Ok(Stream {
+                        io: Io::Unix(stream),
+                        negotiated_h2: false,
+                        timings: ConnectTimings {
+                            established_at: Instant::now(),
+                            dns: None,
+                            connect: started.elapsed(),
+                            tls: None,
+                        },
+                    })
+                })
+            }
+        }
+    }
+}
+
+async fn connect_tcp(uri: Uri, tls: Option<Arc<rustls::ClientConfig>>) -> Result<Stream, BoxError> {
+    let host = uri
+        .host()
+        .ok_or_else(|| format!("URI has no host: {}", uri))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = uri.port_u16().unwrap_or(if tls.is_some() { 443 } else { 80 });
+
+    let started = Instant::now();
+    let (addrs, dns) = match host.parse::<IpAddr>() {
+        Ok(ip) => (vec![SocketAddr::new(ip, port)], None),
+        Err(_) => {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+                .await
+                .map_err(|e| format!("dns error: {}", e))?
+                .collect();
+            (addrs, Some(started.elapsed()))
+        }
+    };
+
+    if addrs.is_empty() {
+        return Err(format!("dns error: no addresses found for {}", host).into());
+    }
+
+    let connect_started = Instant::now();
+    let tcp = race(interleave(addrs), ATTEMPT_DELAY, |addr| connect_addr(addr, CONNECT_TIMEOUT))
+        .await
+        .map_err(|e| format!("tcp connect error: {}", e))?;
+    let connect = connect_started.elapsed();
+    tcp.set_nodelay(true)?;
+
+    let (io, negotiated_h2, tls_time) = match tls {
+        Some(config) => {
+            let server_name = rustls::ServerName::try_from(host.as_str())
+                .map_err(|e| format!("invalid TLS server name '{}': {}", host, e))?;
+            let handshake_started = Instant::now();
+            let stream = tokio_rustls::TlsConnector::from(config)
+                .connect(server_name, tcp)
+                .await
+                .map_err(|e| format!("tls handshake error: {}", e))?;
+            let h2 = stream.get_ref().1.alpn_protocol() == Some(b"h2".as_slice());
+            (Io::Tls(Box::new(stream)), h2, Some(handshake_started.elapsed()))
+        }
+        None => (Io::Tcp(tcp), false, None),
+    };
+
+    Ok(Stream {
+        io,
+        negotiated_h2,
+        timings: ConnectTimings { established_at: Instant::now(), dns, connect, tls: tls_time },
+    })
+}
+
+async fn connect_addr(addr: SocketAddr, timeout: Duration) -> io::Result<TcpStream> {
+    tokio::time::timeout(timeout, TcpStream::connect(addr))
+        .await
+        .unwrap_or_else(|_| {
+            Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("connection to {} timed out after {} ms", addr, timeout.as_millis()),
+            ))
+        })
+}
+
+/// Alternate IPv6 and IPv4 addresses, starting with the family the resolver put
+/// first and keeping its order within each family, so a broken family only costs
+/// one attempt delay.
+fn interleave(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let first_v6 = addrs.first().is_some_and(SocketAddr::is_ipv6);
+    let (preferred, other): (Vec<_>, Vec<_>) = addrs.into_iter().partition(|a| a.is_ipv6() == first_v6);
+    let mut preferred = preferred.into_iter();
+    let mut other = other.into_iter();
+    let mut out = Vec::with_capacity(preferred.len() + other.len());
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => return out,
+            (a, b) => out.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+/// Try `addrs` in order, starting the next attempt when the current ones have run
+/// for `delay` without connecting, or as soon as one fails. The first connection
+/// wins; the others are dropped. Fails with the last error once every address has.
+async fn race<T, F, Fut>(addrs: Vec<SocketAddr>, delay: Duration, connect: F) -> io::Result<T>
+where
+    F: Fn(SocketAddr) -> Fut,
+    Fut: Future<Output = io::Result<T>>,
+{
+    let mut addrs = addrs.into_iter();
+    let mut attempts = FuturesUnordered::new();
+    let mut last_err = None;
+    loop {
+        if attempts.is_empty() {
+            match addrs.next() {
+                Some(addr) => attempts.push(connect(addr)),
+                None => {
+                    return Err(last_err
+                        .unwrap_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no addresses to connect to")))
+                }
+            }
+        }
+        tokio::select! {
+            Some(result) = attempts.next() => match result {
+                Ok(stream) => return Ok(stream),
+                Err(e) => {
+                    last_err = Some(e);
+                    if let Some(addr) = addrs.next() {
+                        attempts.push(connect(addr));
+                    }
+                }
+            },
+            _ = tokio::time::sleep(delay), if addrs.len() > 0 => {
+                attempts.extend(addrs.next().map(&connect));
+            }
+        }
+    }
+}
+
+pub struct Stream {
+    io: Io,
+    negotiated_h2: bool,
+    timings: ConnectTimings,
+}
+
+enum Io {
+    Tcp(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixStream),
+}
+
+impl Connection for Stream {
+    fn connected(&self) -> Connected {
+        let connected = Connected::new().extra(self.timings);
+        if self.negotiated_h2 {
+            connected.negotiated_h2()
+        } else {
+            connected
+        }
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().io {
+            Io::Tcp(s) => Pin::new(s).poll_read(cx, buf),
+            Io::Tls(s) => Pin::new(s).poll_read(cx, buf),
+            #[cfg(unix)]
+            Io::Unix(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match &mut self.get_mut().io {
+            Io::Tcp(s) => Pin::new(s).poll_write(cx, buf),
+            Io::Tls(s) => Pin::new(s).poll_write(cx, buf),
+            #[cfg(unix)]
+            Io::Unix(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().io {
+            Io::Tcp(s) => Pin::new(s).poll_flush(cx),
+            Io::Tls(s) => Pin::new(s).poll_flush(cx),
+            #[cfg(unix)]
+            Io::Unix(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut self.get_mut().io {
+            Io::Tcp(s) => Pin::new(s).poll_shutdown(cx),
+            Io::Tls(s) => Pin::new(s).poll_shutdown(cx),
+            #[cfg(unix)]
+            Io::Unix(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unix_socket_path_forms() {
+        assert_eq!(unix_socket_path("unix:///run/app.sock"), Some("/run/app.sock"));
+        assert_eq!(unix_socket_path("unix:/run/app.sock"), Some("/run/app.sock"));
+        assert_eq!(unix_socket_path("unix:app.sock"), Some("app.sock"));
+        assert_eq!(unix_socket_path("localhost:50051"), None);
+    }
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_interleave_alternates_families() {
+        let resolved = ["[::1]:1", "[::2]:1", "[::3]:1", "10.0.0.1:1", "10.0.0.2:1"].map(addr).to_vec();
+        assert_eq!(
+            interleave(resolved),
+            ["[::1]:1", "10.0.0.1:1", "[::2]:1", "10.0.0.2:1", "[::3]:1"].map(addr)
+        );
+        let v4_first = ["10.0.0.1:1", "[::1]:1"].map(addr).to_vec();
+        assert_eq!(interleave(v4_first.clone()), v4_first);
+    }
+
+    /// Connects to ports 1xx after xx ms; other ports fail at once.
+    async fn fake_connect(addr: SocketAddr) -> io::Result<u16> {
+        match addr.port() {
+            port @ 100..=199 => {
+                tokio::time::sleep(Duration::from_millis(u64::from(port - 100))).await;
+                Ok(port)
+            }
+            port => Err(io::Error::new(io::ErrorKind::ConnectionRefused, format!("refused {}", port))),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_race_moves_on_when_an_attempt_fails() {
+        let addrs = ["127.0.0.1:1", "127.0.0.1:2", "127.0.0.1:100"].map(addr).to_vec();
+        let started = Instant::now();
+        assert_eq!(race(addrs, Duration::from_secs(5), fake_connect).await.unwrap(), 100);
+        assert!(started.elapsed() < Duration::from_secs(1), "failures shouldn't wait out the delay");
+
+        let err = race(["127.0.0.1:1", "127.0.0.1:2"].map(addr).to_vec(), Duration::from_secs(5), fake_connect)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "refused 2");
+    }
+
+    #[tokio::test]
+    async fn test_race_starts_the_next_address_after_the_delay() {
+        // The first address would take far longer than the second.
+        let addrs = ["127.0.0.1:199", "127.0.0.1:120"].map(addr).to_vec();
+        assert_eq!(race(addrs, Duration::from_millis(10), fake_connect).await.unwrap(), 120);
+    }
+
+    #[tokio::test]
+    async fn test_connect_falls_back_to_a_listening_address() {
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let open = listener.local_addr().unwrap();
+
+        let timeout = Duration::from_secs(1);
+        let stream = race(vec![closed, open], ATTEMPT_DELAY, |a| connect_addr(a, timeout)).await.unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), open);
+    }
+
+    // -- TLS ----------------------------------------------------------------
+
+    const CERT: &[u8] = include_bytes!("../testdata/localhost.pem");
+    const KEY: &[u8] = include_bytes!("../testdata/localhost-key.pem");
+
+    fn cert() -> rustls::Certificate {
+        rustls::Certificate(rustls_pemfile::certs(&mut &CERT[..]).unwrap().remove(0))
+    }
+
+    /// A TLS server for localhost offering `alpn`, holding each connection open
+    /// until the client closes it.
+    async fn tls_server(alpn: &[&[u8]]) -> u16 {
+        let key = rustls::PrivateKey(rustls_pemfile::pkcs8_private_keys(&mut &KEY[..]).unwrap().remove(0));
+        let mut config = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert()], key)
+            .unwrap();
+        config.alpn_protocols = alpn.iter().map(|p| p.to_vec()).collect();
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((tcp, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    if let Ok(mut tls) = acceptor.accept(tcp).await {
+                        let _ = tokio::io::copy(&mut tls, &mut tokio::io::sink()).await;
+                    }
+                });
+            }
+        });
+        port
+    }
+
+    fn client_config(trusted: bool) -> rustls::ClientConfig {
+        let mut roots = rustls::RootCertStore::empty();
+        if trusted {
+            roots.add(&cert()).unwrap();
+        }
+        let mut config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        config
+    }
+
+    async fn connect_tls(port: u16, trusted: bool) -> Result<Stream, BoxError> {
+        let uri: Uri = format!("https://localhost:{}/", port).parse().unwrap();
+        Connector::tcp(Some(client_config(trusted))).call(uri).await
+    }
+
+    #[tokio::test]
+    async fn test_tls_reports_negotiated_protocol() {
+        let h2 = connect_tls(tls_server(&[b"h2"]).await, true).await.unwrap();
+        assert!(h2.negotiated_h2);
+        assert!(h2.timings.tls.is_some() && h2.timings.dns.is_some());
+
+        let http1 = connect_tls(tls_server(&[b"http/1.1"]).await, true).await.unwrap();
+        assert!(!http1.negotiated_h2);
+
+        // A server without ALPN leaves the HTTP version to the client's settings.
+        let none = connect_tls(tls_server(&[]).await, true).await.unwrap();
+        assert!(!none.negotiated_h2);
+    }
+
+    #[tokio::test]
+    async fn test_tls_handshake_failure() {
+        let err = connect_tls(tls_server(&[b"h2"]).await, false).await.err().unwrap();
+        assert!(err.to_string().starts_with("tls handshake error"), "{}", err);
+    }
+}
@@ -0,0 +1,1211 @@
+use crate::compression::{self, Encoding};
+use crate::connector::{self, Connector};
+use crate::grpc_web::{self, Transport, TransportConfig};
+use crate::message_format::MessageFormat;
+use crate::wire::{self, Inspected};
+use crate::{metadata, status_details, timing};
+use base64::{engine::general_purpose, Engine as _};
+use bytes::Buf;
+use http::{HeaderMap, Request as HttpRequest, Uri};
+use hyper::body::HttpBody;
+use hyper::{Body, Client};
+use prost::Message as ProstMessage;
+use prost_reflect::{DescriptorPool, DynamicMessage, MethodDescriptor, ServiceDescriptor};
+use rustls::{Certificate, PrivateKey};
+use rustls_pemfile::{certs, pkcs8_private_keys};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::BufReader;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+// ---------------------------------------------------------------------------
+// Shared types
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TlsConfig {
+    pub enabled: bool,
+    pub client_cert_path: Option<String>,
+    pub client_key_path: Option<String>,
+    pub server_ca_cert_path: Option<String>,
+    pub insecure_skip_verify: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthConfig {
+    #[serde(rename = "type")]
+    pub auth_type: String,
+    pub token: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub key: Option<String>,
+    pub value: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Endpoint and service lookup
+// ---------------------------------------------------------------------------
+
+pub fn clean_endpoint(endpoint: &str) -> String {
+    endpoint
+        .trim_start_matches("http://")
+        .trim_start_matches("https://")
+        .to_string()
+}
+
+/// Look up a service by fully-qualified name (`pkg.sub.Service`), falling back to the
+/// short name for requests saved before names were qualified. A short name defined
+/// in several packages is an error listing the candidates.
+pub fn find_service(pool: &DescriptorPool, service: &str) -> Result<ServiceDescriptor, String> {
+    let service = service.trim_start_matches('.');
+    if let Some(desc) = pool.get_service_by_name(service) {
+        return Ok(desc);
+    }
+
+    let mut matches: Vec<_> = pool.services().filter(|s| s.name() == service).collect();
+    match matches.len() {
+        0 => Err(format!("Service '{}' not found in proto", service)),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            let mut candidates: Vec<&str> = matches.iter().map(|s| s.full_name()).collect();
+            candidates.sort();
+            Err(format!(
+                "Service name '{}' is ambiguous; use a fully-qualified name: {}",
+                service,
+                candidates.join(", ")
+            ))
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// TLS helpers
+// ---------------------------------------------------------------------------
+
+fn load_certificates(path: &str) -> Result<Vec<Certificate>, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open certificate '{}': {}", path, e))?;
+    let certs = certs(&mut BufReader::new(file))
+        .map_err(|e| format!("Failed to parse certificates from '{}': {}", path, e))?;
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn load_private_key(path: &str) -> Result<PrivateKey, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open private key '{}': {}", path, e))?;
+    pkcs8_private_keys(&mut BufReader::new(file))
+        .map_err(|e| format!("Failed to parse private key from '{}': {}", path, e))?
+        .into_iter()
+        .next()
+        .map(PrivateKey)
+        .ok_or_else(|| format!("No private key found in '{}'", path))
+}
+
+struct NoCertificateVerification;
+
+impl rustls::client::ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+/// Build the TLS client config. `http2` picks the ALPN protocols offered: h2 only,
+/// HTTP/1.1 only, or both (`None`, letting the server choose; gRPC-Web only).
+fn build_tls_config(cfg: &TlsConfig, http2: Option<bool>) -> Result<rustls::ClientConfig, String> {
+    let mut root_store = rustls::RootCertStore::empty();
+    if let Some(ca_path) = &cfg.server_ca_cert_path {
+        for cert in load_certificates(ca_path)? {
+            root_store.add(&cert)
+                .map_err(|e| format!("Failed to add CA certificate: {}", e))?;
+        }
+    } else {
+        root_store.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+            rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+                ta.subject.to_vec(),
+                ta.spki.to_vec(),
+                ta.name_constraints.as_ref().map(|nc| nc.to_vec()),
+            )
+        }));
+    }
+
+    let builder = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_store);
+
+    let client_config = if let (Some(cert), Some(key)) =
+        (&cfg.client_cert_path, &cfg.client_key_path)
+    {
+        builder
+            .with_client_auth_cert(load_certificates(cert)?, load_private_key(key)?)
+            .map_err(|e| format!("Failed to configure client auth: {}", e))?
+    } else {
+        builder.with_no_client_auth()
+    };
+
+    let mut final_config = if cfg.insecure_skip_verify.unwrap_or(false) {
+        let mut c = client_config;
+        c.dangerous()
+            .set_certificate_verifier(Arc::new(NoCertificateVerification));
+        c
+    } else {
+        client_config
+    };
+
+    final_config.alpn_protocols = match http2 {
+        Some(true) => vec![b"h2".to_vec()],
+        Some(false) => vec![b"http/1.1".to_vec()],
+        None => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+    };
+    Ok(final_config)
+}
+
+/// HTTP version for a call: native gRPC is always HTTP/2; gRPC-Web uses whichever
+/// version the transport config asks for (`None` lets ALPN decide).
+pub fn http2_preference(transport: Transport, transport_config: Option<&TransportConfig>) -> Option<bool> {
+    match transport {
+        Transport::Grpc => Some(true),
+        _ => transport_config.and_then(|c| c.http2),
+    }
+}
+
+/// Build a client for the given endpoint. `unix:` endpoints connect to the socket in
+/// cleartext. Calls should go through `AppState::channel` so connections are reused.
+pub fn build_client(
+    endpoint: &str,
+    tls: Option<&TlsConfig>,
+    http2: Option<bool>,
+) -> Result<Client<Connector, Body>, String> {
+    let connector = match connector::unix_socket_path(endpoint) {
+        Some(_) if tls.is_some_and(|c| c.enabled) => {
+            return Err("TLS is not supported for unix: endpoints".to_string())
+        }
+        Some(path) => Connector::unix(path)?,
+        None => Connector::tcp(tls.filter(|c| c.enabled).map(|c| build_tls_config(c, http2)).transpose()?),
+    };
+    Ok(Client::builder()
+        .http2_only(http2 == Some(true))
+        .build::<_, Body>(connector))
+}
+
+/// URI for a call to `path` on `endpoint`. A socket path can't go in a URI, so
+/// `unix:` endpoints use the configured `:authority` (default "localhost") instead.
+pub fn request_uri(
+    endpoint: &str,
+    tls: Option<&TlsConfig>,
+    transport_config: Option<&TransportConfig>,
+    path: &str,
+) -> Result<Uri, String> {
+    let use_tls = tls.map(|c| c.enabled).unwrap_or(false);
+    let authority = match connector::unix_socket_path(endpoint) {
+        Some(_) => transport_config
+            .and_then(|c| c.authority.as_deref())
+            .unwrap_or(connector::DEFAULT_UDS_AUTHORITY),
+        None => endpoint,
+    };
+    format!("{}://{}{}", if use_tls { "https" } else { "http" }, authority, path)
+        .parse()
+        .map_err(|e| format!("Invalid URI: {}", e))
+}
+
+// ---------------------------------------------------------------------------
+// Auth header helper
+// ---------------------------------------------------------------------------
+
+fn apply_auth(mut builder: hyper::http::request::Builder, auth: &AuthConfig) -> hyper::http::request::Builder {
+    match auth.auth_type.as_str() {
+        "bearer" => {
+            if let Some(token) = &auth.token {
+                builder = builder.header("authorization", format!("Bearer {}", token));
+            }
+        }
+        "basic" => {
+            if let (Some(u), Some(p)) = (&auth.username, &auth.password) {
+                let encoded = general_purpose::STANDARD.encode(format!("{}:{}", u, p).as_bytes());
+                builder = builder.header("authorization", format!("Basic {}", encoded));
+            }
+        }
+        "apiKey" => {
+            if let (Some(k), Some(v)) = (&auth.key, &auth.value) {
+                builder = builder.header(k.as_str(), v.as_str());
+            }
+        }
+        _ => {}
+    }
+    builder
+}
+
+/// Start a gRPC (or gRPC-Web) POST to `uri` with the standard headers, auth and custom
+/// metadata applied.
+pub fn grpc_request_builder(
+    uri: Uri,
+    auth: Option<&AuthConfig>,
+    metadata: Option<&[(String, String)]>,
+    timeout_ms: Option<u64>,
+    encoding: Encoding,
+    transport: Transport,
+) -> Result<hyper::http::request::Builder, String> {
+    let mut builder = HttpRequest::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", transport.content_type())
+        .header("grpc-accept-encoding", compression::ACCEPT_ENCODING);
+
+    builder = if transport.is_web() {
+        builder
+            .header("accept", transport.content_type())
+            .header("x-grpc-web", "1")
+    } else {
+        builder.header("te", "trailers")
+    };
+
+    if encoding != Encoding::Identity {
+        builder = builder.header("grpc-encoding", encoding.as_str());
+    }
+
+    if let Some(ms) = timeout_ms {
+        builder = builder.header("grpc-timeout", grpc_timeout_value(ms));
+    }
+
+    if let Some(a) = auth {
+        builder = apply_auth(builder, a);
+    }
+    if let Some(meta) = metadata {
+        metadata::validate(meta)?;
+        for (k, v) in meta {
+            builder = builder.header(k.as_str(), metadata::encode_value(k, v)?);
+        }
+    }
+    Ok(builder)
+}
+
+/// Encode a timeout for the `grpc-timeout` header, which allows at most 8 digits
+/// per value. Falls back to coarser units (rounding up) for very long timeouts.
+fn grpc_timeout_value(ms: u64) -> String {
+    const MAX_DIGITS: u64 = 99_999_999;
+    if ms <= MAX_DIGITS {
+        format!("{}m", ms)
+    } else if ms.div_ceil(1000) <= MAX_DIGITS {
+        format!("{}S", ms.div_ceil(1000))
+    } else {
+        format!("{}M", ms.div_ceil(60_000).min(MAX_DIGITS))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// gRPC framing helpers
+// ---------------------------------------------------------------------------
+
+pub(crate) fn grpc_frame(protobuf_bytes: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(5 + protobuf_bytes.len());
+    frame.push(0u8); // no compression
+    frame.extend_from_slice(&(protobuf_bytes.len() as u32).to_be_bytes());
+    frame.extend_from_slice(protobuf_bytes);
+    frame
+}
+
+/// Frame a message for sending, compressing it and setting the compressed flag
+/// unless `encoding` is identity.
+pub fn encode_message(protobuf_bytes: &[u8], encoding: Encoding) -> Result<Vec<u8>, String> {
+    if encoding == Encoding::Identity {
+        return Ok(grpc_frame(protobuf_bytes));
+    }
+    let mut frame = grpc_frame(&compression::compress(encoding, protobuf_bytes)?);
+    frame[0] = 1;
+    Ok(frame)
+}
+
+/// Pop the next complete length-prefixed message off the front of `buf`, leaving any
+/// partial frame in place until more data arrives. Returns the frame's compressed
+/// flag alongside its (still possibly compressed) payload.
+pub(crate) fn next_frame(buf: &mut bytes::BytesMut) -> Option<(bool, bytes::Bytes)> {
+    if buf.len() < 5 {
+        return None;
+    }
+    let compressed = buf[0] & 1 == 1;
+    let msg_len = u32::from_be_bytes([buf[1], buf[2], buf[3], buf[4]]) as usize;
+    if buf.len() < 5 + msg_len {
+        return None;
+    }
+    buf.advance(5);
+    Some((compressed, buf.split_to(msg_len).freeze()))
+}
+
+/// Splits a response body into messages as chunks arrive, undoing compression and,
+/// for gRPC-Web, the text encoding and the in-body trailer frame.
+struct FrameReader {
+    buf: bytes::BytesMut,
+    encoding: Encoding,
+    text: Option<grpc_web::TextDecoder>,
+    trailers: Option<HeaderMap>,
+}
+
+impl FrameReader {
+    fn new(headers: &HeaderMap, transport: Transport) -> Result<Self, String> {
+        Ok(FrameReader {
+            buf: bytes::BytesMut::new(),
+            encoding: Encoding::from_headers(headers)?,
+            text: (transport == Transport::GrpcWebText).then(grpc_web::TextDecoder::default),
+            trailers: None,
+        })
+    }
+
+    fn push(&mut self, chunk: &[u8]) -> Result<(), String> {
+        match &mut self.text {
+            Some(decoder) => decoder.push(chunk, &mut self.buf),
+            None => {
+                self.buf.extend_from_slice(chunk);
+                Ok(())
+            }
+        }
+    }
+
+    /// Next complete message, if one is buffered. A gRPC-Web trailer frame is kept
+    /// aside and ends the message stream.
+    fn next_message(&mut self) -> Result<Option<bytes::Bytes>, String> {
+        let Some(&flag) = self.buf.first() else {
+            return Ok(None);
+        };
+        let Some((compressed, data)) = next_frame(&mut self.buf) else {
+            return Ok(None);
+        };
+        if flag & grpc_web::TRAILERS_FLAG != 0 {
+            self.trailers = Some(grpc_web::parse_trailers(&data));
+            return Ok(None);
+        }
+        compression::decode_frame(compressed, data, self.encoding).map(Some)
+    }
+
+    /// Drop buffered messages without decompressing them. A gRPC-Web trailer frame is
+    /// kept aside as in `next_message`.
+    fn skip_messages(&mut self) {
+        while self.trailers.is_none() {
+            let Some(&flag) = self.buf.first() else {
+                return;
+            };
+            let Some((_, data)) = next_frame(&mut self.buf) else {
+                return;
+            };
+            if flag & grpc_web::TRAILERS_FLAG != 0 {
+                self.trailers = Some(grpc_web::parse_trailers(&data));
+            }
+        }
+    }
+
+    /// Consume whatever is left of the body and return the call's trailers: the
+    /// gRPC-Web trailer frame if one arrived, otherwise the HTTP trailers.
+    fn finish(mut self, http_trailers: Option<HeaderMap>) -> Result<Option<HeaderMap>, String> {
+        if let Some(decoder) = &self.text {
+            decoder.finish()?;
+        }
+        while self.trailers.is_none() && self.next_message()?.is_some() {}
+        Ok(self.trailers.or(http_trailers))
+    }
+}
+
+/// Drain a unary response body, returning its message (if any) plus the trailers,
+/// and stamping the arrival of both on `timer`.
+async fn read_unary(
+    mut body: Body,
+    mut reader: FrameReader,
+    timer: &mut timing::CallTimer,
+) -> Result<(Option<bytes::Bytes>, Option<HeaderMap>), String> {
+    let mut message = None;
+    while let Some(chunk) = body.data().await {
+        reader.push(&chunk.map_err(|e| format!("Failed to read response: {}", e))?)?;
+        while let Some(msg_bytes) = reader.next_message()? {
+            if message.is_none() {
+                timer.message();
+                message = Some(msg_bytes);
+            }
+        }
+    }
+    let http_trailers = body
+        .trailers()
+        .await
+        .map_err(|e| format!("Failed to read trailers: {}", e))?;
+    let trailers = reader.finish(http_trailers)?;
+    timer.trailers();
+    Ok((message, trailers))
+}
+
+/// Resolve the call's final status. Servers normally send `grpc-status` in the
+/// trailers; trailers-only responses (typically immediate errors) put it in the
+/// headers instead. Returns "unknown" when neither carries a status.
+pub(crate) fn grpc_status(headers: &HeaderMap, trailers: Option<&HeaderMap>) -> (String, String) {
+    let source = trailers
+        .filter(|t| t.contains_key("grpc-status"))
+        .unwrap_or(headers);
+    let code = source
+        .get("grpc-status")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let message = source
+        .get("grpc-message")
+        .and_then(|v| v.to_str().ok())
+        .map(percent_decode)
+        .unwrap_or_default();
+    (code, message)
+}
+
+/// Decode a percent-encoded `grpc-message` value. Invalid escapes are kept verbatim,
+/// as the gRPC spec asks receivers to do.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// ---------------------------------------------------------------------------
+// Error formatting
+// ---------------------------------------------------------------------------
+
+/// A call that failed without the server returning a gRPC status.
+#[derive(Clone, Copy, Debug)]
+pub enum ConnectionError<'a> {
+    /// The connection or HTTP/2 exchange failed; classified by its message.
+    Transport(&'a str),
+    /// The call's deadline passed before it completed.
+    DeadlineExceeded { timeout_ms: u64 },
+}
+
+pub fn format_connection_error(error: ConnectionError, endpoint: &str, service: &str, method: &str) -> String {
+    let (raw, grpc_status, category, hints) = match error {
+        ConnectionError::DeadlineExceeded { timeout_ms } => (
+            format!("deadline exceeded after {} ms", timeout_ms),
+            "DEADLINE_EXCEEDED",
+            "Deadline Exceeded",
+            vec![
+                "The call did not complete within the configured timeout".to_string(),
+                "Increase the timeout or check for slow handlers on the server".to_string(),
+            ],
+        ),
+        ConnectionError::Transport(raw) => {
+            let (category, hints) = classify_transport_error(raw);
+            (raw.to_string(), "UNAVAILABLE", category, hints)
+        }
+    };
+
+    serde_json::json!({
+        "status": "error",
+        "error": raw,
+        "error_category": category,
+        "troubleshooting_hints": hints,
+        "grpc_status": grpc_status,
+        "grpc_message": raw,
+        "endpoint": endpoint,
+        "service": service,
+        "method": method,
+        "response": null,
+    })
+    .to_string()
+}
+
+/// Category and troubleshooting hints for a transport failure, from its message.
+fn classify_transport_error(raw: &str) -> (&'static str, Vec<String>) {
+    if raw.contains("certificate") || raw.contains("tls") || raw.contains("ssl") {
+        ("TLS/Certificate Error", vec![
+            "Server may require TLS but TLS is not enabled".to_string(),
+            "Try 'Insecure Skip Verify' for self-signed certs in development".to_string(),
+            "Check that cert/key file paths are correct".to_string(),
+        ])
+    } else if raw.contains("connection refused") {
+        ("Connection Refused", vec![
+            "Server may not be running".to_string(),
+            "Check host and port".to_string(),
+        ])
+    } else if raw.contains("broken pipe") || raw.contains("stream closed") || raw.contains("connection reset") {
+        ("Connection Closed", vec![
+            "TLS mismatch: server expects TLS but client is not using it (or vice versa)".to_string(),
+            "Server closed the connection during handshake".to_string(),
+        ])
+    } else if raw.contains("timeout") || raw.contains("timed out") {
+        ("Connection Timeout", vec![
+            "Server took too long to respond".to_string(),
+        ])
+    } else {
+        ("Error", vec![])
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Reflection
+// ---------------------------------------------------------------------------
+
+/// Fetch the server's descriptors through reflection. Reflection always uses native
+/// gRPC; of the transport options only the `:authority` override applies.
+pub async fn reflect(
+    client: &Client<Connector, Body>,
+    endpoint: &str,
+    options: &CallOptions,
+) -> Result<(DescriptorPool, Vec<String>), String> {
+    let endpoint = clean_endpoint(endpoint);
+    crate::reflection::fetch_descriptor_pool(client, |path| {
+        let uri = request_uri(&endpoint, options.tls.as_ref(), options.transport_config.as_ref(), path)?;
+        grpc_request_builder(
+            uri,
+            options.auth.as_ref(),
+            options.metadata.as_deref(),
+            None,
+            Encoding::Identity,
+            Transport::Grpc,
+        )
+    })
+    .await
+    .map_err(|e| format_connection_error(ConnectionError::Transport(&e), &endpoint, "grpc.reflection.v1.ServerReflection", "ServerReflectionInfo"))
+}
+
+// ---------------------------------------------------------------------------
+// Calls
+// ---------------------------------------------------------------------------
+
+/// Split a stream body into messages. JSON bodies may be an array of messages or a
+/// single message spanning several lines; anything else is one message per
+/// non-empty line.
+pub fn split_messages(data: &str, format: MessageFormat) -> Result<Vec<String>, String> {
+    let trimmed = data.trim();
+    if matches!(format, MessageFormat::Json(_)) && trimmed.starts_with('[') {
+        let items: Vec<Value> =
+            serde_json::from_str(trimmed).map_err(|e| format!("Failed to parse request JSON array: {}", e))?;
+        return Ok(items.iter().map(Value::to_string).collect());
+    }
+    if matches!(format, MessageFormat::Json(_)) && serde_json::from_str::<Value>(trimmed).is_ok() {
+        return Ok(vec![trimmed.to_string()]);
+    }
+    Ok(trimmed
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// How a call is made and how its responses are rendered: everything except the
+/// method and the request body.
+#[derive(Clone, Default)]
+pub struct CallOptions {
+    /// Request metadata in the order given; a key may repeat.
+    pub metadata: Option<Vec<(String, String)>>,
+    pub auth: Option<AuthConfig>,
+    pub tls: Option<TlsConfig>,
+    pub timeout_ms: Option<u64>,
+    /// Compression applied to request messages.
+    pub encoding: Encoding,
+    pub transport_config: Option<TransportConfig>,
+    pub response_format: MessageFormat,
+    /// Attach the schema-less wire view to every response message.
+    pub include_raw: bool,
+    /// Message types to decode `-bin` response metadata as, keyed by header name.
+    pub bin_metadata_types: Option<HashMap<String, String>>,
+}
+
+/// A method resolved on an endpoint, ready to call. Used by the app commands and
+/// the CLI alike.
+#[derive(Clone)]
+pub struct Call {
+    /// Endpoint without its `http://` / `https://` prefix.
+    pub endpoint: String,
+    /// Service name as the caller gave it, echoed in results.
+    pub service: String,
+    pub method: MethodDescriptor,
+    pub transport: Transport,
+    pub options: CallOptions,
+    pool: Arc<DescriptorPool>,
+}
+
+impl Call {
+    pub fn new(
+        pool: Arc<DescriptorPool>,
+        endpoint: &str,
+        service: &str,
+        method: &str,
+        options: CallOptions,
+    ) -> Result<Self, String> {
+        let method = find_service(&pool, service)?
+            .methods()
+            .find(|m| m.name() == method)
+            .ok_or_else(|| format!("Method '{}' not found in service '{}'", method, service))?;
+        let transport = options
+            .transport_config
+            .as_ref()
+            .map(|c| c.transport())
+            .transpose()?
+            .unwrap_or(Transport::Grpc);
+        // gRPC-Web carries exactly one request message per call.
+        if transport.is_web() && method.is_client_streaming() {
+            return Err(format!(
+                "{} is {}-streaming, which gRPC-Web does not support; use the grpc transport",
+                method.full_name(),
+                if method.is_server_streaming() { "bidirectional" } else { "client" }
+            ));
+        }
+        Ok(Call {
+            endpoint: clean_endpoint(endpoint),
+            service: service.to_string(),
+            method,
+            transport,
+            options,
+            pool,
+        })
+    }
+
+    /// A new client for this call's endpoint, TLS settings and HTTP version. The app
+    /// pools these per endpoint instead; see `AppState::channel`.
+    pub fn connect(&self) -> Result<Client<Connector, Body>, String> {
+        let http2 = http2_preference(self.transport, self.options.transport_config.as_ref());
+        build_client(&self.endpoint, self.options.tls.as_ref(), http2)
+    }
+
+    /// Frame a request message for the wire with the call's compression.
+    pub fn encode(&self, msg: &DynamicMessage) -> Result<Vec<u8>, String> {
+        encode_message(&msg.encode_to_vec(), self.options.encoding)
+    }
+
+    /// Make a unary or server-streaming call and build its result. `on_message` sees
+    /// each response message as it is decoded, with its index; `request_echo` is
+    /// returned as the result's `request`.
+    pub async fn unary(
+        &self,
+        client: &Client<Connector, Body>,
+        request: &DynamicMessage,
+        request_echo: Value,
+        mut on_message: impl FnMut(usize, &Inspected),
+    ) -> Result<Value, String> {
+        let req = self.request(Body::from(self.transport.encode_body(self.encode(request)?)))?;
+        let output_desc = self.method.output();
+        let is_server_streaming = self.method.is_server_streaming();
+        let response_format = self.options.response_format;
+        let include_raw = self.options.include_raw;
+
+        let exchange = async {
+            let mut timer = timing::CallTimer::start();
+            let response = client
+                .request(req)
+                .await
+                .map_err(|e| self.connection_error(ConnectionError::Transport(&e.to_string())))?;
+            timer.headers(response.extensions());
+
+            let (parts, mut body) = response.into_parts();
+            let mut reader = FrameReader::new(&parts.headers, self.transport)?;
+            let mut response_data = None;
+            let mut raw_response = None;
+            let mut decode_error = None;
+            let mut unknown_fields = Vec::new();
+            let trailers;
+
+            if is_server_streaming {
+                let mut messages = Vec::new();
+                let mut raw_messages = Vec::new();
+
+                while let Some(chunk) = body.data().await {
+                    reader.push(&chunk.map_err(|e| format!("Stream read error: {}", e))?)?;
+                    while let Some(msg_bytes) = reader.next_message()? {
+                        timer.message();
+                        let inspected = wire::inspect(&output_desc, &msg_bytes, response_format, include_raw)
+                            .unwrap_or_else(|e| wire::undecodable(&msg_bytes, e));
+                        on_message(messages.len(), &inspected);
+                        let index = messages.len();
+                        unknown_fields.extend(inspected.unknown_fields.iter().map(|path| format!("[{}].{}", index, path)));
+                        messages.push(inspected.data);
+                        raw_messages.push(inspected.raw.unwrap_or(Value::Null));
+                    }
+                }
+                if raw_messages.iter().any(|r| !r.is_null()) {
+                    raw_response = Some(Value::Array(raw_messages));
+                }
+                let http_trailers = body
+                    .trailers()
+                    .await
+                    .map_err(|e| format!("Failed to read trailers: {}", e))?;
+                trailers = reader.finish(http_trailers)?;
+                timer.trailers();
+                response_data = Some(Value::Array(messages));
+            } else {
+                let (message, t) = read_unary(body, reader, &mut timer).await?;
+                trailers = t;
+
+                if let Some(msg_bytes) = message {
+                    let inspected = wire::inspect(&output_desc, &msg_bytes, response_format, include_raw)?;
+                    response_data = Some(inspected.data);
+                    raw_response = inspected.raw;
+                    decode_error = inspected.decode_error;
+                    unknown_fields = inspected.unknown_fields;
+                }
+            }
+
+            Ok::<_, String>((parts, response_data, raw_response, decode_error, unknown_fields, trailers, timer))
+        };
+
+        let (parts, response_data, raw_response, decode_error, unknown_fields, trailers, timer) =
+            self.with_deadline(exchange).await?;
+
+        let (grpc_status, grpc_message) = grpc_status(&parts.headers, trailers.as_ref());
+        let error_details =
+            status_details::error_details(&parts.headers, trailers.as_ref(), &self.pool).unwrap_or_default();
+
+        let response_size = response_data
+            .as_ref()
+            .and_then(|d| serde_json::to_string(d).ok())
+            .map(|s| s.len())
+            .unwrap_or(0);
+
+        Ok(serde_json::json!({
+            "status": if grpc_status == "0" { "success" } else { "error" },
+            "grpc_status": grpc_status,
+            "grpc_message": grpc_message,
+            "endpoint": self.endpoint,
+            "service": self.service,
+            "method": self.method.name(),
+            "is_streaming": is_server_streaming,
+            "message_count": match &response_data {
+                Some(Value::Array(messages)) if is_server_streaming => messages.len(),
+                other => usize::from(other.is_some()),
+            },
+            "request": request_echo,
+            "response": response_data,
+            "raw_response": raw_response,
+            "decode_error": decode_error,
+            "unknown_fields": unknown_fields,
+            "response_metadata": self.metadata_json(&parts.headers),
+            "response_trailers": self.trailers_json(trailers.as_ref()),
+            "error_details": error_details,
+            "response_size": response_size,
+            "timing": timer.to_json(),
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }))
+    }
+
+    /// Make a client- or bidi-streaming call (native gRPC only; see `new`), sending the
+    /// frames from `frames` (see `encode`) until the sender is dropped. `on_message`
+    /// sees bidi responses as they arrive. The deadline covers the whole stream,
+    /// including time spent waiting for messages to send.
+    pub async fn stream(
+        &self,
+        client: &Client<Connector, Body>,
+        mut frames: mpsc::UnboundedReceiver<Vec<u8>>,
+        mut on_message: impl FnMut(usize, &Inspected),
+    ) -> Result<Value, String> {
+        let output_desc = self.method.output();
+        let is_bidi = self.method.is_client_streaming() && self.method.is_server_streaming();
+        let response_format = self.options.response_format;
+        let include_raw = self.options.include_raw;
+
+        let exchange = async {
+            let (mut body_sender, body_receiver) = Body::channel();
+            let req = self.request(body_receiver)?;
+
+            let mut timer = timing::CallTimer::start();
+            let response_future = client.request(req);
+
+            let sender_task = tokio::spawn(async move {
+                while let Some(msg_bytes) = frames.recv().await {
+                    if body_sender.send_data(msg_bytes.into()).await.is_err() {
+                        break;
+                    }
+                }
+            });
+
+            let response = response_future.await.map_err(|e| self.connection_error(ConnectionError::Transport(&e.to_string())))?;
+            timer.headers(response.extensions());
+            let (parts, mut body) = response.into_parts();
+            let mut reader = FrameReader::new(&parts.headers, self.transport)?;
+
+            if is_bidi {
+                let mut idx = 0;
+
+                while let Some(chunk) = body.data().await {
+                    let c = chunk.map_err(|e| format!("Stream read error: {}", e))?;
+                    reader.push(&c)?;
+                    while let Some(msg_bytes) = reader.next_message()? {
+                        timer.message();
+                        let inspected = wire::inspect(&output_desc, &msg_bytes, response_format, include_raw)
+                            .unwrap_or_else(|e| wire::undecodable(&msg_bytes, e));
+                        on_message(idx, &inspected);
+                        idx += 1;
+                    }
+                }
+                let http_trailers = body
+                    .trailers()
+                    .await
+                    .map_err(|e| format!("Failed to read trailers: {}", e))?;
+                let trailers = reader.finish(http_trailers)?;
+                timer.trailers();
+                let _ = sender_task.await;
+
+                let (grpc_status, grpc_message) = grpc_status(&parts.headers, trailers.as_ref());
+                let error_details = status_details::error_details(&parts.headers, trailers.as_ref(), &self.pool)
+                    .unwrap_or_default();
+                Ok(serde_json::json!({
+                    "status": if grpc_status == "0" { "success" } else { "error" },
+                    "grpc_status": grpc_status,
+                    "grpc_message": grpc_message,
+                    "message": "Bidirectional stream completed",
+                    "message_count": idx,
+                    "response_metadata": self.metadata_json(&parts.headers),
+                    "response_trailers": self.trailers_json(trailers.as_ref()),
+                    "error_details": error_details,
+                    "timing": timer.to_json(),
+                }))
+            } else {
+                let _ = sender_task.await;
+                let (message, trailers) = read_unary(body, reader, &mut timer).await?;
+                let (grpc_status, grpc_message) = grpc_status(&parts.headers, trailers.as_ref());
+                let error_details = status_details::error_details(&parts.headers, trailers.as_ref(), &self.pool)
+                    .unwrap_or_default();
+
+                // A failed call legitimately carries no message, only a status.
+                let inspected = match message {
+                    Some(msg_bytes) => wire::inspect(&output_desc, &msg_bytes, response_format, include_raw)?,
+                    None if grpc_status == "0" => return Err("Response too short".to_string()),
+                    None => Inspected { data: Value::Null, raw: None, decode_error: None, unknown_fields: Vec::new() },
+                };
+                Ok(serde_json::json!({
+                    "status": if grpc_status == "0" { "success" } else { "error" },
+                    "response": inspected.data,
+                    "raw_response": inspected.raw,
+                    "decode_error": inspected.decode_error,
+                    "unknown_fields": inspected.unknown_fields,
+                    "grpc_status": grpc_status,
+                    "grpc_message": grpc_message,
+                    "response_metadata": self.metadata_json(&parts.headers),
+                    "response_trailers": self.trailers_json(trailers.as_ref()),
+                    "error_details": error_details,
+                    "timing": timer.to_json(),
+                }))
+            }
+        };
+
+        self.with_deadline(exchange).await
+    }
+
+    /// Send pre-encoded request frames (see `encode`) and drain the response without
+    /// decoding it, returning only the final status code and message. Benchmarks time
+    /// this rather than `unary` / `stream`, which decode and render every message.
+    pub async fn status(&self, client: &Client<Connector, Body>, frames: &[Vec<u8>]) -> Result<(String, String), String> {
+        let req = self.request(Body::from(self.transport.encode_body(frames.concat())))?;
+
+        let exchange = async {
+            let response = client
+                .request(req)
+                .await
+                .map_err(|e| self.connection_error(ConnectionError::Transport(&e.to_string())))?;
+            let (parts, mut body) = response.into_parts();
+            let mut reader = FrameReader::new(&parts.headers, self.transport)?;
+            while let Some(chunk) = body.data().await {
+                reader.push(&chunk.map_err(|e| format!("Failed to read response: {}", e))?)?;
+                reader.skip_messages();
+            }
+            let http_trailers = body
+                .trailers()
+                .await
+                .map_err(|e| format!("Failed to read trailers: {}", e))?;
+            let trailers = reader.finish(http_trailers)?;
+            Ok(grpc_status(&parts.headers, trailers.as_ref()))
+        };
+
+        self.with_deadline(exchange).await
+    }
+
+    fn request(&self, body: Body) -> Result<HttpRequest<Body>, String> {
+        let path = format!("/{}/{}", self.method.parent_service().full_name(), self.method.name());
+        let uri = request_uri(&self.endpoint, self.options.tls.as_ref(), self.options.transport_config.as_ref(), &path)?;
+        let options = &self.options;
+        grpc_request_builder(
+            uri,
+            options.auth.as_ref(),
+            options.metadata.as_deref(),
+            options.timeout_ms,
+            options.encoding,
+            self.transport,
+        )?
+        .body(body)
+        .map_err(|e| format!("Failed to build request: {}", e))
+    }
+
+    /// The server is told the deadline via grpc-timeout, but it's enforced locally
+    /// too so an unresponsive server or network can't hang the call.
+    async fn with_deadline<T>(&self, exchange: impl Future<Output = Result<T, String>>) -> Result<T, String> {
+        match self.options.timeout_ms {
+            Some(ms) => tokio::time::timeout(Duration::from_millis(ms), exchange)
+                .await
+                .unwrap_or_else(|_| Err(self.connection_error(ConnectionError::DeadlineExceeded { timeout_ms: ms }))),
+            None => exchange.await,
+        }
+    }
+
+    fn connection_error(&self, error: ConnectionError) -> String {
+        format_connection_error(error, &self.endpoint, &self.service, self.method.name())
+    }
+
+    fn metadata_json(&self, headers: &HeaderMap) -> Value {
+        metadata::to_json(headers, self.options.bin_metadata_types.as_ref(), &self.pool)
+    }
+
+    fn trailers_json(&self, trailers: Option<&HeaderMap>) -> Value {
+        trailers.map(|t| self.metadata_json(t)).unwrap_or_else(|| serde_json::json!({}))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_decode_grpc_message() {
+        assert_eq!(percent_decode("user%20not%20found"), "user not found");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+        // Malformed escapes are passed through untouched
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz ok"), "%zz ok");
+    }
+
+    #[test]
+    fn test_grpc_timeout_value_fits_eight_digits() {
+        assert_eq!(grpc_timeout_value(1500), "1500m");
+        assert_eq!(grpc_timeout_value(99_999_999), "99999999m");
+        assert_eq!(grpc_timeout_value(100_000_001), "100001S");
+    }
+
+    #[test]
+    fn test_find_service_disambiguates_packages() {
+        let file = |package: &str| prost_types::FileDescriptorProto {
+            name: Some(format!("{}/health.proto", package)),
+            package: Some(package.to_string()),
+            message_type: vec![prost_types::DescriptorProto {
+                name: Some("Ping".to_string()),
+                ..Default::default()
+            }],
+            service: vec![prost_types::ServiceDescriptorProto {
+                name: Some("HealthService".to_string()),
+                method: vec![prost_types::MethodDescriptorProto {
+                    name: Some("Check".to_string()),
+                    input_type: Some(format!(".{}.Ping", package)),
+                    output_type: Some(format!(".{}.Ping", package)),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            syntax: Some("proto3".to_string()),
+            ..Default::default()
+        };
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_protos([file("billing.v1"), file("admin")]).unwrap();
+
+        assert_eq!(find_service(&pool, "billing.v1.HealthService").unwrap().full_name(), "billing.v1.HealthService");
+        let err = find_service(&pool, "HealthService").unwrap_err();
+        assert!(err.contains("admin.HealthService, billing.v1.HealthService"), "{}", err);
+        assert!(find_service(&pool, "Missing").is_err());
+    }
+
+    #[test]
+    fn test_grpc_status_prefers_trailers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("content-type", "application/grpc".parse().unwrap());
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", "5".parse().unwrap());
+        trailers.insert("grpc-message", "no%20such%20user".parse().unwrap());
+
+        assert_eq!(
+            grpc_status(&headers, Some(&trailers)),
+            ("5".to_string(), "no such user".to_string())
+        );
+
+        // Trailers-only response: status lives in the headers
+        headers.insert("grpc-status", "12".parse().unwrap());
+        assert_eq!(grpc_status(&headers, None).0, "12");
+
+        assert_eq!(grpc_status(&HeaderMap::new(), None).0, "unknown");
+    }
+
+    #[test]
+    fn test_connection_errors_are_classified_by_kind() {
+        let json = |error| serde_json::from_str::<Value>(&format_connection_error(error, "h:1", "pkg.S", "M")).unwrap();
+
+        let deadline = json(ConnectionError::DeadlineExceeded { timeout_ms: 250 });
+        assert_eq!(deadline["grpc_status"], "DEADLINE_EXCEEDED");
+        assert_eq!(deadline["error"], "deadline exceeded after 250 ms");
+
+        // A transport message that merely mentions a deadline is still a transport failure.
+        let transport = json(ConnectionError::Transport("upstream said: deadline exceeded"));
+        assert_eq!(transport["grpc_status"], "UNAVAILABLE");
+        assert_eq!(json(ConnectionError::Transport("connection refused"))["error_category"], "Connection Refused");
+    }
+
+    const WEB_PROTO: &str = r#"
+syntax = "proto3";
+package web.v1;
+service Feed {
+  rpc List (Page) returns (stream Page);
+  rpc Upload (stream Page) returns (Page);
+  rpc Chat (stream Page) returns (stream Page);
+}
+message Page { int32 n = 1; }
+"#;
+
+    fn web_call(addr: std::net::SocketAddr, mode: &str, method: &str) -> Result<Call, String> {
+        let pool = Arc::new(crate::proto_parser::compile_single_file(WEB_PROTO).unwrap());
+        let options = CallOptions {
+            transport_config: Some(TransportConfig {
+                mode: Some(mode.to_string()),
+                http2: Some(true),
+                authority: None,
+            }),
+            ..Default::default()
+        };
+        Call::new(pool, &addr.to_string(), "web.v1.Feed", method, options)
+    }
+
+    #[tokio::test]
+    async fn test_grpc_web_streams_several_messages() {
+        let addr = crate::test_server::serve(|req| async move {
+            let text = req.headers()["content-type"] == "application/grpc-web-text";
+            let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+            // The request body decodes as a whole, whatever the transport.
+            let body = if text { general_purpose::STANDARD.decode(&body).unwrap() } else { body.to_vec() };
+            let mut buf = bytes::BytesMut::from(&body[..]);
+            let (_, request) = next_frame(&mut buf).unwrap();
+            assert!(buf.is_empty());
+
+            // Echo the request page twice, then trailers; text bodies encode each
+            // frame separately, as streaming servers do.
+            let mut frames = vec![grpc_frame(&request), grpc_frame(&request)];
+            let trailer = b"grpc-status:0\r\n";
+            let mut trailer_frame = vec![grpc_web::TRAILERS_FLAG];
+            trailer_frame.extend_from_slice(&(trailer.len() as u32).to_be_bytes());
+            trailer_frame.extend_from_slice(trailer);
+            frames.push(trailer_frame);
+            let (content_type, body) = if text {
+                let encoded: Vec<String> = frames.iter().map(|f| general_purpose::STANDARD.encode(f)).collect();
+                ("application/grpc-web-text", encoded.concat().into_bytes())
+            } else {
+                ("application/grpc-web+proto", frames.concat())
+            };
+            hyper::Response::builder().header("content-type", content_type).body(Body::from(body)).unwrap()
+        });
+
+        for mode in ["grpc-web", "grpc-web-text"] {
+            let call = web_call(addr, mode, "List").unwrap();
+            let mut request = DynamicMessage::new(call.method.input());
+            request.set_field_by_name("n", prost_reflect::Value::I32(7));
+
+            let result = call.unary(&call.connect().unwrap(), &request, Value::Null, |_, _| {}).await.unwrap();
+            assert_eq!(result["grpc_status"], "0", "{}", mode);
+            assert_eq!(result["response"], serde_json::json!([{ "n": 7 }, { "n": 7 }]), "{}", mode);
+        }
+    }
+
+    #[test]
+    fn test_grpc_web_rejects_client_streaming() {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 1));
+        let err = web_call(addr, "grpc-web", "Upload").err().unwrap();
+        assert_eq!(err, "web.v1.Feed.Upload is client-streaming, which gRPC-Web does not support; use the grpc transport");
+        let err = web_call(addr, "grpc-web-text", "Chat").err().unwrap();
+        assert!(err.contains("is bidirectional-streaming"), "{}", err);
+        assert!(web_call(addr, "grpc", "Chat").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_bidi_keeps_undecodable_frames_in_place() {
+        let addr = crate::test_server::serve(|req| async move {
+            let request = crate::test_server::read_messages(req).await.remove(0);
+            crate::test_server::grpc_response(vec![request.clone(), vec![0x0f], request], 0, "")
+        });
+        let call = web_call(addr, "grpc", "Chat").unwrap();
+        let mut page = DynamicMessage::new(call.method.input());
+        page.set_field_by_name("n", prost_reflect::Value::I32(3));
+        let (frames, frames_rx) = mpsc::unbounded_channel();
+        frames.send(call.encode(&page).unwrap()).unwrap();
+        drop(frames);
+
+        let mut received = Vec::new();
+        let result = call
+            .stream(&call.connect().unwrap(), frames_rx, |index, inspected| {
+                received.push((index, inspected.data.clone(), inspected.decode_error.is_some()))
+            })
+            .await
+            .unwrap();
+        assert_eq!(result["status"], "success");
+        assert_eq!(result["message_count"], 3);
+        assert_eq!(
+            received,
+            [
+                (0, serde_json::json!({ "n": 3 }), false),
+                (1, Value::Null, true),
+                (2, serde_json::json!({ "n": 3 }), false),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_stream_reports_status_from_trailers() {
+        let addr = crate::test_server::serve(|req| async move {
+            assert_eq!(crate::test_server::read_messages(req).await.len(), 2);
+            crate::test_server::grpc_response(Vec::new(), 5, "no such page")
+        });
+        let call = web_call(addr, "grpc", "Upload").unwrap();
+        let page = DynamicMessage::new(call.method.input());
+        let (frames, frames_rx) = mpsc::unbounded_channel();
+        frames.send(call.encode(&page).unwrap()).unwrap();
+        frames.send(call.encode(&page).unwrap()).unwrap();
+        drop(frames);
+
+        let result = call.stream(&call.connect().unwrap(), frames_rx, |_, _| {}).await.unwrap();
+        assert_eq!(result["status"], "error");
+        assert_eq!(result["grpc_status"], "5");
+        assert_eq!(result["grpc_message"], "no such page");
+        assert!(result["response"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_local_deadline_reports_deadline_exceeded() {
+        let addr = crate::test_server::serve(|_req| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            crate::test_server::grpc_response(Vec::new(), 0, "")
+        });
+        let pool = Arc::new(
+            crate::proto_parser::compile_single_file(
+                "syntax = \"proto3\"; package slow.v1; service Slow { rpc Wait (Empty) returns (Empty); } message Empty {}",
+            )
+            .unwrap(),
+        );
+        let options = CallOptions { timeout_ms: Some(50), ..Default::default() };
+        let call = Call::new(pool, &addr.to_string(), "slow.v1.Slow", "Wait", options).unwrap();
+        let request = DynamicMessage::new(call.method.input());
+
+        let err = call.unary(&call.connect().unwrap(), &request, Value::Null, |_, _| {}).await.unwrap_err();
+        let err: Value = serde_json::from_str(&err).unwrap();
+        assert_eq!(err["grpc_status"], "DEADLINE_EXCEEDED");
+        assert_eq!(err["error_category"], "Deadline Exceeded");
+    }
+}
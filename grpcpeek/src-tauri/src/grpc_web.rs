@@ -0,0 +1,162 @@
+use base64::{engine::general_purpose, Engine as _};
+use http::header::{HeaderName, HeaderValue};
+use http::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+/// Flag bit marking a gRPC-Web frame that carries trailers instead of a message.
+pub const TRAILERS_FLAG: u8 = 0x80;
+
+/// How a call is carried over HTTP.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transport {
+    /// Native gRPC over HTTP/2 with HTTP trailers.
+    Grpc,
+    /// `application/grpc-web+proto`: binary frames, trailers sent as the last frame.
+    GrpcWeb,
+    /// `application/grpc-web-text`: as gRPC-Web, but the body is base64-encoded.
+    GrpcWebText,
+}
+
+/// Transport options accepted by the call commands.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TransportConfig {
+    /// "grpc" (default), "grpc-web" or "grpc-web-text".
+    pub mode: Option<String>,
+    /// gRPC-Web only: `true` forces HTTP/2 and `false` forces HTTP/1.1. When unset,
+    /// cleartext uses HTTP/1.1 and TLS lets ALPN pick.
+    pub http2: Option<bool>,
+    /// `:authority` for `unix:` endpoints. Defaults to "localhost".
+    pub authority: Option<String>,
+}
+
+impl TransportConfig {
+    pub fn transport(&self) -> Result<Transport, String> {
+        match self.mode.as_deref().map(|m| m.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("grpc") => Ok(Transport::Grpc),
+            Some("grpc-web") | Some("grpc-web+proto") => Ok(Transport::GrpcWeb),
+            Some("grpc-web-text") => Ok(Transport::GrpcWebText),
+            Some(other) => Err(format!(
+                "Unsupported transport '{}'. Supported: grpc, grpc-web, grpc-web-text",
+                other
+            )),
+        }
+    }
+}
+
+impl Transport {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Transport::Grpc => "application/grpc",
+            Transport::GrpcWeb => "application/grpc-web+proto",
+            Transport::GrpcWebText => "application/grpc-web-text",
+        }
+    }
+
+    pub fn is_web(&self) -> bool {
+        *self != Transport::Grpc
+    }
+
+    /// Encode a whole framed request body for the wire. A grpc-web-text body is one
+    /// base64 stream, so this takes every frame at once rather than one at a time.
+    pub fn encode_body(&self, frames: Vec<u8>) -> Vec<u8> {
+        match self {
+            Transport::GrpcWebText => general_purpose::STANDARD.encode(frames).into_bytes(),
+            _ => frames,
+        }
+    }
+}
+
+/// Incremental decoder for `grpc-web-text` response bodies. Servers may base64-encode
+/// each chunk separately, so padding can appear mid-stream; decoding proceeds one
+/// padded segment at a time and holds back incomplete quads until more data arrives.
+#[derive(Default)]
+pub struct TextDecoder {
+    pending: Vec<u8>,
+}
+
+impl TextDecoder {
+    pub fn push(&mut self, chunk: &[u8], out: &mut bytes::BytesMut) -> Result<(), String> {
+        self.pending.extend(chunk.iter().filter(|b| !b.is_ascii_whitespace()));
+
+        let complete = self.pending.len() / 4 * 4;
+        let mut start = 0;
+        while start < complete {
+            // Decode up to and including the first padded quad, or everything complete.
+            let end = self.pending[start..complete]
+                .iter()
+                .position(|&b| b == b'=')
+                .map(|i| (start + i) / 4 * 4 + 4)
+                .unwrap_or(complete);
+            let decoded = general_purpose::STANDARD
+                .decode(&self.pending[start..end])
+                .map_err(|e| format!("Invalid grpc-web-text body: {}", e))?;
+            out.extend_from_slice(&decoded);
+            start = end;
+        }
+        self.pending.drain(..complete);
+        Ok(())
+    }
+
+    /// Error if the body ended partway through a base64 quad.
+    pub fn finish(&self) -> Result<(), String> {
+        if self.pending.is_empty() {
+            Ok(())
+        } else {
+            Err("Truncated grpc-web-text body".to_string())
+        }
+    }
+}
+
+/// Parse the payload of a gRPC-Web trailer frame: HTTP/1-style `name: value` lines
+/// separated by CRLF. Names are lower-cased; malformed lines are skipped.
+pub fn parse_trailers(payload: &[u8]) -> HeaderMap {
+    let mut trailers = HeaderMap::new();
+    for line in payload.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let Some(colon) = line.iter().position(|&b| b == b':') else {
+            continue;
+        };
+        let name = HeaderName::from_bytes(line[..colon].trim_ascii().to_ascii_lowercase().as_slice());
+        let value = HeaderValue::from_bytes(line[colon + 1..].trim_ascii());
+        if let (Ok(name), Ok(value)) = (name, value) {
+            trailers.append(name, value);
+        }
+    }
+    trailers
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_trailer_frame() {
+        let trailers = parse_trailers(b"grpc-status:0\r\nGrpc-Message: all%20good\r\nx-extra: a\r\nx-extra: b\r\n");
+        assert_eq!(trailers["grpc-status"], "0");
+        assert_eq!(trailers["grpc-message"], "all%20good");
+        assert_eq!(trailers.get_all("x-extra").iter().count(), 2);
+    }
+
+    #[test]
+    fn test_text_decoder_handles_chunked_padding() {
+        // Two separately encoded chunks, split at awkward boundaries.
+        let body = format!(
+            "{}{}",
+            general_purpose::STANDARD.encode(b"\x00\x00\x00\x00\x01\x2a"),
+            general_purpose::STANDARD.encode(b"\x80\x00\x00\x00\x0dgrpc-status:0")
+        );
+        let mut decoder = TextDecoder::default();
+        let mut out = bytes::BytesMut::new();
+        for chunk in body.as_bytes().chunks(3) {
+            decoder.push(chunk, &mut out).unwrap();
+        }
+        decoder.finish().unwrap();
+        assert_eq!(&out[..6], b"\x00\x00\x00\x00\x01\x2a");
+        assert_eq!(&out[6..], b"\x80\x00\x00\x00\x0dgrpc-status:0");
+    }
+}
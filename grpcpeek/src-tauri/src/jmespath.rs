@@ -0,0 +1,643 @@
+use serde_json::Value;
+
+/// Functions `search` supports, for error messages.
+const FUNCTIONS: &str = "length, contains, starts_with, ends_with, keys, values, type, to_string, to_number";
+
+/// Evaluate an expression in a subset of JMESPath. Supported: identifiers (bare or
+/// quoted), `@`, sub-expressions, indexes, `[*]` / `*` / `[]` projections, `[?...]`
+/// filters, pipes, comparisons, `&&` / `||` / `!`, parentheses, raw strings
+/// (`'text'`), JSON literals (`` `1` ``) and the functions listed in `FUNCTIONS`.
+/// Slices, multi-select lists and hashes and `&` expression references are rejected
+/// with an error saying so. Precedence follows the spec: `!` binds tighter than the
+/// comparators, which bind tighter than `&&`, then `||`, then `|`.
+pub fn search(expression: &str, data: &Value) -> Result<Value, String> {
+    let tokens = tokenize(expression)?;
+    let mut parser = Parser { tokens: &tokens, pos: 0 };
+    let expr = parser.pipe()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("Invalid JMESPath '{}': unexpected {:?}", expression, token));
+    }
+    eval(&expr, data)
+}
+
+/// JMESPath truthiness: false, null and empty strings, arrays and objects are false.
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(obj) => !obj.is_empty(),
+        Value::Number(_) => true,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tokens
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    /// `"quoted identifier"`
+    Quoted(String),
+    /// `'raw string'` or `` `json` ``
+    Literal(Value),
+    Number(i64),
+    Dot,
+    Star,
+    At,
+    Comma,
+    LBracket,
+    RBracket,
+    LParen,
+    RParen,
+    Question,
+    Pipe,
+    Or,
+    And,
+    Not,
+    Cmp(Cmp),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+fn unsupported(what: &str) -> String {
+    format!("{} are not supported", what)
+}
+
+fn multi_select() -> String {
+    format!("Invalid JMESPath: {}", unsupported("multi-select lists ([a, b])"))
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let invalid = |why: String| format!("Invalid JMESPath '{}': {}", input, why);
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    // Read up to an unescaped `end`, returning the text between the delimiters.
+    let delimited = |i: &mut usize, end: char| -> Result<String, String> {
+        let mut text = String::new();
+        *i += 1;
+        while *i < chars.len() {
+            match chars[*i] {
+                '\\' if chars.get(*i + 1) == Some(&end) => {
+                    text.push(end);
+                    *i += 2;
+                }
+                c if c == end => {
+                    *i += 1;
+                    return Ok(text);
+                }
+                c => {
+                    text.push(c);
+                    *i += 1;
+                }
+            }
+        }
+        Err(invalid(format!("unterminated {}", end)))
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            'a'..='z' | 'A'..='Z' | '_' => {
+                let end = (i..chars.len())
+                    .find(|&j| !(chars[j].is_ascii_alphanumeric() || chars[j] == '_'))
+                    .unwrap_or(chars.len());
+                tokens.push(Token::Ident(chars[i..end].iter().collect()));
+                i = end;
+                continue;
+            }
+            '0'..='9' | '-' => {
+                let end = (i + 1..chars.len())
+                    .find(|&j| !chars[j].is_ascii_digit())
+                    .unwrap_or(chars.len());
+                let text: String = chars[i..end].iter().collect();
+                let n = text.parse().map_err(|_| invalid(format!("invalid number '{}'", text)))?;
+                tokens.push(Token::Number(n));
+                i = end;
+                continue;
+            }
+            '"' => {
+                let raw = delimited(&mut i, '"')?;
+                let name = serde_json::from_str(&format!("\"{}\"", raw)).unwrap_or(raw);
+                tokens.push(Token::Quoted(name));
+                continue;
+            }
+            '\'' => {
+                let text = delimited(&mut i, '\'')?;
+                tokens.push(Token::Literal(Value::String(text)));
+                continue;
+            }
+            '`' => {
+                let text = delimited(&mut i, '`')?;
+                let value = serde_json::from_str(text.trim())
+                    .map_err(|e| invalid(format!("invalid literal `{}`: {}", text, e)))?;
+                tokens.push(Token::Literal(value));
+                continue;
+            }
+            '.' => (Token::Dot, 1),
+            '*' => (Token::Star, 1),
+            '@' => (Token::At, 1),
+            ',' => (Token::Comma, 1),
+            '[' => (Token::LBracket, 1),
+            ']' => (Token::RBracket, 1),
+            '(' => (Token::LParen, 1),
+            ')' => (Token::RParen, 1),
+            '?' => (Token::Question, 1),
+            '|' if next == Some('|') => (Token::Or, 2),
+            '|' => (Token::Pipe, 1),
+            '&' if next == Some('&') => (Token::And, 2),
+            '&' => return Err(invalid(unsupported("expression references (&expr)"))),
+            ':' => return Err(invalid(unsupported("slices ([start:stop:step])"))),
+            '{' => return Err(invalid(unsupported("multi-select hashes ({key: expr})"))),
+            '=' if next == Some('=') => (Token::Cmp(Cmp::Eq), 2),
+            '!' if next == Some('=') => (Token::Cmp(Cmp::Ne), 2),
+            '!' => (Token::Not, 1),
+            '<' if next == Some('=') => (Token::Cmp(Cmp::Le), 2),
+            '<' => (Token::Cmp(Cmp::Lt), 1),
+            '>' if next == Some('=') => (Token::Cmp(Cmp::Ge), 2),
+            '>' => (Token::Cmp(Cmp::Gt), 1),
+            other => return Err(invalid(format!("unexpected '{}'", other))),
+        };
+        tokens.push(token);
+        i += len;
+    }
+    Ok(tokens)
+}
+
+// ---------------------------------------------------------------------------
+// Parser
+// ---------------------------------------------------------------------------
+
+enum Expr {
+    /// A value followed by steps applied to it in turn.
+    Path(Box<Expr>, Vec<Step>),
+    Current,
+    Literal(Value),
+    Function(String, Vec<Expr>),
+    Compare(Cmp, Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Pipe(Box<Expr>, Box<Expr>),
+}
+
+enum Step {
+    Field(String),
+    Index(i64),
+    /// `[*]`: project over array elements.
+    Wildcard,
+    /// `*`: project over object values.
+    Values,
+    /// `[]`: flatten one level, then project.
+    Flatten,
+    /// `[?cond]`: keep elements where `cond` is truthy, then project.
+    Filter(Box<Expr>),
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            other => Err(format!("Invalid JMESPath: expected {:?}, got {:?}", expected, other)),
+        }
+    }
+
+    fn close_bracket(&mut self) -> Result<(), String> {
+        if self.peek() == Some(&Token::Comma) {
+            return Err(multi_select());
+        }
+        self.expect(Token::RBracket)
+    }
+
+    fn pipe(&mut self) -> Result<Expr, String> {
+        let mut left = self.or()?;
+        while self.peek() == Some(&Token::Pipe) {
+            self.pos += 1;
+            left = Expr::Pipe(Box::new(left), Box::new(self.or()?));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.comparison()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = Expr::And(Box::new(left), Box::new(self.comparison()?));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.not()?;
+        match self.peek() {
+            Some(Token::Cmp(cmp)) => {
+                let cmp = *cmp;
+                self.pos += 1;
+                Ok(Expr::Compare(cmp, Box::new(left), Box::new(self.not()?)))
+            }
+            _ => Ok(left),
+        }
+    }
+
+    /// `!` applies to the path right after it: `!a == b` is `(!a) == b`.
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.path()
+    }
+
+    fn path(&mut self) -> Result<Expr, String> {
+        let (root, mut steps) = match self.next() {
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::LParen) => {
+                self.pos += 1;
+                let mut args = Vec::new();
+                if self.peek() != Some(&Token::RParen) {
+                    loop {
+                        args.push(self.pipe()?);
+                        if self.peek() != Some(&Token::Comma) {
+                            break;
+                        }
+                        self.pos += 1;
+                    }
+                }
+                self.expect(Token::RParen)?;
+                (Expr::Function(name, args), Vec::new())
+            }
+            Some(Token::Ident(name)) | Some(Token::Quoted(name)) => (Expr::Current, vec![Step::Field(name)]),
+            Some(Token::At) => (Expr::Current, Vec::new()),
+            Some(Token::Star) => (Expr::Current, vec![Step::Values]),
+            Some(Token::Literal(value)) => (Expr::Literal(value), Vec::new()),
+            Some(Token::LParen) => {
+                let inner = self.pipe()?;
+                self.expect(Token::RParen)?;
+                (inner, Vec::new())
+            }
+            Some(Token::LBracket) => {
+                self.pos -= 1;
+                (Expr::Current, Vec::new())
+            }
+            other => return Err(format!("Invalid JMESPath: unexpected {:?}", other)),
+        };
+
+        loop {
+            match self.peek() {
+                Some(Token::Dot) => {
+                    self.pos += 1;
+                    steps.push(match self.next() {
+                        Some(Token::Ident(name)) | Some(Token::Quoted(name)) => Step::Field(name),
+                        Some(Token::Star) => Step::Values,
+                        other => return Err(format!("Invalid JMESPath: unexpected {:?} after '.'", other)),
+                    });
+                }
+                Some(Token::LBracket) => {
+                    self.pos += 1;
+                    steps.push(match self.next() {
+                        Some(Token::Number(n)) => {
+                            self.close_bracket()?;
+                            Step::Index(n)
+                        }
+                        Some(Token::Star) => {
+                            self.close_bracket()?;
+                            Step::Wildcard
+                        }
+                        Some(Token::RBracket) => Step::Flatten,
+                        Some(Token::Question) => {
+                            let cond = self.pipe()?;
+                            self.expect(Token::RBracket)?;
+                            Step::Filter(Box::new(cond))
+                        }
+                        Some(Token::Ident(_) | Token::Quoted(_) | Token::At | Token::LBracket | Token::Literal(_)) => {
+                            return Err(multi_select());
+                        }
+                        other => return Err(format!("Invalid JMESPath: unexpected {:?} after '['", other)),
+                    });
+                }
+                _ => break,
+            }
+        }
+
+        Ok(if steps.is_empty() {
+            root
+        } else {
+            Expr::Path(Box::new(root), steps)
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Evaluation
+// ---------------------------------------------------------------------------
+
+fn eval(expr: &Expr, data: &Value) -> Result<Value, String> {
+    Ok(match expr {
+        Expr::Current => data.clone(),
+        Expr::Literal(value) => value.clone(),
+        Expr::Path(root, steps) => apply(&eval(root, data)?, steps)?,
+        Expr::Function(name, args) => {
+            let args = args.iter().map(|a| eval(a, data)).collect::<Result<Vec<_>, _>>()?;
+            call(name, &args)?
+        }
+        Expr::Compare(cmp, left, right) => compare(*cmp, &eval(left, data)?, &eval(right, data)?),
+        Expr::And(left, right) => {
+            let left = eval(left, data)?;
+            if is_truthy(&left) {
+                eval(right, data)?
+            } else {
+                left
+            }
+        }
+        Expr::Or(left, right) => {
+            let left = eval(left, data)?;
+            if is_truthy(&left) {
+                left
+            } else {
+                eval(right, data)?
+            }
+        }
+        Expr::Not(inner) => Value::Bool(!is_truthy(&eval(inner, data)?)),
+        Expr::Pipe(left, right) => eval(right, &eval(left, data)?)?,
+    })
+}
+
+/// Apply steps in turn. A projection applies the following steps to each element
+/// and drops the null results; a flatten ends the projection before it and applies
+/// to its result.
+fn apply(value: &Value, steps: &[Step]) -> Result<Value, String> {
+    let Some((step, rest)) = steps.split_first() else {
+        return Ok(value.clone());
+    };
+    let project = |items: Vec<&Value>| -> Result<Value, String> {
+        let end = rest.iter().position(|s| matches!(s, Step::Flatten)).unwrap_or(rest.len());
+        let mut out = Vec::new();
+        for item in items {
+            let v = apply(item, &rest[..end])?;
+            if !v.is_null() {
+                out.push(v);
+            }
+        }
+        apply(&Value::Array(out), &rest[end..])
+    };
+
+    match (step, value) {
+        (Step::Field(name), Value::Object(obj)) => apply(obj.get(name).unwrap_or(&Value::Null), rest),
+        (Step::Index(i), Value::Array(items)) => {
+            let i = if *i < 0 { items.len() as i64 + i } else { *i };
+            let item = usize::try_from(i).ok().and_then(|i| items.get(i));
+            apply(item.unwrap_or(&Value::Null), rest)
+        }
+        (Step::Wildcard, Value::Array(items)) => project(items.iter().collect()),
+        (Step::Values, Value::Object(obj)) => project(obj.values().collect()),
+        (Step::Flatten, Value::Array(items)) => project(
+            items
+                .iter()
+                .flat_map(|item| match item {
+                    Value::Array(inner) => inner.iter().collect(),
+                    other => vec![other],
+                })
+                .collect(),
+        ),
+        (Step::Filter(cond), Value::Array(items)) => {
+            let mut kept = Vec::new();
+            for item in items {
+                if is_truthy(&eval(cond, item)?) {
+                    kept.push(item);
+                }
+            }
+            project(kept)
+        }
+        _ => Ok(Value::Null),
+    }
+}
+
+fn compare(cmp: Cmp, left: &Value, right: &Value) -> Value {
+    match cmp {
+        Cmp::Eq => Value::Bool(left == right),
+        Cmp::Ne => Value::Bool(left != right),
+        _ => match (left.as_f64(), right.as_f64()) {
+            (Some(l), Some(r)) => Value::Bool(match cmp {
+                Cmp::Lt => l < r,
+                Cmp::Le => l <= r,
+                Cmp::Gt => l > r,
+                _ => l >= r,
+            }),
+            // Ordering is only defined for numbers.
+            _ => Value::Null,
+        },
+    }
+}
+
+fn call(name: &str, args: &[Value]) -> Result<Value, String> {
+    let arity = |n: usize| {
+        if args.len() == n {
+            Ok(())
+        } else {
+            Err(format!("{}() takes {} argument(s), got {}", name, n, args.len()))
+        }
+    };
+    let type_error = |expected: &str| format!("{}() expects {}", name, expected);
+
+    match name {
+        "length" => {
+            arity(1)?;
+            match &args[0] {
+                Value::String(s) => Ok(Value::from(s.chars().count())),
+                Value::Array(items) => Ok(Value::from(items.len())),
+                Value::Object(obj) => Ok(Value::from(obj.len())),
+                _ => Err(type_error("a string, array or object")),
+            }
+        }
+        "contains" => {
+            arity(2)?;
+            match (&args[0], &args[1]) {
+                (Value::Array(items), search) => Ok(Value::Bool(items.contains(search))),
+                (Value::String(s), Value::String(search)) => Ok(Value::Bool(s.contains(search.as_str()))),
+                (Value::String(_), _) => Ok(Value::Bool(false)),
+                _ => Err(type_error("an array or string")),
+            }
+        }
+        "starts_with" | "ends_with" => {
+            arity(2)?;
+            match (&args[0], &args[1]) {
+                (Value::String(s), Value::String(affix)) => Ok(Value::Bool(if name == "starts_with" {
+                    s.starts_with(affix.as_str())
+                } else {
+                    s.ends_with(affix.as_str())
+                })),
+                _ => Err(type_error("two strings")),
+            }
+        }
+        "keys" | "values" => {
+            arity(1)?;
+            match &args[0] {
+                Value::Object(obj) if name == "keys" => Ok(obj.keys().cloned().map(Value::String).collect()),
+                Value::Object(obj) => Ok(obj.values().cloned().collect()),
+                _ => Err(type_error("an object")),
+            }
+        }
+        "type" => {
+            arity(1)?;
+            Ok(Value::from(match &args[0] {
+                Value::Null => "null",
+                Value::Bool(_) => "boolean",
+                Value::Number(_) => "number",
+                Value::String(_) => "string",
+                Value::Array(_) => "array",
+                Value::Object(_) => "object",
+            }))
+        }
+        "to_string" => {
+            arity(1)?;
+            Ok(match &args[0] {
+                Value::String(s) => Value::String(s.clone()),
+                other => Value::String(other.to_string()),
+            })
+        }
+        // proto3 JSON writes 64-bit integers as strings; this turns them back.
+        "to_number" => {
+            arity(1)?;
+            Ok(match &args[0] {
+                Value::Number(n) => Value::Number(n.clone()),
+                Value::String(s) => s
+                    .trim()
+                    .parse::<i64>()
+                    .map(Value::from)
+                    .or_else(|_| s.trim().parse::<f64>().map(Value::from))
+                    .unwrap_or(Value::Null),
+                _ => Value::Null,
+            })
+        }
+        other => Err(format!("Unknown JMESPath function '{}'. Supported: {}", other, FUNCTIONS)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_search_expressions() {
+        let data = json!({
+            "user": {"id": "42", "name": "Ada"},
+            "items": [
+                {"sku": "a", "qty": 2, "tags": ["x"]},
+                {"sku": "b", "qty": 0, "tags": ["y", "z"]},
+                {"sku": "c", "qty": 5}
+            ],
+            "weird key": true,
+        });
+        let cases = [
+            ("user.name", json!("Ada")),
+            ("items[0].sku", json!("a")),
+            ("items[-1].qty", json!(5)),
+            ("items[*].sku", json!(["a", "b", "c"])),
+            ("items[?qty > `1`].sku", json!(["a", "c"])),
+            ("items[].tags[]", json!(["x", "y", "z"])),
+            ("items[*].tags | [0]", json!(["x"])),
+            ("length(items) == `3` && user.id == '42'", json!(true)),
+            ("to_number(user.id) >= `40`", json!(true)),
+            ("contains(items[*].sku, 'b')", json!(true)),
+            ("\"weird key\"", json!(true)),
+            ("!missing", json!(true)),
+            ("user.*", json!(["42", "Ada"])),
+            ("missing.deeper", Value::Null),
+        ];
+        for (expr, expected) in cases {
+            assert_eq!(search(expr, &data).unwrap(), expected, "{}", expr);
+        }
+
+        assert!(search("items[", &data).is_err());
+        assert!(search("nope(user)", &data).is_err());
+        assert!(!is_truthy(&json!([])) && is_truthy(&json!(0)));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let data = json!({"a": true, "b": false, "n": 3, "list": [1, 2]});
+        let cases = [
+            // `!` binds tighter than comparators: (!a) == b
+            ("!a == b", json!(true)),
+            ("!(a == b)", json!(true)),
+            ("!b == a", json!(true)),
+            ("!n == `false`", json!(true)),
+            // Comparators bind tighter than && and ||.
+            ("n > `1` && n < `5`", json!(true)),
+            ("b || n == `3`", json!(true)),
+            // && binds tighter than ||.
+            ("a || b && b", json!(true)),
+            ("(a || b) && b", json!(false)),
+            // The pipe is loosest: the whole left side runs first.
+            ("list[*] | [0]", json!(1)),
+            ("a && list | length(@)", json!(2)),
+        ];
+        for (expr, expected) in cases {
+            assert_eq!(search(expr, &data).unwrap(), expected, "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_unsupported_syntax_is_rejected() {
+        let data = json!({"items": [1, 2, 3], "a": 1, "b": 2});
+        let cases = [
+            ("items[0:2]", "slices"),
+            ("items[::-1]", "slices"),
+            ("[a, b]", "multi-select lists"),
+            ("items[0, 1]", "multi-select lists"),
+            ("{x: a}", "multi-select hashes"),
+            ("sort_by(items, &a)", "expression references"),
+        ];
+        for (expr, why) in cases {
+            let err = search(expr, &data).unwrap_err();
+            assert!(err.contains(why) && err.contains("not supported"), "{}: {}", expr, err);
+        }
+        let err = search("sort(items)", &data).unwrap_err();
+        assert!(err.contains("Unknown JMESPath function 'sort'"), "{}", err);
+    }
+}
@@ -0,0 +1,22 @@
+pub mod any;
+pub mod assertions;
+pub mod benchmark;
+pub mod channels;
+pub mod compression;
+pub mod connector;
+pub mod engine;
+pub mod grpc_web;
+pub mod jmespath;
+pub mod message_format;
+pub mod metadata;
+pub mod proto_parser;
+pub mod proto_printer;
+pub mod reflection;
+pub mod runner;
+pub mod schema;
+pub mod status_details;
+#[cfg(test)]
+mod test_server;
+pub mod timing;
+pub mod validation;
+pub mod wire;
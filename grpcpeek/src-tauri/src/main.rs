@@ -1,27 +1,26 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod proto_parser;
-
-use base64::{engine::general_purpose, Engine as _};
-use bytes::Buf;
-use futures::StreamExt;
-use http::{Request as HttpRequest, Uri};
+use futures::future::{AbortHandle, Abortable};
+use grpcpeek_lib::assertions::{self, Assertion};
+use grpcpeek_lib::benchmark::{Benchmark, BenchmarkOptions};
+use grpcpeek_lib::compression::Encoding;
+use grpcpeek_lib::connector::Connector;
+use grpcpeek_lib::engine::{self, AuthConfig, Call, CallOptions, TlsConfig};
+use grpcpeek_lib::grpc_web::{Transport, TransportConfig};
+use grpcpeek_lib::message_format::{JsonOptions, MessageFormat};
+use grpcpeek_lib::{channels, proto_parser, proto_printer, schema, validation, wire};
 use hyper::{Body, Client};
-use hyper::client::HttpConnector;
-use hyper_rustls::HttpsConnector;
 use lazy_static::lazy_static;
 use prost::Message as ProstMessage;
-use prost_reflect::{DescriptorPool, DynamicMessage};
+use prost_reflect::DescriptorPool;
 use regex::Regex;
-use rustls::{Certificate, PrivateKey};
-use rustls_pemfile::{certs, pkcs8_private_keys};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::io::BufReader;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use tauri::Emitter;
 use tokio::sync::mpsc;
@@ -37,11 +36,23 @@ use std::os::windows::process::CommandExt;
 struct AppState {
     /// (cache_key, pool). The key is the sorted enabled import path strings joined by NUL.
     pool: Mutex<Option<(String, Arc<DescriptorPool>)>>,
+    /// Pools fetched via server reflection, keyed by endpoint (scheme stripped).
+    reflected: Mutex<HashMap<String, Arc<DescriptorPool>>>,
+    /// Last result of `with_known_types` per endpoint, reused until a pool it was
+    /// merged from is replaced.
+    merged: Mutex<HashMap<String, MergedPool>>,
+    /// Reusable clients so calls to the same endpoint share connections.
+    channels: channels::ChannelPool,
 }
 
 impl AppState {
     fn new() -> Self {
-        Self { pool: Mutex::new(None) }
+        Self {
+            pool: Mutex::new(None),
+            reflected: Mutex::new(HashMap::new()),
+            merged: Mutex::new(HashMap::new()),
+            channels: channels::ChannelPool::default(),
+        }
     }
 
     fn get_or_compile(
@@ -66,18 +77,88 @@ impl AppState {
         let key = cache_key(import_paths);
         *self.pool.lock().unwrap_or_else(|p| p.into_inner()) = Some((key, Arc::new(pool)));
     }
-}
 
-fn validate_metadata(meta: &HashMap<String, String>) -> Result<(), String> {
-    for (k, v) in meta {
-        if k.chars().any(|c| c.is_control()) {
-            return Err(format!("Invalid metadata key '{}': contains control characters", k));
-        }
-        if v.chars().any(|c| c == '\n' || c == '\r' || c == '\0') {
-            return Err(format!("Invalid metadata value for '{}': contains newline or null characters", k));
+    fn store_reflected(&self, endpoint: &str, pool: DescriptorPool) {
+        self.reflected
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(endpoint.to_string(), Arc::new(pool));
+    }
+
+    fn reflected_pool(&self, endpoint: &str) -> Option<Arc<DescriptorPool>> {
+        self.reflected
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(endpoint)
+            .cloned()
+    }
+
+    /// `pool` plus the types it lacks from the reflected pool for `endpoint` and the
+    /// compiled import-path pool. The merge is cached per endpoint and redone only
+    /// when one of those pools changes.
+    fn with_known_types(&self, pool: Arc<DescriptorPool>, endpoint: &str) -> Result<Arc<DescriptorPool>, String> {
+        let compiled = self
+            .pool
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .as_ref()
+            .map(|(_, p)| Arc::clone(p));
+        let others: Vec<Arc<DescriptorPool>> = [self.reflected_pool(endpoint), compiled]
+            .into_iter()
+            .flatten()
+            .filter(|other| !Arc::ptr_eq(other, &pool))
+            .collect();
+        let sources: Vec<Arc<DescriptorPool>> = std::iter::once(Arc::clone(&pool)).chain(others.iter().cloned()).collect();
+
+        if let Some(cached) = self.merged.lock().unwrap_or_else(|p| p.into_inner()).get(endpoint) {
+            if cached.is_from(&sources) {
+                return Ok(Arc::clone(&cached.pool));
+            }
         }
+
+        let merged = match proto_parser::merge_types(&pool, others.iter().map(|p| p.as_ref()))? {
+            Some(merged) => Arc::new(merged),
+            None => pool,
+        };
+        self.merged
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(endpoint.to_string(), MergedPool { sources, pool: Arc::clone(&merged) });
+        Ok(merged)
+    }
+
+    /// Pooled client for the endpoint, TLS config and HTTP version, created on first use.
+    fn channel(
+        &self,
+        endpoint: &str,
+        tls: Option<&TlsConfig>,
+        transport: Transport,
+        transport_config: Option<&TransportConfig>,
+    ) -> Result<Client<Connector, Body>, String> {
+        let http2 = engine::http2_preference(transport, transport_config);
+        let tls = tls.filter(|c| c.enabled);
+        let tls_key = tls.and_then(|c| serde_json::to_string(c).ok()).unwrap_or_default();
+        let key = format!("{}\0{}\0{:?}", endpoint, tls_key, http2);
+        let protocol = match http2 {
+            Some(true) => "h2",
+            Some(false) => "http/1.1",
+            None => "auto",
+        };
+        self.channels
+            .get_or_connect(key, endpoint, tls.is_some(), protocol, || engine::build_client(endpoint, tls, http2))
+    }
+}
+
+/// A call's pool merged with the other cached pools, and the pools it came from.
+struct MergedPool {
+    sources: Vec<Arc<DescriptorPool>>,
+    pool: Arc<DescriptorPool>,
+}
+
+impl MergedPool {
+    fn is_from(&self, sources: &[Arc<DescriptorPool>]) -> bool {
+        self.sources.len() == sources.len() && self.sources.iter().zip(sources).all(|(a, b)| Arc::ptr_eq(a, b))
     }
-    Ok(())
 }
 
 fn cache_key(paths: &[proto_parser::ImportPath]) -> String {
@@ -93,12 +174,94 @@ fn cache_key(paths: &[proto_parser::ImportPath]) -> String {
 struct ActiveClientStream {
     sender: mpsc::UnboundedSender<Vec<u8>>,
     input_desc: prost_reflect::MessageDescriptor,
+    /// Compression applied to each outgoing message.
+    encoding: Encoding,
     response_receiver: tokio::sync::oneshot::Receiver<Result<String, String>>,
+    abort: AbortHandle,
+    /// Responses decoded so far, so a cancelled stream can still report them.
+    received: Arc<Mutex<Vec<Value>>>,
+    /// Whether responses stream back (bidi) rather than arriving as one message.
+    is_streaming: bool,
+}
+
+// ---------------------------------------------------------------------------
+// In-flight unary / server-streaming calls, tracked so they can be cancelled
+// ---------------------------------------------------------------------------
+
+struct ActiveCall {
+    /// Distinguishes calls on the same tab so a finished call never unregisters
+    /// a newer one.
+    id: u64,
+    abort: AbortHandle,
+    received: Arc<Mutex<Vec<Value>>>,
+    is_streaming: bool,
 }
 
 lazy_static! {
     static ref ACTIVE_CLIENT_STREAMS: Mutex<HashMap<String, ActiveClientStream>> =
         Mutex::new(HashMap::new());
+    static ref ACTIVE_CALLS: Mutex<HashMap<String, ActiveCall>> = Mutex::new(HashMap::new());
+    /// Stop flags of running benchmarks, keyed by tab id.
+    static ref ACTIVE_BENCHMARKS: Mutex<HashMap<String, Arc<AtomicBool>>> = Mutex::new(HashMap::new());
+}
+
+static NEXT_CALL_ID: AtomicU64 = AtomicU64::new(0);
+
+fn register_call(tab_id: &str, abort: AbortHandle, received: Arc<Mutex<Vec<Value>>>, is_streaming: bool) -> u64 {
+    let id = NEXT_CALL_ID.fetch_add(1, Ordering::Relaxed);
+    ACTIVE_CALLS
+        .lock()
+        .unwrap_or_else(|p| p.into_inner())
+        .insert(tab_id.to_string(), ActiveCall { id, abort, received, is_streaming });
+    id
+}
+
+fn unregister_call(tab_id: &str, id: u64) {
+    let mut calls = ACTIVE_CALLS.lock().unwrap_or_else(|p| p.into_inner());
+    if calls.get(tab_id).map(|c| c.id) == Some(id) {
+        calls.remove(tab_id);
+    }
+}
+
+/// Abort the in-flight call or open stream for a tab and forget it. Returns the
+/// responses received so far and whether the call streams its responses.
+fn abort_active_call(tab_id: &str) -> Result<(Vec<Value>, bool), String> {
+    let call = ACTIVE_CALLS.lock().unwrap_or_else(|p| p.into_inner()).remove(tab_id);
+    let (received, is_streaming) = if let Some(call) = call {
+        call.abort.abort();
+        (call.received, call.is_streaming)
+    } else {
+        let stream = ACTIVE_CLIENT_STREAMS
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(tab_id)
+            .ok_or("No active call for this tab")?;
+        stream.abort.abort();
+        (stream.received, stream.is_streaming)
+    };
+    let messages = received.lock().unwrap_or_else(|p| p.into_inner()).clone();
+    Ok((messages, is_streaming))
+}
+
+/// Result returned for a call the user cancelled, carrying whatever arrived before.
+/// Non-streaming calls report their single response (if any) as `response`, like a
+/// finished call does.
+fn cancelled_result(messages: Vec<Value>, is_streaming: bool) -> Value {
+    let message_count = messages.len();
+    let response = if is_streaming {
+        Value::Array(messages)
+    } else {
+        messages.into_iter().next().unwrap_or(Value::Null)
+    };
+    serde_json::json!({
+        "status": "error",
+        "grpc_status": "1",
+        "grpc_message": "CANCELLED: call cancelled by client",
+        "is_streaming": is_streaming,
+        "message_count": message_count,
+        "response": response,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -124,209 +287,32 @@ pub struct MethodInfo {
     pub sample_request: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct TlsConfig {
-    pub enabled: bool,
-    pub client_cert_path: Option<String>,
-    pub client_key_path: Option<String>,
-    pub server_ca_cert_path: Option<String>,
-    pub insecure_skip_verify: Option<bool>,
-}
-
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct AuthConfig {
-    #[serde(rename = "type")]
-    pub auth_type: String,
-    pub token: Option<String>,
-    pub username: Option<String>,
-    pub password: Option<String>,
-    pub key: Option<String>,
-    pub value: Option<String>,
-}
-
 // ---------------------------------------------------------------------------
-// TLS helpers
+// Tauri commands
 // ---------------------------------------------------------------------------
 
-fn load_certificates(path: &str) -> Result<Vec<Certificate>, String> {
-    let file = std::fs::File::open(path)
-        .map_err(|e| format!("Failed to open certificate '{}': {}", path, e))?;
-    let certs = certs(&mut BufReader::new(file))
-        .map_err(|e| format!("Failed to parse certificates from '{}': {}", path, e))?;
-    Ok(certs.into_iter().map(Certificate).collect())
-}
-
-fn load_private_key(path: &str) -> Result<PrivateKey, String> {
-    let file = std::fs::File::open(path)
-        .map_err(|e| format!("Failed to open private key '{}': {}", path, e))?;
-    pkcs8_private_keys(&mut BufReader::new(file))
-        .map_err(|e| format!("Failed to parse private key from '{}': {}", path, e))?
-        .into_iter()
-        .next()
-        .map(PrivateKey)
-        .ok_or_else(|| format!("No private key found in '{}'", path))
-}
-
-struct NoCertificateVerification;
-
-impl rustls::client::ServerCertVerifier for NoCertificateVerification {
-    fn verify_server_cert(
-        &self,
-        _end_entity: &rustls::Certificate,
-        _intermediates: &[rustls::Certificate],
-        _server_name: &rustls::ServerName,
-        _scts: &mut dyn Iterator<Item = &[u8]>,
-        _ocsp_response: &[u8],
-        _now: std::time::SystemTime,
-    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
-        Ok(rustls::client::ServerCertVerified::assertion())
-    }
+/// List the pooled connections (one per endpoint + TLS config + HTTP version).
+#[tauri::command]
+fn list_channels(state: tauri::State<'_, AppState>) -> Vec<channels::ChannelInfo> {
+    state.channels.list()
 }
 
-fn build_https_connector(tls: Option<&TlsConfig>) -> Result<HttpsConnector<HttpConnector>, String> {
-    if let Some(cfg) = tls.filter(|c| c.enabled) {
-        let mut root_store = rustls::RootCertStore::empty();
-        if let Some(ca_path) = &cfg.server_ca_cert_path {
-            for cert in load_certificates(ca_path)? {
-                root_store.add(&cert)
-                    .map_err(|e| format!("Failed to add CA certificate: {}", e))?;
-            }
-        } else {
-            root_store.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
-                rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
-                    ta.subject.to_vec(),
-                    ta.spki.to_vec(),
-                    ta.name_constraints.as_ref().map(|nc| nc.to_vec()),
-                )
-            }));
-        }
-
-        let builder = rustls::ClientConfig::builder()
-            .with_safe_defaults()
-            .with_root_certificates(root_store);
-
-        let client_config = if let (Some(cert), Some(key)) =
-            (&cfg.client_cert_path, &cfg.client_key_path)
-        {
-            builder
-                .with_client_auth_cert(load_certificates(cert)?, load_private_key(key)?)
-                .map_err(|e| format!("Failed to configure client auth: {}", e))?
-        } else {
-            builder.with_no_client_auth()
-        };
-
-        let final_config = if cfg.insecure_skip_verify.unwrap_or(false) {
-            let mut c = client_config;
-            c.dangerous()
-                .set_certificate_verifier(Arc::new(NoCertificateVerification));
-            c
-        } else {
-            client_config
-        };
-
-        Ok(hyper_rustls::HttpsConnectorBuilder::new()
-            .with_tls_config(final_config)
-            .https_or_http()
-            .enable_http2()
-            .build())
+/// Close a pooled channel. The next call to its endpoint reconnects.
+#[tauri::command]
+fn close_channel(state: tauri::State<'_, AppState>, channel_id: u64) -> Result<(), String> {
+    if state.channels.close(channel_id) {
+        Ok(())
     } else {
-        Ok(hyper_rustls::HttpsConnectorBuilder::new()
-            .with_native_roots()
-            .https_or_http()
-            .enable_http2()
-            .build())
-    }
-}
-
-// ---------------------------------------------------------------------------
-// Auth header helper
-// ---------------------------------------------------------------------------
-
-fn apply_auth(mut builder: hyper::http::request::Builder, auth: &AuthConfig) -> hyper::http::request::Builder {
-    match auth.auth_type.as_str() {
-        "bearer" => {
-            if let Some(token) = &auth.token {
-                builder = builder.header("authorization", format!("Bearer {}", token));
-            }
-        }
-        "basic" => {
-            if let (Some(u), Some(p)) = (&auth.username, &auth.password) {
-                let encoded = general_purpose::STANDARD.encode(format!("{}:{}", u, p).as_bytes());
-                builder = builder.header("authorization", format!("Basic {}", encoded));
-            }
-        }
-        "apiKey" => {
-            if let (Some(k), Some(v)) = (&auth.key, &auth.value) {
-                builder = builder.header(k.as_str(), v.as_str());
-            }
-        }
-        _ => {}
+        Err(format!("Channel {} not found", channel_id))
     }
-    builder
 }
 
-// ---------------------------------------------------------------------------
-// gRPC framing helpers
-// ---------------------------------------------------------------------------
-
-fn grpc_frame(protobuf_bytes: &[u8]) -> Vec<u8> {
-    let mut frame = Vec::with_capacity(5 + protobuf_bytes.len());
-    frame.push(0u8); // no compression
-    frame.extend_from_slice(&(protobuf_bytes.len() as u32).to_be_bytes());
-    frame.extend_from_slice(protobuf_bytes);
-    frame
-}
-
-// ---------------------------------------------------------------------------
-// Error formatting
-// ---------------------------------------------------------------------------
-
-fn format_connection_error(raw: &str, endpoint: &str, service: &str, method: &str) -> String {
-    let (category, hints) = if raw.contains("certificate") || raw.contains("tls") || raw.contains("ssl") {
-        ("TLS/Certificate Error", vec![
-            "Server may require TLS but TLS is not enabled".to_string(),
-            "Try 'Insecure Skip Verify' for self-signed certs in development".to_string(),
-            "Check that cert/key file paths are correct".to_string(),
-        ])
-    } else if raw.contains("connection refused") {
-        ("Connection Refused", vec![
-            "Server may not be running".to_string(),
-            "Check host and port".to_string(),
-        ])
-    } else if raw.contains("broken pipe") || raw.contains("stream closed") || raw.contains("connection reset") {
-        ("Connection Closed", vec![
-            "TLS mismatch: server expects TLS but client is not using it (or vice versa)".to_string(),
-            "Server closed the connection during handshake".to_string(),
-        ])
-    } else if raw.contains("timeout") || raw.contains("timed out") {
-        ("Connection Timeout", vec![
-            "Server took too long to respond".to_string(),
-        ])
-    } else {
-        ("Error", vec![])
-    };
-
-    serde_json::json!({
-        "status": "error",
-        "error": raw,
-        "error_category": category,
-        "troubleshooting_hints": hints,
-        "grpc_status": "UNAVAILABLE",
-        "grpc_message": raw,
-        "endpoint": endpoint,
-        "service": service,
-        "method": method,
-        "response": null,
-    })
-    .to_string()
+/// Close every pooled channel, returning how many were open.
+#[tauri::command]
+fn close_all_channels(state: tauri::State<'_, AppState>) -> usize {
+    state.channels.close_all()
 }
 
-// ---------------------------------------------------------------------------
-// Tauri commands
-// ---------------------------------------------------------------------------
-
 /// Parse a single proto file uploaded directly by the user (legacy path).
 #[tauri::command]
 async fn parse_proto_file(proto_content: String) -> Result<Vec<ServiceInfo>, String> {
@@ -439,6 +425,37 @@ fn parse_proto_files(
     result
 }
 
+/// Discover services through gRPC server reflection instead of local proto files.
+/// The fetched pool is cached per endpoint and used by call commands whenever no
+/// import paths or proto content are supplied.
+#[tauri::command]
+async fn reflect_services(
+    state: tauri::State<'_, AppState>,
+    endpoint: String,
+    metadata: Option<HashMap<String, String>>,
+    auth: Option<AuthConfig>,
+    tls_config: Option<TlsConfig>,
+    transport_config: Option<TransportConfig>,
+) -> Result<proto_parser::ProtoParseResult, String> {
+    let clean_endpoint = engine::clean_endpoint(&endpoint);
+
+    // Reflection always uses native gRPC; only the `:authority` override applies.
+    let client = state.channel(&clean_endpoint, tls_config.as_ref(), Transport::Grpc, None)?;
+
+    let options = CallOptions {
+        metadata: metadata.map(|m| m.into_iter().collect()),
+        auth,
+        tls: tls_config,
+        transport_config,
+        ..Default::default()
+    };
+    let (pool, warnings) = engine::reflect(&client, &clean_endpoint, &options).await?;
+
+    let result = proto_parser::parse_descriptor_pool(&pool, warnings);
+    state.store_reflected(&clean_endpoint, pool);
+    Ok(result)
+}
+
 #[tauri::command]
 async fn call_grpc_method(
     state: tauri::State<'_, AppState>,
@@ -453,185 +470,105 @@ async fn call_grpc_method(
     metadata: Option<HashMap<String, String>>,
     auth: Option<AuthConfig>,
     tls_config: Option<TlsConfig>,
+    timeout_ms: Option<u64>,
+    compression: Option<String>,
+    transport_config: Option<TransportConfig>,
+    request_format: Option<String>,
+    response_format: Option<String>,
+    json_options: Option<JsonOptions>,
+    include_raw: Option<bool>,
+    bin_metadata_types: Option<HashMap<String, String>>,
+    assertions: Option<Vec<Assertion>>,
 ) -> Result<String, String> {
-    let request_json: Value = serde_json::from_str(&request_data)
-        .map_err(|e| format!("Failed to parse request JSON: {}", e))?;
-
-    let pool = resolve_pool(&state, proto_content.as_deref(), import_paths.as_deref())?;
-
-    let clean_endpoint = endpoint
-        .trim_start_matches("http://")
-        .trim_start_matches("https://")
-        .to_string();
-
-    let service_desc = pool
-        .services()
-        .find(|s| s.name() == service)
-        .ok_or_else(|| format!("Service '{}' not found in proto", service))?;
-
-    let pkg = service_desc.parent_file().package_name().to_string();
-    let grpc_path = if pkg.is_empty() {
-        format!("/{}/{}", service, method)
-    } else {
-        format!("/{}.{}/{}", pkg, service, method)
+    let request_format = request_format.as_deref().map(MessageFormat::parse).transpose()?.unwrap_or_default();
+    let options = CallOptions {
+        metadata: metadata.map(|m| m.into_iter().collect()),
+        auth,
+        tls: tls_config,
+        timeout_ms,
+        encoding: compression.as_deref().map(Encoding::parse).transpose()?.unwrap_or_default(),
+        transport_config,
+        response_format: response_format
+            .as_deref()
+            .map(MessageFormat::parse)
+            .transpose()?
+            .unwrap_or_default()
+            .with_json_options(json_options),
+        include_raw: include_raw.unwrap_or(false),
+        bin_metadata_types,
     };
 
-    let method_desc = service_desc
-        .methods()
-        .find(|m| m.name() == method)
-        .ok_or_else(|| format!("Method '{}' not found in service '{}'", method, service))?;
-
-    let input_desc = method_desc.input();
-    let output_desc = method_desc.output();
-
-    let request_msg =
-        DynamicMessage::deserialize(input_desc.clone(), &mut serde_json::Deserializer::from_str(&request_data))
-            .map_err(|e| format!("Failed to deserialize request JSON to protobuf: {}", e))?;
-
-    let request_body = grpc_frame(&request_msg.encode_to_vec());
-
-    let use_tls = tls_config.as_ref().map(|c| c.enabled).unwrap_or(false);
-    let uri: Uri = format!(
-        "{}://{}{}",
-        if use_tls { "https" } else { "http" },
-        clean_endpoint,
-        grpc_path
-    )
-    .parse()
-    .map_err(|e| format!("Invalid URI: {}", e))?;
+    // The request is echoed in the result. Non-JSON bodies are echoed as written.
+    let request_json: Value = match request_format {
+        MessageFormat::Json(_) => serde_json::from_str(&request_data)
+            .map_err(|e| format!("Failed to parse request JSON: {}", e))?,
+        _ => Value::String(request_data.clone()),
+    };
 
-    let mut req_builder = HttpRequest::builder()
-        .method("POST")
-        .uri(uri)
-        .header("content-type", "application/grpc")
-        .header("te", "trailers");
+    let clean_endpoint = engine::clean_endpoint(&endpoint);
+    let pool = resolve_pool(&state, proto_content.as_deref(), import_paths.as_deref(), &clean_endpoint)?;
+    let call = Call::new(pool, &clean_endpoint, &service, &method, options)?;
+    let request_msg = request_format.decode(call.method.input(), &request_data)?;
+
+    let client = state.channel(&call.endpoint, call.options.tls.as_ref(), call.transport, call.options.transport_config.as_ref())?;
+
+    let is_server_streaming = call.method.is_server_streaming();
+    let received = Arc::new(Mutex::new(Vec::<Value>::new()));
+    let (abort_handle, abort_registration) = AbortHandle::new_pair();
+    let call_id = register_call(&tab_id, abort_handle, Arc::clone(&received), is_server_streaming);
+
+    let on_message = |index: usize, inspected: &wire::Inspected| {
+        let _ = app.emit("grpc-stream-message", serde_json::json!({
+            "tabId": tab_id,
+            "index": index,
+            "data": inspected.data,
+            "raw": inspected.raw,
+            "decode_error": inspected.decode_error,
+            "unknown_fields": inspected.unknown_fields,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }));
+        received.lock().unwrap_or_else(|p| p.into_inner()).push(inspected.data.clone());
+    };
 
-    if let Some(ref a) = auth {
-        req_builder = apply_auth(req_builder, a);
-    }
-    if let Some(ref meta) = metadata {
-        validate_metadata(meta)?;
-        for (k, v) in meta {
-            req_builder = req_builder.header(k.as_str(), v.as_str());
+    // Dropping the exchange on cancel drops the HTTP/2 stream, which makes hyper
+    // send RST_STREAM(CANCEL) to the server.
+    let outcome = Abortable::new(call.unary(&client, &request_msg, request_json.clone(), on_message), abort_registration).await;
+    unregister_call(&tab_id, call_id);
+
+    let result = match outcome {
+        Ok(outcome) => check_assertions(outcome, &assertions.unwrap_or_default())?,
+        Err(_aborted) => {
+            let messages = received.lock().unwrap_or_else(|p| p.into_inner()).clone();
+            let mut result = cancelled_result(messages, is_server_streaming);
+            result["endpoint"] = Value::String(call.endpoint);
+            result["service"] = Value::String(service);
+            result["method"] = Value::String(method);
+            result["request"] = request_json;
+            return Ok(serde_json::to_string_pretty(&result).unwrap());
         }
-    }
+    };
+    Ok(serde_json::to_string_pretty(&result).unwrap())
+}
 
-    let req = req_builder
-        .body(Body::from(request_body))
-        .map_err(|e| format!("Failed to build request: {}", e))?;
-
-    let connector = build_https_connector(tls_config.as_ref())?;
-    let client = Client::builder().http2_only(true).build::<_, Body>(connector);
-
-    let response = client.request(req).await.map_err(|e| {
-        format_connection_error(&e.to_string(), &clean_endpoint, &service, &method)
-    })?;
-
-    let grpc_status_raw = response
-        .headers()
-        .get("grpc-status")
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
-    let grpc_message = response
-        .headers()
-        .get("grpc-message")
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("")
-        .to_string();
-
-    let mut response_metadata = serde_json::Map::new();
-    for (name, value) in response.headers() {
-        if let Ok(val_str) = value.to_str() {
-            response_metadata.insert(name.as_str().to_string(), Value::String(val_str.to_string()));
-        }
+/// Check `assertions` against a finished call. Connection failures are reported as
+/// a JSON error; a status assertion may expect them, so they're checked too.
+fn check_assertions(outcome: Result<Value, String>, assertions: &[Assertion]) -> Result<Value, String> {
+    if assertions.is_empty() {
+        return outcome;
     }
-
-    let is_server_streaming = method_desc.is_server_streaming();
-    let mut response_data = None;
-    let mut response_messages: Vec<Value> = Vec::new();
-    let mut decode_success = false;
-
-    if is_server_streaming {
-        let mut body_stream = response.into_body();
-        let mut buf = bytes::BytesMut::new();
-        let mut idx = 0;
-
-        while let Some(chunk) = body_stream.next().await {
-            buf.extend_from_slice(&chunk.map_err(|e| format!("Stream read error: {}", e))?);
-            loop {
-                if buf.len() < 5 {
-                    break;
-                }
-                let msg_len = u32::from_be_bytes([buf[1], buf[2], buf[3], buf[4]]) as usize;
-                if buf.len() < 5 + msg_len {
-                    break;
-                }
-                buf.advance(5);
-                let msg_bytes = buf.split_to(msg_len);
-                let msg = DynamicMessage::decode(output_desc.clone(), msg_bytes.as_ref())
-                    .map_err(|e| format!("Failed to decode streaming response frame: {}", e))?;
-                let json = serde_json::to_value(msg)
-                    .map_err(|e| format!("Failed to serialize response: {}", e))?;
-
-                let _ = app.emit("grpc-stream-message", serde_json::json!({
-                    "tabId": tab_id,
-                    "index": idx,
-                    "data": json.clone(),
-                    "timestamp": chrono::Utc::now().to_rfc3339(),
-                }));
-                response_messages.push(json);
-                idx += 1;
-                decode_success = true;
-            }
+    match outcome {
+        Ok(mut result) => {
+            assertions::attach(&mut result, assertions);
+            Ok(result)
         }
-        response_data = Some(Value::Array(response_messages.clone()));
-    } else {
-        let body_bytes = hyper::body::to_bytes(response.into_body())
-            .await
-            .map_err(|e| format!("Failed to read response: {}", e))?;
-
-        if body_bytes.len() >= 5 {
-            let msg_len = u32::from_be_bytes([
-                body_bytes[1], body_bytes[2], body_bytes[3], body_bytes[4],
-            ]) as usize;
-            if body_bytes.len() >= 5 + msg_len {
-                let msg = DynamicMessage::decode(output_desc.clone(), &body_bytes[5..5 + msg_len])
-                    .map_err(|e| format!("Failed to decode response: {}", e))?;
-                let json = serde_json::to_value(msg)
-                    .map_err(|e| format!("Failed to serialize response: {}", e))?;
-                response_data = Some(json);
-                decode_success = true;
+        Err(e) => Err(match serde_json::from_str::<Value>(&e) {
+            Ok(mut result @ Value::Object(_)) => {
+                assertions::attach(&mut result, assertions);
+                result.to_string()
             }
-        }
+            _ => e,
+        }),
     }
-
-    let grpc_status = grpc_status_raw.unwrap_or_else(|| {
-        if decode_success { "0".to_string() } else { "unknown".to_string() }
-    });
-
-    let response_size = response_data
-        .as_ref()
-        .and_then(|d| serde_json::to_string(d).ok())
-        .map(|s| s.len())
-        .unwrap_or(0);
-
-    let result = serde_json::json!({
-        "status": if grpc_status == "0" { "success" } else { "error" },
-        "grpc_status": grpc_status,
-        "grpc_message": grpc_message,
-        "endpoint": clean_endpoint,
-        "service": service,
-        "method": method,
-        "is_streaming": is_server_streaming,
-        "message_count": if is_server_streaming { response_messages.len() } else { 1 },
-        "request": request_json,
-        "response": response_data,
-        "response_metadata": Value::Object(response_metadata),
-        "response_size": response_size,
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-    });
-
-    Ok(serde_json::to_string_pretty(&result).unwrap())
 }
 
 #[tauri::command]
@@ -647,153 +584,82 @@ async fn start_client_stream(
     metadata: Option<HashMap<String, String>>,
     auth: Option<AuthConfig>,
     tls_config: Option<TlsConfig>,
+    timeout_ms: Option<u64>,
+    compression: Option<String>,
+    transport_config: Option<TransportConfig>,
+    response_format: Option<String>,
+    json_options: Option<JsonOptions>,
+    include_raw: Option<bool>,
+    bin_metadata_types: Option<HashMap<String, String>>,
+    assertions: Option<Vec<Assertion>>,
 ) -> Result<String, String> {
-    let pool = resolve_pool(&state, proto_content.as_deref(), import_paths.as_deref())?;
-
-    let service_desc = pool
-        .services()
-        .find(|s| s.name() == service)
-        .ok_or_else(|| format!("Service '{}' not found", service))?;
-
-    let method_desc = service_desc
-        .methods()
-        .find(|m| m.name() == method)
-        .ok_or_else(|| format!("Method '{}' not found", method))?;
-
-    let input_desc = method_desc.input();
-    let output_desc = method_desc.output();
-    let is_bidi = method_desc.is_client_streaming() && method_desc.is_server_streaming();
-
-    let pkg = service_desc.parent_file().package_name().to_string();
-    let grpc_path = if pkg.is_empty() {
-        format!("/{}/{}", service, method)
-    } else {
-        format!("/{}.{}/{}", pkg, service, method)
+    let options = CallOptions {
+        metadata: metadata.map(|m| m.into_iter().collect()),
+        auth,
+        tls: tls_config,
+        timeout_ms,
+        encoding: compression.as_deref().map(Encoding::parse).transpose()?.unwrap_or_default(),
+        transport_config,
+        response_format: response_format
+            .as_deref()
+            .map(MessageFormat::parse)
+            .transpose()?
+            .unwrap_or_default()
+            .with_json_options(json_options),
+        include_raw: include_raw.unwrap_or(false),
+        bin_metadata_types,
     };
 
-    let clean_endpoint = endpoint
-        .trim_start_matches("http://")
-        .trim_start_matches("https://")
-        .to_string();
-
-    let use_tls = tls_config.as_ref().map(|c| c.enabled).unwrap_or(false);
-    let uri: Uri = format!(
-        "{}://{}{}",
-        if use_tls { "https" } else { "http" },
-        clean_endpoint,
-        grpc_path
-    )
-    .parse()
-    .map_err(|e| format!("Invalid URI: {}", e))?;
+    let clean_endpoint = engine::clean_endpoint(&endpoint);
+    let pool = resolve_pool(&state, proto_content.as_deref(), import_paths.as_deref(), &clean_endpoint)?;
+    let call = Call::new(pool, &clean_endpoint, &service, &method, options)?;
+    let input_desc = call.method.input();
+    let encoding = call.options.encoding;
+    let is_bidi = call.method.is_server_streaming();
+    let assertions = assertions.unwrap_or_default();
+
+    let client = state.channel(&call.endpoint, call.options.tls.as_ref(), call.transport, call.options.transport_config.as_ref())?;
 
-    let (message_tx, mut message_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    let (message_tx, message_rx) = mpsc::unbounded_channel::<Vec<u8>>();
     let (response_tx, response_rx) = tokio::sync::oneshot::channel();
 
-    let metadata_c = metadata.clone();
-    let auth_c = auth.clone();
-    let tls_c = tls_config.clone();
-    let output_desc_c = output_desc.clone();
     let tab_id_c = tab_id.clone();
-    let app_c = app.clone();
+    let received = Arc::new(Mutex::new(Vec::<Value>::new()));
+    let received_c = Arc::clone(&received);
+    let (abort_handle, abort_registration) = AbortHandle::new_pair();
 
     tokio::spawn(async move {
-        let result: Result<String, String> = async {
-            let mut req_builder = HttpRequest::builder()
-                .method("POST")
-                .uri(uri)
-                .header("content-type", "application/grpc")
-                .header("te", "trailers");
-
-            if let Some(ref a) = auth_c {
-                req_builder = apply_auth(req_builder, a);
-            }
-            if let Some(ref meta) = metadata_c {
-                validate_metadata(meta)?;
-                for (k, v) in meta {
-                    req_builder = req_builder.header(k.as_str(), v.as_str());
-                }
-            }
-
-            let (mut body_sender, body_receiver) = Body::channel();
-            let req = req_builder.body(body_receiver).map_err(|e| e.to_string())?;
-
-            let connector = build_https_connector(tls_c.as_ref())?;
-            let client = Client::builder().http2_only(true).build::<_, Body>(connector);
-
-            let response_future = client.request(req);
-
-            let sender_task = tokio::spawn(async move {
-                while let Some(msg_bytes) = message_rx.recv().await {
-                    if body_sender.send_data(bytes::Bytes::from(msg_bytes)).await.is_err() {
-                        break;
-                    }
-                }
-            });
+        let on_message = |index: usize, inspected: &wire::Inspected| {
+            let _ = app.emit("grpc-stream-message", serde_json::json!({
+                "tabId": tab_id_c,
+                "index": index,
+                "data": inspected.data,
+                "raw": inspected.raw,
+                "decode_error": inspected.decode_error,
+                "unknown_fields": inspected.unknown_fields,
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            }));
+            received_c.lock().unwrap_or_else(|p| p.into_inner()).push(inspected.data.clone());
+        };
 
-            let response = response_future.await.map_err(|e| e.to_string())?;
-
-            if is_bidi {
-                let mut body_stream = response.into_body();
-                let mut buf = bytes::BytesMut::new();
-                let mut idx = 0;
-
-                while let Some(chunk) = body_stream.next().await {
-                    if let Ok(c) = chunk {
-                        buf.extend_from_slice(&c);
-                        loop {
-                            if buf.len() < 5 { break; }
-                            let msg_len = u32::from_be_bytes([buf[1], buf[2], buf[3], buf[4]]) as usize;
-                            if buf.len() < 5 + msg_len { break; }
-                            buf.advance(5);
-                            let msg_bytes = buf.split_to(msg_len);
-                            if let Ok(msg) = DynamicMessage::decode(output_desc_c.clone(), msg_bytes.as_ref()) {
-                                if let Ok(json) = serde_json::to_value(msg) {
-                                    let _ = app_c.emit("grpc-stream-message", serde_json::json!({
-                                        "tabId": tab_id_c,
-                                        "index": idx,
-                                        "data": json,
-                                        "timestamp": chrono::Utc::now().to_rfc3339(),
-                                    }));
-                                    idx += 1;
-                                }
-                            }
-                        }
+        let outcome = Abortable::new(call.stream(&client, message_rx, on_message), abort_registration).await;
+        let messages = received_c.lock().unwrap_or_else(|p| p.into_inner()).clone();
+        let result = match outcome {
+            Ok(outcome) => {
+                // Bidi responses were emitted one by one; collect them so the result
+                // (and assertions on it) cover the whole stream.
+                let outcome = outcome.map(|mut result| {
+                    if is_bidi {
+                        result["is_streaming"] = Value::Bool(true);
+                        result["response"] = Value::Array(messages);
                     }
-                }
-                let _ = sender_task.await;
-                serde_json::to_string(&serde_json::json!({
-                    "grpc_status": "0",
-                    "grpc_message": "OK",
-                    "message": "Bidirectional stream completed"
-                }))
-                .map_err(|e| e.to_string())
-            } else {
-                let _ = sender_task.await;
-                let body_bytes = hyper::body::to_bytes(response.into_body())
-                    .await
-                    .map_err(|e| e.to_string())?;
-
-                if body_bytes.len() < 5 {
-                    return Err("Response too short".to_string());
-                }
-                let msg_len = u32::from_be_bytes([
-                    body_bytes[1], body_bytes[2], body_bytes[3], body_bytes[4],
-                ]) as usize;
-                if body_bytes.len() < 5 + msg_len {
-                    return Err("Incomplete response".to_string());
-                }
-                let msg = DynamicMessage::decode(output_desc_c, &body_bytes[5..5 + msg_len])
-                    .map_err(|e| e.to_string())?;
-                let json = serde_json::to_value(msg).map_err(|e| e.to_string())?;
-                serde_json::to_string(&serde_json::json!({
-                    "response": json,
-                    "grpc_status": "0",
-                    "grpc_message": "OK"
-                }))
-                .map_err(|e| e.to_string())
+                    result
+                });
+                check_assertions(outcome, &assertions)
             }
+            Err(_aborted) => Ok(cancelled_result(messages, is_bidi)),
         }
-        .await;
+        .map(|result| result.to_string());
 
         let _ = response_tx.send(result);
     });
@@ -803,7 +669,11 @@ async fn start_client_stream(
         ActiveClientStream {
             sender: message_tx,
             input_desc,
+            encoding,
             response_receiver: response_rx,
+            abort: abort_handle,
+            received,
+            is_streaming: is_bidi,
         },
     );
 
@@ -815,20 +685,21 @@ async fn send_stream_message(
     tab_id: String,
     message_id: String,
     body: String,
+    format: Option<String>,
 ) -> Result<String, String> {
-    let (sender, input_desc) = {
+    let format = format.as_deref().map(MessageFormat::parse).transpose()?.unwrap_or_default();
+    let (sender, input_desc, encoding) = {
         let streams = ACTIVE_CLIENT_STREAMS.lock().unwrap_or_else(|p| p.into_inner());
         let s = streams
             .get(&tab_id)
             .ok_or("Stream not found. Start the stream first.")?;
-        (s.sender.clone(), s.input_desc.clone())
+        (s.sender.clone(), s.input_desc.clone(), s.encoding)
     };
 
-    let msg = DynamicMessage::deserialize(input_desc, &mut serde_json::Deserializer::from_str(&body))
-        .map_err(|e| format!("Failed to deserialize message: {}", e))?;
+    let msg = format.decode(input_desc, &body)?;
 
     sender
-        .send(grpc_frame(&msg.encode_to_vec()))
+        .send(engine::encode_message(&msg.encode_to_vec(), encoding)?)
         .map_err(|_| "Failed to send message, stream may be closed".to_string())?;
 
     Ok(format!("Message {} sent", message_id))
@@ -850,6 +721,163 @@ async fn finish_streaming(tab_id: String) -> Result<String, String> {
         .map_err(|_| "Failed to receive response from stream task".to_string())?
 }
 
+/// Cancel the in-flight call for a tab, whether it is a unary/server-streaming call
+/// or an open client/bidi stream. The server receives RST_STREAM(CANCEL) and the
+/// messages received so far are returned with a CANCELLED status.
+#[tauri::command]
+async fn cancel_call(app: tauri::AppHandle, tab_id: String) -> Result<String, String> {
+    let (messages, is_streaming) = abort_active_call(&tab_id)?;
+    let _ = app.emit("grpc-stream-end", serde_json::json!({
+        "tabId": tab_id,
+        "grpc_status": "1",
+        "grpc_message": "CANCELLED",
+        "message_count": messages.len(),
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    }));
+
+    Ok(serde_json::to_string_pretty(&cancelled_result(messages, is_streaming)).unwrap())
+}
+
+/// Call a method repeatedly at the given concurrency until `benchmark.total` calls
+/// or `benchmark.durationMs` have passed, over one pooled connection. The body and
+/// metadata may use `{{iteration.index}}`, `{{iteration.uuid}}` and the like, filled
+/// in per call. Progress is emitted as "benchmark-progress" events; the returned
+/// report has latency percentiles, throughput and status counts.
+#[tauri::command]
+async fn run_benchmark(
+    state: tauri::State<'_, AppState>,
+    app: tauri::AppHandle,
+    tab_id: String,
+    service: String,
+    method: String,
+    request_data: String,
+    endpoint: String,
+    proto_content: Option<String>,
+    import_paths: Option<Vec<proto_parser::ImportPath>>,
+    metadata: Option<HashMap<String, String>>,
+    auth: Option<AuthConfig>,
+    tls_config: Option<TlsConfig>,
+    timeout_ms: Option<u64>,
+    compression: Option<String>,
+    transport_config: Option<TransportConfig>,
+    request_format: Option<String>,
+    benchmark: BenchmarkOptions,
+) -> Result<String, String> {
+    let request_format = request_format.as_deref().map(MessageFormat::parse).transpose()?.unwrap_or_default();
+    let options = CallOptions {
+        metadata: metadata.map(|m| m.into_iter().collect()),
+        auth,
+        tls: tls_config,
+        timeout_ms,
+        encoding: compression.as_deref().map(Encoding::parse).transpose()?.unwrap_or_default(),
+        transport_config,
+        ..Default::default()
+    };
+
+    let clean_endpoint = engine::clean_endpoint(&endpoint);
+    let pool = resolve_pool(&state, proto_content.as_deref(), import_paths.as_deref(), &clean_endpoint)?;
+    let call = Call::new(pool, &clean_endpoint, &service, &method, options)?;
+    let client = state.channel(&call.endpoint, call.options.tls.as_ref(), call.transport, call.options.transport_config.as_ref())?;
+
+    let bench = Benchmark::new(call, client, &request_data, request_format, benchmark)?;
+    {
+        let mut running = ACTIVE_BENCHMARKS.lock().unwrap_or_else(|p| p.into_inner());
+        if running.contains_key(&tab_id) {
+            return Err("A benchmark is already running for this tab".to_string());
+        }
+        running.insert(tab_id.clone(), bench.stop_handle());
+    }
+
+    let report = bench
+        .run(|progress| {
+            let _ = app.emit("benchmark-progress", serde_json::json!({
+                "tabId": tab_id,
+                "progress": progress,
+            }));
+        })
+        .await;
+    ACTIVE_BENCHMARKS.lock().unwrap_or_else(|p| p.into_inner()).remove(&tab_id);
+
+    serde_json::to_string_pretty(&report).map_err(|e| e.to_string())
+}
+
+/// Stop a tab's benchmark. Calls in flight finish, and `run_benchmark` returns the
+/// report so far.
+#[tauri::command]
+fn stop_benchmark(tab_id: String) -> Result<(), String> {
+    let running = ACTIVE_BENCHMARKS.lock().unwrap_or_else(|p| p.into_inner());
+    let stop = running.get(&tab_id).ok_or("No benchmark running for this tab")?;
+    stop.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Check a request body against the method's input type without sending it. An
+/// empty list means the request is valid.
+#[tauri::command]
+fn validate_request(
+    state: tauri::State<'_, AppState>,
+    service: String,
+    method: String,
+    request_data: String,
+    endpoint: Option<String>,
+    proto_content: Option<String>,
+    import_paths: Option<Vec<proto_parser::ImportPath>>,
+) -> Result<Vec<validation::Diagnostic>, String> {
+    let endpoint = endpoint.as_deref().map(engine::clean_endpoint).unwrap_or_default();
+    let pool = resolve_pool(&state, proto_content.as_deref(), import_paths.as_deref(), &endpoint)?;
+    let service_desc = engine::find_service(&pool, &service)?;
+    let method_desc = service_desc
+        .methods()
+        .find(|m| m.name() == method)
+        .ok_or_else(|| format!("Method '{}' not found in service '{}'", method, service))?;
+    Ok(validation::validate_request(&method_desc.input(), &request_data))
+}
+
+/// Sample request body for a method. `google.protobuf.Any` fields embed a sample of
+/// `any_type` (a full message name) when given.
+#[tauri::command]
+fn generate_sample_request(
+    state: tauri::State<'_, AppState>,
+    service: String,
+    method: String,
+    any_type: Option<String>,
+    endpoint: Option<String>,
+    proto_content: Option<String>,
+    import_paths: Option<Vec<proto_parser::ImportPath>>,
+) -> Result<String, String> {
+    let endpoint = endpoint.as_deref().map(engine::clean_endpoint).unwrap_or_default();
+    let pool = resolve_pool(&state, proto_content.as_deref(), import_paths.as_deref(), &endpoint)?;
+    let service_desc = engine::find_service(&pool, &service)?;
+    let method_desc = service_desc
+        .methods()
+        .find(|m| m.name() == method)
+        .ok_or_else(|| format!("Method '{}' not found in service '{}'", method, service))?;
+    let any_desc = any_type
+        .as_deref()
+        .map(|name| {
+            pool.get_message_by_name(name.trim_start_matches('.'))
+                .ok_or_else(|| format!("Message type '{}' not found", name))
+        })
+        .transpose()?;
+    let sample = proto_parser::sample_request(&method_desc.input(), any_desc.as_ref());
+    serde_json::to_string_pretty(&sample).map_err(|e| e.to_string())
+}
+
+/// Structured schema of a message, for building request forms and autocompletion.
+#[tauri::command]
+fn describe_message(
+    state: tauri::State<'_, AppState>,
+    message_name: String,
+    import_paths: Option<Vec<proto_parser::ImportPath>>,
+    endpoint: Option<String>,
+) -> Result<schema::MessageSchema, String> {
+    let pool = cached_pool(&state, import_paths.as_deref(), endpoint.as_deref())?;
+    let desc = pool
+        .get_message_by_name(message_name.trim_start_matches('.'))
+        .ok_or_else(|| format!("Message type '{}' not found", message_name))?;
+    Ok(schema::describe_message(&desc))
+}
+
 #[tauri::command]
 fn open_response_in_temp_file(file_name: String, contents: String) -> Result<String, String> {
     let sanitized = Regex::new(r#"[^A-Za-z0-9._-]"#)
@@ -885,7 +913,76 @@ fn open_response_in_temp_file(file_name: String, contents: String) -> Result<Str
 #[tauri::command]
 fn save_response_to_file(path: String, contents: String) -> Result<(), String> {
     let dest: PathBuf = PathBuf::from(&path);
+    ensure_within_home(&dest)?;
 
+    std::fs::write(&dest, contents)
+        .map_err(|e| format!("Failed to save file: {}", e))
+}
+
+/// Write the schema behind the given import paths (or the endpoint's reflected
+/// schema) as a binary `FileDescriptorSet`. Returns the number of files written.
+#[tauri::command]
+fn export_descriptor_set(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    import_paths: Option<Vec<proto_parser::ImportPath>>,
+    endpoint: Option<String>,
+) -> Result<usize, String> {
+    let pool = cached_pool(&state, import_paths.as_deref(), endpoint.as_deref())?;
+    let dest = PathBuf::from(&path);
+    ensure_within_home(&dest)?;
+
+    std::fs::write(&dest, pool.encode_to_vec())
+        .map_err(|e| format!("Failed to save descriptor set: {}", e))?;
+    let file_count = pool.files().len();
+    Ok(file_count)
+}
+
+/// Regenerate a `.proto` file for every file in the schema, laid out under
+/// `directory` by import path. Returns the paths written.
+#[tauri::command]
+fn export_proto_sources(
+    state: tauri::State<'_, AppState>,
+    directory: String,
+    import_paths: Option<Vec<proto_parser::ImportPath>>,
+    endpoint: Option<String>,
+) -> Result<Vec<String>, String> {
+    let pool = cached_pool(&state, import_paths.as_deref(), endpoint.as_deref())?;
+    let root = PathBuf::from(&directory);
+    ensure_within_home(&root)?;
+
+    let mut written = Vec::new();
+    for file in pool.files() {
+        // File names come from the server when reflected; never let one escape `root`.
+        let relative = Path::new(file.name());
+        if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(format!("Refusing to export file with unsafe name '{}'", file.name()));
+        }
+        let dest = root.join(relative);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&dest, proto_printer::print_file(&file))
+            .map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+        written.push(dest.to_string_lossy().to_string());
+    }
+    Ok(written)
+}
+
+/// The schema for commands that don't make a call: compiled from `import_paths` when
+/// any are enabled, otherwise the one reflected from `endpoint`.
+fn cached_pool(
+    state: &tauri::State<'_, AppState>,
+    import_paths: Option<&[proto_parser::ImportPath]>,
+    endpoint: Option<&str>,
+) -> Result<Arc<DescriptorPool>, String> {
+    let endpoint = endpoint.map(engine::clean_endpoint).unwrap_or_default();
+    source_pool(state, None, import_paths, &endpoint)
+}
+
+/// Reject destinations outside the user's home directory.
+fn ensure_within_home(dest: &Path) -> Result<(), String> {
     // Resolve symlinks and ".." so the check can't be fooled by path traversal tricks.
     // Canonicalize requires the parent directory to exist (the file itself may not yet).
     let resolved = if dest.exists() {
@@ -901,26 +998,48 @@ fn save_response_to_file(path: String, contents: String) -> Result<(), String> {
     if !resolved.starts_with(&home) {
         return Err("Save path must be within your home directory".to_string());
     }
-
-    std::fs::write(&dest, contents)
-        .map_err(|e| format!("Failed to save file: {}", e))
+    Ok(())
 }
 
 // ---------------------------------------------------------------------------
 // Pool resolution helper (shared by call_grpc_method and start_client_stream)
 // ---------------------------------------------------------------------------
 
+/// The pool a call runs against, extended with the other cached pool's types so
+/// `Any` payloads defined only there still resolve.
 fn resolve_pool(
     state: &tauri::State<'_, AppState>,
     proto_content: Option<&str>,
     import_paths: Option<&[proto_parser::ImportPath]>,
+    endpoint: &str,
 ) -> Result<Arc<DescriptorPool>, String> {
-    if let Some(paths) = import_paths {
-        state.get_or_compile(paths)
-    } else if let Some(content) = proto_content {
-        proto_parser::compile_single_file(content).map(Arc::new)
-    } else {
-        Err("Either proto_content or import_paths must be provided".to_string())
+    let pool = source_pool(state, proto_content, import_paths, endpoint)?;
+    state.with_known_types(pool, endpoint)
+}
+
+/// The pool built from the request's own protos, or reflection when it has none.
+/// Disabling every import path selects reflection too, but only once services have
+/// been reflected from `endpoint`; until then it's an error of its own.
+fn source_pool(
+    state: &AppState,
+    proto_content: Option<&str>,
+    import_paths: Option<&[proto_parser::ImportPath]>,
+    endpoint: &str,
+) -> Result<Arc<DescriptorPool>, String> {
+    match (import_paths, proto_content) {
+        (Some(paths), _) if paths.iter().any(|p| p.enabled) => state.get_or_compile(paths),
+        (_, Some(content)) => proto_parser::compile_single_file(content).map(Arc::new),
+        (Some(paths), None) if !paths.is_empty() => state.reflected_pool(endpoint).ok_or_else(|| {
+            format!(
+                "All import paths are disabled and no services have been loaded from {} via server reflection; \
+                 enable an import path or load services via reflection",
+                endpoint
+            )
+        }),
+        _ => state.reflected_pool(endpoint).ok_or_else(|| {
+            "Either proto_content or import_paths must be provided, or load services via server reflection first"
+                .to_string()
+        }),
     }
 }
 
@@ -935,13 +1054,145 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             parse_proto_file,
             parse_proto_files,
+            reflect_services,
             call_grpc_method,
             start_client_stream,
             send_stream_message,
             finish_streaming,
+            cancel_call,
+            run_benchmark,
+            stop_benchmark,
+            list_channels,
+            close_channel,
+            close_all_channels,
             open_response_in_temp_file,
             save_response_to_file,
+            export_descriptor_set,
+            export_proto_sources,
+            describe_message,
+            validate_request,
+            generate_sample_request,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool_with(file: &str, package: &str) -> DescriptorPool {
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_protos([prost_types::FileDescriptorProto {
+            name: Some(file.to_string()),
+            package: Some(package.to_string()),
+            message_type: vec![prost_types::DescriptorProto {
+                name: Some("Payload".to_string()),
+                ..Default::default()
+            }],
+            syntax: Some("proto3".to_string()),
+            ..Default::default()
+        }])
+        .unwrap();
+        pool
+    }
+
+    #[test]
+    fn test_merged_pool_is_cached_until_a_source_changes() {
+        let state = AppState::new();
+        state.store(&[], pool_with("local.proto", "local"));
+        state.store_reflected("svc:443", pool_with("remote.proto", "remote"));
+        let local = state.get_or_compile(&[]).unwrap();
+
+        let first = state.with_known_types(Arc::clone(&local), "svc:443").unwrap();
+        assert!(first.get_message_by_name("remote.Payload").is_some());
+        let second = state.with_known_types(Arc::clone(&local), "svc:443").unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        state.store_reflected("svc:443", pool_with("other.proto", "other"));
+        let third = state.with_known_types(local, "svc:443").unwrap();
+        assert!(!Arc::ptr_eq(&first, &third));
+        assert!(third.get_message_by_name("other.Payload").is_some());
+    }
+
+    #[test]
+    fn test_disabled_import_paths_select_reflection() {
+        let state = AppState::new();
+        let disabled = [proto_parser::ImportPath {
+            id: "1".to_string(),
+            path: "/protos".to_string(),
+            path_type: "directory".to_string(),
+            enabled: false,
+        }];
+
+        let err = source_pool(&state, None, Some(&disabled), "svc:443").unwrap_err();
+        assert!(err.starts_with("All import paths are disabled"), "{}", err);
+        assert!(source_pool(&state, None, Some(&[]), "svc:443").unwrap_err().starts_with("Either proto_content"));
+
+        state.store_reflected("svc:443", pool_with("remote.proto", "remote"));
+        let pool = source_pool(&state, None, Some(&disabled), "svc:443").unwrap();
+        assert!(pool.get_message_by_name("remote.Payload").is_some());
+    }
+
+    #[test]
+    fn test_check_assertions_covers_failed_calls() {
+        let assertions: Vec<Assertion> = serde_json::from_str(r#"[{"type": "status", "code": "UNAVAILABLE"}]"#).unwrap();
+
+        let ok = check_assertions(Ok(serde_json::json!({ "grpc_status": "0" })), &assertions).unwrap();
+        assert_eq!(ok["assertions_passed"], false);
+
+        let refused = engine::format_connection_error(engine::ConnectionError::Transport("connection refused"), "localhost:1", "pkg.S", "M");
+        let err = check_assertions(Err(refused), &assertions).unwrap_err();
+        let err: Value = serde_json::from_str(&err).unwrap();
+        assert_eq!(err["assertions_passed"], true);
+
+        // Plain errors and calls without assertions pass through untouched.
+        assert_eq!(check_assertions(Err("bad".to_string()), &assertions).unwrap_err(), "bad");
+        assert!(check_assertions(Ok(serde_json::json!({})), &[]).unwrap().get("assertions").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_aborts_the_call_and_keeps_what_arrived() {
+        let received = Arc::new(Mutex::new(vec![serde_json::json!({ "n": 1 })]));
+        let (abort, registration) = AbortHandle::new_pair();
+        let call = Abortable::new(std::future::pending::<()>(), registration);
+        register_call("cancel-tab", abort, Arc::clone(&received), true);
+
+        let (messages, is_streaming) = abort_active_call("cancel-tab").unwrap();
+        assert!(call.await.is_err(), "the call future is aborted");
+        assert_eq!(messages, vec![serde_json::json!({ "n": 1 })]);
+        assert!(is_streaming);
+
+        // The call is gone, so a second cancel has nothing to abort.
+        assert_eq!(abort_active_call("cancel-tab").unwrap_err(), "No active call for this tab");
+    }
+
+    #[test]
+    fn test_unregister_leaves_a_newer_call_on_the_tab() {
+        let empty = || Arc::new(Mutex::new(Vec::new()));
+        let first = register_call("rerun-tab", AbortHandle::new_pair().0, empty(), false);
+        let second = register_call("rerun-tab", AbortHandle::new_pair().0, empty(), false);
+
+        unregister_call("rerun-tab", first);
+        assert!(ACTIVE_CALLS.lock().unwrap().contains_key("rerun-tab"));
+        unregister_call("rerun-tab", second);
+        assert!(!ACTIVE_CALLS.lock().unwrap().contains_key("rerun-tab"));
+    }
+
+    #[test]
+    fn test_cancelled_result_matches_the_call_shape() {
+        let unary = cancelled_result(vec![], false);
+        assert_eq!(unary["is_streaming"], false);
+        assert_eq!(unary["response"], Value::Null);
+        assert_eq!(unary["grpc_status"], "1");
+
+        let streamed = cancelled_result(vec![serde_json::json!(1), serde_json::json!(2)], true);
+        assert_eq!(streamed["is_streaming"], true);
+        assert_eq!(streamed["message_count"], 2);
+        assert_eq!(streamed["response"], serde_json::json!([1, 2]));
+    }
+}
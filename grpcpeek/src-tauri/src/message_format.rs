@@ -0,0 +1,206 @@
+use crate::status_details::decode_bin_value;
+use base64::{engine::general_purpose, Engine as _};
+use prost::Message as ProstMessage;
+use prost_reflect::text_format::FormatOptions;
+use prost_reflect::{DynamicMessage, MessageDescriptor, SerializeOptions};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// How a message is written in a request body or rendered in a response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// Proto3 JSON mapping.
+    Json(JsonOptions),
+    /// Protobuf text format (`textproto`).
+    Text,
+    /// Wire-format bytes, base64-encoded.
+    Base64,
+    /// Wire-format bytes, hex-encoded.
+    Hex,
+}
+
+impl MessageFormat {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "" | "json" => Ok(MessageFormat::default()),
+            "text" | "textproto" | "prototext" => Ok(MessageFormat::Text),
+            "base64" | "binary" => Ok(MessageFormat::Base64),
+            "hex" => Ok(MessageFormat::Hex),
+            other => Err(format!(
+                "Unsupported message format '{}'. Supported: json, text, base64, hex",
+                other
+            )),
+        }
+    }
+
+    /// Apply JSON output options; other formats are unaffected.
+    pub fn with_json_options(self, options: Option<JsonOptions>) -> Self {
+        match (self, options) {
+            (MessageFormat::Json(_), Some(options)) => MessageFormat::Json(options),
+            (format, _) => format,
+        }
+    }
+
+    /// Parse a request body written in this format.
+    pub fn decode(&self, desc: MessageDescriptor, data: &str) -> Result<DynamicMessage, String> {
+        match self {
+            MessageFormat::Json(_) => {
+                DynamicMessage::deserialize(desc, &mut serde_json::Deserializer::from_str(data))
+                    .map_err(|e| format!("Failed to deserialize request JSON to protobuf: {}", e))
+            }
+            MessageFormat::Text => DynamicMessage::parse_text_format(desc, data)
+                .map_err(|e| format!("Failed to parse request text format: {}", e)),
+            MessageFormat::Base64 | MessageFormat::Hex => {
+                let bytes = match self {
+                    MessageFormat::Base64 => decode_base64(data)?,
+                    _ => decode_hex(data).map_err(|e| format!("Invalid hex request body: {}", e))?,
+                };
+                DynamicMessage::decode(desc, bytes.as_slice())
+                    .map_err(|e| format!("Failed to decode request bytes: {}", e))
+            }
+        }
+    }
+
+    /// Render a message for the result JSON: an object for `Json`, a string otherwise.
+    pub fn render(&self, msg: &DynamicMessage) -> Result<Value, String> {
+        match self {
+            MessageFormat::Json(options) => crate::any::to_json(msg, &options.serialize_options()),
+            MessageFormat::Text => {
+                Ok(Value::String(msg.to_text_format_with_options(&FormatOptions::new().pretty(true))))
+            }
+            MessageFormat::Base64 => Ok(Value::String(general_purpose::STANDARD.encode(msg.encode_to_vec()))),
+            MessageFormat::Hex => Ok(Value::String(encode_hex(&msg.encode_to_vec()))),
+        }
+    }
+}
+
+impl Default for MessageFormat {
+    fn default() -> Self {
+        MessageFormat::Json(JsonOptions::default())
+    }
+}
+
+/// How responses are written as JSON. The defaults are the canonical proto3 JSON
+/// mapping.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct JsonOptions {
+    /// Include fields that hold their default value (`0`, `""`, `false`, `[]`).
+    pub emit_defaults: bool,
+    /// Use field names as written in the `.proto` instead of lowerCamelCase.
+    pub proto_field_names: bool,
+    pub enums_as_ints: bool,
+    /// Write 64-bit integers as JSON numbers rather than strings. JavaScript
+    /// loses precision above 2^53.
+    pub int64_as_numbers: bool,
+}
+
+impl JsonOptions {
+    fn serialize_options(&self) -> SerializeOptions {
+        SerializeOptions::new()
+            .skip_default_fields(!self.emit_defaults)
+            .use_proto_field_name(self.proto_field_names)
+            .use_enum_numbers(self.enums_as_ints)
+            .stringify_64_bit_integers(!self.int64_as_numbers)
+    }
+}
+
+/// Accepts padded and unpadded input; URL-safe characters are mapped first.
+fn decode_base64(data: &str) -> Result<Vec<u8>, String> {
+    let normalized: String = data
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| match c {
+            '-' => '+',
+            '_' => '/',
+            c => c,
+        })
+        .collect();
+    decode_bin_value(normalized.as_bytes()).map_err(|e| format!("Invalid base64 request body: {}", e))
+}
+
+/// Hex digits, optionally `0x`-prefixed and separated by whitespace or colons.
+pub fn decode_hex(data: &str) -> Result<Vec<u8>, String> {
+    let trimmed = data.trim();
+    let digits: Vec<u8> = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed)
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace() && *b != b':')
+        .collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err("odd number of digits".to_string());
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|s| u8::from_str_radix(s, 16).ok())
+                .ok_or_else(|| format!("'{}' is not a hex byte", String::from_utf8_lossy(pair)))
+        })
+        .collect()
+}
+
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto_parser::compile_single_file;
+
+    #[test]
+    fn test_formats_round_trip() {
+        let proto = r#"
+syntax = "proto3";
+package fmt.v1;
+service Svc { rpc Do (Req) returns (Req); }
+message Req { string name = 1; repeated int32 ids = 2; }
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let desc = pool.get_message_by_name("fmt.v1.Req").unwrap();
+
+        let from_text = MessageFormat::Text.decode(desc.clone(), "name: \"a\" ids: 1 ids: 2").unwrap();
+        let from_json = MessageFormat::default().decode(desc.clone(), r#"{"name": "a", "ids": [1, 2]}"#).unwrap();
+        assert_eq!(from_text, from_json);
+
+        let hex = MessageFormat::Hex.render(&from_text).unwrap();
+        assert_eq!(hex, "0a016112020102");
+        assert_eq!(MessageFormat::Hex.decode(desc.clone(), "0x0a:01:61 12 02 01 02").unwrap(), from_json);
+
+        let b64 = MessageFormat::Base64.render(&from_text).unwrap();
+        let unpadded = b64.as_str().unwrap().trim_end_matches('=');
+        assert_eq!(MessageFormat::Base64.decode(desc.clone(), unpadded).unwrap(), from_json);
+
+        let text = MessageFormat::Text.render(&from_json).unwrap();
+        assert_eq!(MessageFormat::Text.decode(desc, text.as_str().unwrap()).unwrap(), from_json);
+    }
+
+    #[test]
+    fn test_json_options() {
+        let proto = r#"
+syntax = "proto3";
+package fmt.v1;
+service Svc { rpc Do (Resp) returns (Resp); }
+enum State { STATE_UNSPECIFIED = 0; STATE_ON = 1; }
+message Resp { int64 total_count = 1; State state = 2; string note = 3; }
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let desc = pool.get_message_by_name("fmt.v1.Resp").unwrap();
+        let msg = MessageFormat::Text.decode(desc, "total_count: 7 state: STATE_ON").unwrap();
+
+        let canonical = MessageFormat::default().render(&msg).unwrap();
+        assert_eq!(canonical, serde_json::json!({"totalCount": "7", "state": "STATE_ON"}));
+
+        let options = JsonOptions { emit_defaults: true, proto_field_names: true, enums_as_ints: true, int64_as_numbers: true };
+        let custom = MessageFormat::default().with_json_options(Some(options)).render(&msg).unwrap();
+        assert_eq!(custom, serde_json::json!({"total_count": 7, "state": 1, "note": ""}));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::message_format::{decode_hex, encode_hex, MessageFormat};
+use crate::status_details::decode_bin_value;
+use base64::{engine::general_purpose, Engine as _};
+use http::{HeaderMap, HeaderValue};
+use prost_reflect::{DescriptorPool, DynamicMessage};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Keys ending in `-bin` carry binary values, base64-encoded on the wire.
+const BINARY_SUFFIX: &str = "-bin";
+
+pub fn is_binary_key(key: &str) -> bool {
+    key.to_ascii_lowercase().ends_with(BINARY_SUFFIX)
+}
+
+pub fn validate(meta: &[(String, String)]) -> Result<(), String> {
+    for (k, v) in meta {
+        if k.chars().any(|c| c.is_control()) {
+            return Err(format!("Invalid metadata key '{}': contains control characters", k));
+        }
+        if !is_binary_key(k) && v.chars().any(|c| c == '\n' || c == '\r' || c == '\0') {
+            return Err(format!("Invalid metadata value for '{}': contains newline or null characters", k));
+        }
+    }
+    Ok(())
+}
+
+/// Encode a request metadata value for the wire. Text values are sent as written.
+/// Values of `-bin` keys are base64-encoded: `hex:` and `base64:` prefixes give the
+/// raw bytes, anything else is taken as UTF-8 text.
+pub fn encode_value(key: &str, value: &str) -> Result<HeaderValue, String> {
+    if !is_binary_key(key) {
+        return HeaderValue::from_str(value)
+            .map_err(|e| format!("Invalid metadata value for '{}': {}", key, e));
+    }
+    let bytes = if let Some(hex) = value.strip_prefix("hex:") {
+        decode_hex(hex).map_err(|e| format!("Invalid hex in metadata value for '{}': {}", key, e))?
+    } else if let Some(b64) = value.strip_prefix("base64:") {
+        decode_bin_value(b64.trim().as_bytes())
+            .map_err(|e| format!("Invalid base64 in metadata value for '{}': {}", key, e))?
+    } else {
+        value.as_bytes().to_vec()
+    };
+    // gRPC implementations must accept unpadded values, and most send them that way.
+    HeaderValue::from_str(&general_purpose::STANDARD_NO_PAD.encode(bytes))
+        .map_err(|e| format!("Invalid metadata value for '{}': {}", key, e))
+}
+
+/// Render response headers or trailers. Text values are strings; values that aren't
+/// valid UTF-8 are kept with replacement characters. `-bin` values are objects with
+/// the bytes as `base64` and `hex`, plus `decoded` when `bin_types` names a message
+/// type for the key. Repeated text keys are joined with ", "; repeated binary keys
+/// become an array.
+pub fn to_json(map: &HeaderMap, bin_types: Option<&HashMap<String, String>>, pool: &DescriptorPool) -> Value {
+    let mut obj = serde_json::Map::new();
+    for name in map.keys() {
+        let key = name.as_str();
+        let values = map.get_all(name).iter();
+        let value = if is_binary_key(key) {
+            let type_name = bin_types.and_then(|types| {
+                types
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                    .map(|(_, t)| t.as_str())
+            });
+            let mut decoded: Vec<Value> = values.map(|v| binary_value(v.as_bytes(), type_name, pool)).collect();
+            if decoded.len() == 1 {
+                decoded.remove(0)
+            } else {
+                Value::Array(decoded)
+            }
+        } else {
+            let text: Vec<String> = values.map(|v| String::from_utf8_lossy(v.as_bytes()).into_owned()).collect();
+            Value::String(text.join(", "))
+        };
+        obj.insert(key.to_string(), value);
+    }
+    Value::Object(obj)
+}
+
+fn binary_value(raw: &[u8], type_name: Option<&str>, pool: &DescriptorPool) -> Value {
+    let bytes = match decode_bin_value(raw.trim_ascii()) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return serde_json::json!({
+                "raw": String::from_utf8_lossy(raw),
+                "decode_error": format!("Invalid base64: {}", e),
+            })
+        }
+    };
+    let mut value = serde_json::json!({
+        "base64": general_purpose::STANDARD.encode(&bytes),
+        "hex": encode_hex(&bytes),
+    });
+    if let Some(type_name) = type_name {
+        let decoded = pool
+            .get_message_by_name(type_name.trim_start_matches('.'))
+            .ok_or_else(|| format!("Message type '{}' not found", type_name))
+            .and_then(|desc| {
+                DynamicMessage::decode(desc, bytes.as_slice())
+                    .map_err(|e| format!("Failed to decode as {}: {}", type_name, e))
+            })
+            .and_then(|msg| MessageFormat::default().render(&msg));
+        match decoded {
+            Ok(message) => value["decoded"] = message,
+            Err(e) => value["decode_error"] = Value::String(e),
+        }
+    }
+    value
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto_parser::compile_single_file;
+
+    #[test]
+    fn test_binary_metadata_round_trip() {
+        let proto = r#"
+syntax = "proto3";
+package meta.v1;
+service Svc { rpc Do (Tag) returns (Tag); }
+message Tag { string name = 1; }
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+
+        assert_eq!(encode_value("trace-bin", "hex:0a 02 68 69").unwrap(), "CgJoaQ");
+        assert_eq!(encode_value("trace-bin", "base64:CgJoaQ==").unwrap(), "CgJoaQ");
+        assert_eq!(encode_value("x-user", "alice").unwrap(), "alice");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("trace-bin", HeaderValue::from_static("CgJoaQ"));
+        headers.insert("x-raw", HeaderValue::from_bytes(b"caf\xe9").unwrap());
+        let types = HashMap::from([("Trace-Bin".to_string(), "meta.v1.Tag".to_string())]);
+        let json = to_json(&headers, Some(&types), &pool);
+
+        assert_eq!(json["trace-bin"]["hex"], "0a026869");
+        assert_eq!(json["trace-bin"]["base64"], "CgJoaQ==");
+        assert_eq!(json["trace-bin"]["decoded"]["name"], "hi");
+        assert_eq!(json["x-raw"], "caf\u{fffd}", "non-UTF-8 values are kept, not dropped");
+    }
+}
@@ -1,24 +1,32 @@
 use prost::Message as ProstMessage;
-use prost_reflect::{DescriptorPool, FieldDescriptor, MessageDescriptor};
+use prost_types::FileDescriptorProto;
+use prost_reflect::{DescriptorPool, FieldDescriptor, MessageDescriptor, ServiceDescriptor};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use protox::file::{ChainFileResolver, DescriptorSetFileResolver, GoogleFileResolver, IncludeFileResolver};
 use tempfile::NamedTempFile;
 use walkdir::WalkDir;
 
 const MAX_SAMPLE_DEPTH: usize = 4;
 
+/// Extensions of pre-compiled `FileDescriptorSet` files (`protoc --descriptor_set_out`).
+const DESCRIPTOR_SET_EXTENSIONS: &[&str] = &["protoset", "pb", "binpb"];
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Service {
+    /// Fully-qualified name (`pkg.sub.Service`), unique across packages.
     pub name: String,
     pub package_name: Option<String>,
     pub methods: Vec<Method>,
     pub source_proto: Option<String>,
+    /// Comments on the service definition, when the descriptor has source info.
+    pub documentation: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -31,6 +39,7 @@ pub struct Method {
     pub is_server_streaming: bool,
     pub method_type: String,
     pub sample_request: Option<String>,
+    pub documentation: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -48,6 +57,9 @@ pub struct ImportPath {
 pub struct ProtoParseResult {
     pub success: bool,
     pub services: Vec<Service>,
+    /// Comments on messages and enums (`pkg.Msg`), their fields (`pkg.Msg.field`) and
+    /// enum values (`pkg.Enum.VALUE`). Only documented elements are listed.
+    pub documentation: BTreeMap<String, String>,
     pub errors: Vec<ProtoParseError>,
     pub warnings: Vec<String>,
 }
@@ -74,29 +86,30 @@ pub fn parse_proto_files(import_paths: Vec<ImportPath>) -> (ProtoParseResult, Op
             message: "All import paths are disabled".to_string(),
             suggestion: Some("Enable at least one proto import path".to_string()),
         });
-        return (ProtoParseResult { success: false, services: vec![], errors, warnings }, None);
+        return (ProtoParseResult { success: false, services: vec![], documentation: BTreeMap::new(), errors, warnings }, None);
     }
 
+    let descriptor_sets = read_descriptor_sets(&enabled, &mut errors);
     let proto_paths = discover_proto_files(&enabled, &mut warnings);
 
-    if proto_paths.is_empty() {
+    if proto_paths.is_empty() && descriptor_sets.is_empty() {
         errors.push(ProtoParseError {
             file: "workspace".to_string(),
-            message: "No .proto files found in the configured import paths".to_string(),
+            message: "No .proto or descriptor set files found in the configured import paths".to_string(),
             suggestion: Some("Add directories or files that contain proto definitions".to_string()),
         });
-        return (ProtoParseResult { success: false, services: vec![], errors, warnings }, None);
+        return (ProtoParseResult { success: false, services: vec![], documentation: BTreeMap::new(), errors, warnings }, None);
     }
 
     let proto_files = read_proto_files(&proto_paths, &mut errors);
 
-    if proto_files.is_empty() {
-        return (ProtoParseResult { success: false, services: vec![], errors, warnings }, None);
+    if proto_files.is_empty() && descriptor_sets.is_empty() {
+        return (ProtoParseResult { success: false, services: vec![], documentation: BTreeMap::new(), errors, warnings }, None);
     }
 
     let mut services = extract_services(&proto_files, &mut warnings);
 
-    let pool = match compile_with_protox(&proto_files, &enabled) {
+    let pool = match compile_with_protox(&proto_files, &enabled, &descriptor_sets) {
         Ok(pool) => {
             enrich_with_samples(&mut services, &pool, &mut warnings);
             Some(pool)
@@ -111,10 +124,16 @@ pub fn parse_proto_files(import_paths: Vec<ImportPath>) -> (ProtoParseResult, Op
                 "Sample request generation degraded: {}. Falling back to regex-based stubs.",
                 err
             ));
-            None
+            // Descriptor sets don't depend on the sources, so they remain usable.
+            pool_from_descriptor_sets(&descriptor_sets).ok().filter(|_| !descriptor_sets.is_empty())
         }
     };
 
+    if let Some(pool) = &pool {
+        add_descriptor_set_services(&mut services, pool, &descriptor_sets);
+        enrich_with_documentation(&mut services, pool);
+    }
+
     // Regex-based fallback for any methods still missing a sample
     generate_stub_samples(&mut services, &proto_files, &mut warnings);
 
@@ -127,7 +146,8 @@ pub fn parse_proto_files(import_paths: Vec<ImportPath>) -> (ProtoParseResult, Op
     }
 
     let success = errors.is_empty() || !services.is_empty();
-    (ProtoParseResult { success, services, errors, warnings }, pool)
+    let documentation = pool.as_ref().map(type_documentation).unwrap_or_default();
+    (ProtoParseResult { success, services, documentation, errors, warnings }, pool)
 }
 
 /// Compile a descriptor pool from import paths. Used by gRPC call commands when
@@ -140,20 +160,77 @@ pub fn compile_descriptor_pool(import_paths: &[ImportPath]) -> Result<Descriptor
     }
 
     let mut warnings = Vec::new();
+    let mut errors = Vec::new();
+    let descriptor_sets = read_descriptor_sets(&enabled, &mut errors);
     let proto_paths = discover_proto_files(&enabled, &mut warnings);
 
-    if proto_paths.is_empty() {
-        return Err("No .proto files found in import paths".to_string());
+    if proto_paths.is_empty() && descriptor_sets.is_empty() && errors.is_empty() {
+        return Err("No .proto or descriptor set files found in import paths".to_string());
     }
 
-    let mut errors = Vec::new();
     let proto_files = read_proto_files(&proto_paths, &mut errors);
 
     if !errors.is_empty() {
         return Err(format!("Failed to read proto files: {:?}", errors));
     }
 
-    compile_with_protox(&proto_files, &enabled)
+    compile_with_protox(&proto_files, &enabled, &descriptor_sets)
+}
+
+/// Build a parse result straight from an existing descriptor pool (e.g. one fetched via
+/// server reflection), where there are no proto sources to scan with regexes.
+pub fn parse_descriptor_pool(pool: &DescriptorPool, warnings: Vec<String>) -> ProtoParseResult {
+    let mut services: Vec<Service> = pool
+        .services()
+        .map(|svc| {
+            let source = svc.parent_file().name().to_string();
+            service_from_descriptor(&svc, source)
+        })
+        .collect();
+
+    services.sort_by(|a, b| {
+        a.package_name.cmp(&b.package_name)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    for service in &mut services {
+        service.methods.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    ProtoParseResult {
+        success: !services.is_empty(),
+        services,
+        documentation: type_documentation(pool),
+        errors: vec![],
+        warnings,
+    }
+}
+
+fn service_from_descriptor(svc: &ServiceDescriptor, source_proto: String) -> Service {
+    let file = svc.parent_file_descriptor_proto();
+    let package = svc.parent_file().package_name().to_string();
+    Service {
+        name: svc.full_name().to_string(),
+        package_name: if package.is_empty() { None } else { Some(package) },
+        methods: svc
+            .methods()
+            .map(|m| {
+                let is_client_streaming = m.is_client_streaming();
+                let is_server_streaming = m.is_server_streaming();
+                Method {
+                    name: m.name().to_string(),
+                    input_type: m.input().full_name().to_string(),
+                    output_type: m.output().full_name().to_string(),
+                    is_client_streaming,
+                    is_server_streaming,
+                    method_type: method_type(is_client_streaming, is_server_streaming),
+                    sample_request: serde_json::to_string_pretty(&generate_sample_json(m.input(), 0, None)).ok(),
+                    documentation: comments(file, m.path()),
+                }
+            })
+            .collect(),
+        source_proto: Some(source_proto),
+        documentation: comments(file, svc.path()),
+    }
 }
 
 /// Compile a descriptor pool from raw proto file content (single-file legacy path).
@@ -199,14 +276,146 @@ pub fn compile_single_file(proto_content: &str) -> Result<DescriptorPool, String
         .map_err(|e| format!("Failed to decode descriptor pool: {}", e))
 }
 
+/// A copy of `pool` with the files from `others` that it lacks, so types defined in
+/// either (e.g. `Any` payloads) resolve. Files that define a type the pool already
+/// has are skipped, along with the files importing them. Returns `None` when nothing
+/// was added.
+pub fn merge_types<'a>(
+    pool: &DescriptorPool,
+    others: impl IntoIterator<Item = &'a DescriptorPool>,
+) -> Result<Option<DescriptorPool>, String> {
+    let mut merged = pool.clone();
+    let mut added = false;
+    for other in others {
+        let missing = missing_files(&merged, other);
+        if missing.is_empty() {
+            continue;
+        }
+        // Added as one batch: reflected pools don't list files in dependency order.
+        merged
+            .add_file_descriptor_protos(missing)
+            .map_err(|e| format!("Failed to merge cached schemas: {}", e))?;
+        added = true;
+    }
+    Ok(added.then_some(merged))
+}
+
+/// The files of `other` that `pool` lacks and can take without a name clash.
+fn missing_files(pool: &DescriptorPool, other: &DescriptorPool) -> Vec<FileDescriptorProto> {
+    let mut skipped: HashSet<String> = other
+        .files()
+        .filter(|f| pool.get_file_by_name(f.name()).is_none() && defines_existing_type(pool, f))
+        .map(|f| f.name().to_string())
+        .collect();
+    // Anything importing a skipped file can't be added either.
+    loop {
+        let before = skipped.len();
+        for file in other.files() {
+            if file.dependencies().any(|d| skipped.contains(d.name())) {
+                skipped.insert(file.name().to_string());
+            }
+        }
+        if skipped.len() == before {
+            break;
+        }
+    }
+    other
+        .files()
+        .filter(|f| pool.get_file_by_name(f.name()).is_none() && !skipped.contains(f.name()))
+        .map(|f| f.file_descriptor_proto().clone())
+        .collect()
+}
+
+fn defines_existing_type(pool: &DescriptorPool, file: &prost_reflect::FileDescriptor) -> bool {
+    file.messages().any(|m| pool.get_message_by_name(m.full_name()).is_some())
+        || file.enums().any(|e| pool.get_enum_by_name(e.full_name()).is_some())
+        || file.services().any(|s| pool.get_service_by_name(s.full_name()).is_some())
+        || file.extensions().any(|e| pool.get_extension_by_name(e.full_name()).is_some())
+}
+
 // ---------------------------------------------------------------------------
 // Internal helpers
 // ---------------------------------------------------------------------------
 
+/// A pre-compiled `FileDescriptorSet` added as an import path.
+struct DescriptorSetFile {
+    path: PathBuf,
+    bytes: Vec<u8>,
+    /// Names of the files in the set, used to find the services it defines.
+    file_names: Vec<String>,
+}
+
+/// Load every enabled import path that points at a descriptor set file. Each set
+/// must be self-contained (`protoc --include_imports`) so it decodes on its own.
+fn read_descriptor_sets(
+    import_paths: &[ImportPath],
+    errors: &mut Vec<ProtoParseError>,
+) -> Vec<DescriptorSetFile> {
+    let mut sets = Vec::new();
+    for import in import_paths {
+        let path = Path::new(&import.path);
+        if !path.is_file() || !is_descriptor_set_file(path) {
+            continue;
+        }
+        let decoded = fs::read(path)
+            .map_err(|e| format!("Failed to read descriptor set: {}", e))
+            .and_then(|bytes| match DescriptorPool::decode(bytes.as_slice()) {
+                Ok(pool) => Ok((bytes, pool)),
+                Err(e) => Err(format!("Failed to decode descriptor set: {}", e)),
+            });
+        match decoded {
+            Ok((bytes, pool)) => sets.push(DescriptorSetFile {
+                path: path.to_path_buf(),
+                bytes,
+                file_names: pool.files().map(|f| f.name().to_string()).collect(),
+            }),
+            Err(message) => errors.push(ProtoParseError {
+                file: import.path.clone(),
+                message,
+                suggestion: Some(
+                    "Generate the set with `protoc --include_imports --descriptor_set_out=...`".to_string(),
+                ),
+            }),
+        }
+    }
+    sets
+}
+
+fn pool_from_descriptor_sets(sets: &[DescriptorSetFile]) -> Result<DescriptorPool, String> {
+    let mut pool = DescriptorPool::new();
+    for set in sets {
+        pool.decode_file_descriptor_set(set.bytes.as_slice())
+            .map_err(|e| format!("Failed to merge descriptor set '{}': {}", set.path.display(), e))?;
+    }
+    Ok(pool)
+}
+
+/// Add the services defined in descriptor sets, which have no source for
+/// `extract_services` to scan. Services already found in `.proto` sources win.
+fn add_descriptor_set_services(services: &mut Vec<Service>, pool: &DescriptorPool, sets: &[DescriptorSetFile]) {
+    for set in sets {
+        for file in set.file_names.iter().filter_map(|name| pool.get_file_by_name(name)) {
+            for svc in file.services() {
+                if !services.iter().any(|s| s.name == svc.full_name()) {
+                    services.push(service_from_descriptor(&svc, set.path.to_string_lossy().to_string()));
+                }
+            }
+        }
+    }
+}
+
+/// Compile the `.proto` sources with protox and merge them with any descriptor sets.
+/// Sources may import files that only exist in a descriptor set.
 fn compile_with_protox(
     proto_files: &[(PathBuf, String)],
     import_paths: &[ImportPath],
+    descriptor_sets: &[DescriptorSetFile],
 ) -> Result<DescriptorPool, String> {
+    let mut pool = pool_from_descriptor_sets(descriptor_sets)?;
+    if proto_files.is_empty() && !descriptor_sets.is_empty() {
+        return Ok(pool);
+    }
+
     let mut seen = HashSet::new();
     let mut include_dirs: Vec<PathBuf> = Vec::new();
 
@@ -269,16 +478,36 @@ fn compile_with_protox(
         .collect();
 
     if service_files.is_empty() {
+        if !descriptor_sets.is_empty() {
+            return Ok(pool);
+        }
         return Err("No proto files with service definitions found".to_string());
     }
 
-    let fds = protox::compile(&service_files, &temp_include_dirs)
+    let mut resolver = ChainFileResolver::new();
+    for dir in &temp_include_dirs {
+        resolver.add(IncludeFileResolver::new(dir.clone()));
+    }
+    resolver.add(GoogleFileResolver::new());
+    for set in descriptor_sets {
+        resolver.add(
+            DescriptorSetFileResolver::decode(set.bytes.as_slice())
+                .map_err(|e| format!("Failed to decode descriptor set '{}': {}", set.path.display(), e))?,
+        );
+    }
+
+    let mut compiler = protox::Compiler::with_file_resolver(resolver);
+    compiler
+        .include_source_info(true)
+        .include_imports(true)
+        .open_files(&service_files)
         .map_err(|e| format!("Proto compilation failed: {:?}", e))?;
 
     drop(temp_dirs); // keep alive until protox finishes
-    let bytes = fds.encode_to_vec();
-    DescriptorPool::decode(bytes.as_slice())
-        .map_err(|e| format!("Failed to decode descriptor pool: {}", e))
+    let bytes = compiler.encode_file_descriptor_set();
+    pool.decode_file_descriptor_set(bytes.as_slice())
+        .map_err(|e| format!("Failed to decode descriptor pool: {}", e))?;
+    Ok(pool)
 }
 
 /// Copy every `.proto` file under `src_dir` into `dest_dir`, preserving the
@@ -381,9 +610,9 @@ fn discover_proto_files(import_paths: &[ImportPath], warnings: &mut Vec<String>)
                 if seen.insert(candidate.to_path_buf()) {
                     results.push(candidate.to_path_buf());
                 }
-            } else {
+            } else if !is_descriptor_set_file(candidate) {
                 warnings.push(format!(
-                    "File '{}' is not a .proto file and was skipped",
+                    "File '{}' is not a .proto or descriptor set file and was skipped",
                     import.path
                 ));
             }
@@ -417,6 +646,13 @@ fn is_proto_file(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
+fn is_descriptor_set_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| DESCRIPTOR_SET_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+        .unwrap_or(false)
+}
+
 fn read_proto_files(
     paths: &[PathBuf],
     errors: &mut Vec<ProtoParseError>,
@@ -465,13 +701,7 @@ fn extract_services(
             for rpc_cap in rpc_re.captures_iter(body) {
                 let is_client_streaming = rpc_cap.get(2).is_some();
                 let is_server_streaming = rpc_cap.get(4).is_some();
-                let method_type = match (is_client_streaming, is_server_streaming) {
-                    (false, false) => "unary",
-                    (false, true) => "server_streaming",
-                    (true, false) => "client_streaming",
-                    (true, true) => "bidirectional_streaming",
-                }
-                .to_string();
+                let method_type = method_type(is_client_streaming, is_server_streaming);
 
                 methods.push(Method {
                     name: rpc_cap.get(1).map(|m| m.as_str().to_string()).unwrap_or_default(),
@@ -481,6 +711,7 @@ fn extract_services(
                     is_server_streaming,
                     method_type,
                     sample_request: None,
+                    documentation: None,
                 });
             }
 
@@ -493,10 +724,14 @@ fn extract_services(
             }
 
             services.push(Service {
-                name: service_name,
+                name: match &package_name {
+                    Some(pkg) => format!("{}.{}", pkg, service_name),
+                    None => service_name,
+                },
                 package_name: package_name.clone(),
                 methods,
                 source_proto: Some(path.to_string_lossy().to_string()),
+                documentation: None,
             });
         }
     }
@@ -504,6 +739,16 @@ fn extract_services(
     services
 }
 
+fn method_type(is_client_streaming: bool, is_server_streaming: bool) -> String {
+    match (is_client_streaming, is_server_streaming) {
+        (false, false) => "unary",
+        (false, true) => "server_streaming",
+        (true, false) => "client_streaming",
+        (true, true) => "bidirectional_streaming",
+    }
+    .to_string()
+}
+
 fn extract_package(content: &str) -> Option<String> {
     let re = Regex::new(r"package\s+([A-Za-z0-9_.]+)\s*;").ok()?;
     re.captures(content)
@@ -581,7 +826,7 @@ fn enrich_with_samples(
 
             match descriptor {
                 Some(msg_desc) => {
-                    match serde_json::to_string_pretty(&generate_sample_json(msg_desc, 0)) {
+                    match serde_json::to_string_pretty(&generate_sample_json(msg_desc, 0, None)) {
                         Ok(json) => method.sample_request = Some(json),
                         Err(e) => warnings.push(format!(
                             "Failed to serialize sample for '{}': {}",
@@ -598,6 +843,69 @@ fn enrich_with_samples(
     }
 }
 
+/// Fill in comments for services found by `extract_services`, which only sees the
+/// raw source text.
+fn enrich_with_documentation(services: &mut [Service], pool: &DescriptorPool) {
+    for service in services.iter_mut() {
+        let Some(desc) = pool.get_service_by_name(&service.name) else {
+            continue;
+        };
+        let file = desc.parent_file_descriptor_proto();
+        service.documentation = comments(file, desc.path());
+        for method in service.methods.iter_mut() {
+            if let Some(m) = desc.methods().find(|m| m.name() == method.name) {
+                method.documentation = comments(file, m.path());
+            }
+        }
+    }
+}
+
+/// Comments on every message, enum, field and enum value in the pool, skipping the
+/// bundled well-known types.
+fn type_documentation(pool: &DescriptorPool) -> BTreeMap<String, String> {
+    let mut docs = BTreeMap::new();
+    let mut add = |name: String, file: &FileDescriptorProto, path: &[i32]| {
+        if let Some(doc) = comments(file, path) {
+            docs.insert(name, doc);
+        }
+    };
+    let documented = |name: &str| !name.starts_with("google/protobuf/");
+
+    for msg in pool.all_messages().filter(|m| documented(m.parent_file().name())) {
+        let file = msg.parent_file_descriptor_proto();
+        add(msg.full_name().to_string(), file, msg.path());
+        for field in msg.fields() {
+            add(format!("{}.{}", msg.full_name(), field.name()), file, field.path());
+        }
+    }
+    for en in pool.all_enums().filter(|e| documented(e.parent_file().name())) {
+        let file = en.parent_file_descriptor_proto();
+        add(en.full_name().to_string(), file, en.path());
+        for value in en.values() {
+            add(format!("{}.{}", en.full_name(), value.name()), file, value.path());
+        }
+    }
+    docs
+}
+
+/// Leading and trailing comments for the element at `path`, from the file's
+/// `SourceCodeInfo`. Detached comments (separated by a blank line) are not included.
+pub(crate) fn comments(file: &FileDescriptorProto, path: &[i32]) -> Option<String> {
+    let location = file.source_code_info.as_ref()?.location.iter().find(|l| l.path == path)?;
+    let text: Vec<String> = [&location.leading_comments, &location.trailing_comments]
+        .into_iter()
+        .flatten()
+        .map(|c| c.lines().map(|l| l.strip_prefix(' ').unwrap_or(l)).collect::<Vec<_>>().join("\n"))
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text.join("\n\n"))
+    }
+}
+
 fn find_message_descriptor(
     pool: &DescriptorPool,
     package: Option<&str>,
@@ -641,7 +949,13 @@ fn find_message_descriptor(
     found
 }
 
-fn generate_sample_json(message: MessageDescriptor, depth: usize) -> Value {
+/// Sample request JSON for `message`. `google.protobuf.Any` fields embed a sample of
+/// `any_type` when given, and a placeholder `@type` otherwise.
+pub fn sample_request(message: &MessageDescriptor, any_type: Option<&MessageDescriptor>) -> Value {
+    generate_sample_json(message.clone(), 0, any_type)
+}
+
+fn generate_sample_json(message: MessageDescriptor, depth: usize, any_type: Option<&MessageDescriptor>) -> Value {
     if depth >= MAX_SAMPLE_DEPTH {
         return Value::Object(Map::new());
     }
@@ -658,14 +972,14 @@ fn generate_sample_json(message: MessageDescriptor, depth: usize) -> Value {
         }
         object.insert(
             field.json_name().to_string(),
-            default_value_for_field(&field, depth + 1),
+            default_value_for_field(&field, depth + 1, any_type),
         );
     }
 
     Value::Object(object)
 }
 
-fn default_value_for_field(field: &FieldDescriptor, depth: usize) -> Value {
+fn default_value_for_field(field: &FieldDescriptor, depth: usize, any_type: Option<&MessageDescriptor>) -> Value {
     if field.is_list() {
         return Value::Array(Vec::new());
     }
@@ -696,9 +1010,32 @@ fn default_value_for_field(field: &FieldDescriptor, depth: usize) -> Value {
             .next()
             .map(|v| Value::String(v.name().to_string()))
             .unwrap_or_else(|| Value::Number(serde_json::Number::from(0))),
-        Kind::Message(msg_desc) => well_known_sample(&msg_desc)
-            .unwrap_or_else(|| generate_sample_json(msg_desc, depth)),
+        Kind::Message(msg_desc) => match any_type {
+            Some(packed) if msg_desc.full_name() == "google.protobuf.Any" && depth < MAX_SAMPLE_DEPTH => {
+                any_sample(packed, depth)
+            }
+            _ => well_known_sample(&msg_desc).unwrap_or_else(|| generate_sample_json(msg_desc, depth, any_type)),
+        },
+    }
+}
+
+/// An `Any` holding a sample of `packed`. Well-known types go under `value`, as the
+/// JSON mapping requires; other messages have their fields inline beside `@type`.
+fn any_sample(packed: &MessageDescriptor, depth: usize) -> Value {
+    let type_url = Value::String(format!("type.googleapis.com/{}", packed.full_name()));
+    let mut object = Map::new();
+    object.insert("@type".to_string(), type_url);
+    match well_known_sample(packed) {
+        Some(value) => {
+            object.insert("value".to_string(), value);
+        }
+        None => {
+            if let Value::Object(fields) = generate_sample_json(packed.clone(), depth, Some(packed)) {
+                object.extend(fields);
+            }
+        }
     }
+    Value::Object(object)
 }
 
 /// Return a correctly-shaped JSON value for proto well-known types.
@@ -711,7 +1048,7 @@ fn well_known_sample(msg: &MessageDescriptor) -> Option<Value> {
             "@type": "type.googleapis.com/package.MessageName"
         })),
         "google.protobuf.FieldMask" => Some(Value::String("field1,field2".into())),
-        "google.protobuf.Struct" => Some(Value::Object(Map::new())),
+        "google.protobuf.Struct" | "google.protobuf.Empty" => Some(Value::Object(Map::new())),
         "google.protobuf.Value" => Some(Value::Null),
         "google.protobuf.ListValue" => Some(Value::Array(vec![])),
         "google.protobuf.StringValue" | "google.protobuf.BytesValue" => {
@@ -883,6 +1220,77 @@ mod tests {
         assert!(missing.is_empty(), "Methods missing samples:\n  {}", missing.join("\n  "));
     }
 
+    #[test]
+    fn test_descriptor_set_import_path() {
+        let proto = r#"
+syntax = "proto3";
+package sets.v1;
+
+service Lookup { rpc Find (FindRequest) returns (FindRequest); }
+
+message FindRequest { string query = 1; repeated int32 ids = 2; }
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let mut set_file = tempfile::Builder::new().suffix(".protoset").tempfile().unwrap();
+        std::io::Write::write_all(&mut set_file, &pool.encode_to_vec()).unwrap();
+
+        let import_paths = vec![ImportPath {
+            id: "set-1".to_string(),
+            path: set_file.path().to_string_lossy().to_string(),
+            path_type: "file".to_string(),
+            enabled: true,
+        }];
+        let (result, pool) = parse_proto_files(import_paths);
+
+        assert!(result.success, "errors: {:?}", result.errors.iter().map(|e| &e.message).collect::<Vec<_>>());
+        assert!(result.warnings.is_empty(), "warnings: {:?}", result.warnings);
+        let svc = result.services.iter().find(|s| s.name == "sets.v1.Lookup").expect("service from set");
+        let sample: Value = serde_json::from_str(svc.methods[0].sample_request.as_ref().unwrap()).unwrap();
+        assert!(sample.get("query").is_some());
+        assert!(pool.unwrap().get_message_by_name("sets.v1.FindRequest").is_some());
+    }
+
+    #[test]
+    fn test_comments_become_documentation() {
+        let proto = r#"
+syntax = "proto3";
+package docs.v1;
+
+// Looks things up.
+service Finder {
+  // Finds one thing.
+  //
+  // Returns NOT_FOUND when missing.
+  rpc Find (Query) returns (Query);
+}
+
+// A search query.
+message Query {
+  string text = 1; // Free text.
+  Mode mode = 2;
+}
+
+enum Mode {
+  MODE_UNSPECIFIED = 0;
+  // Match whole words only.
+  MODE_EXACT = 1;
+}
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let result = parse_descriptor_pool(&pool, vec![]);
+
+        let service = &result.services[0];
+        assert_eq!(service.documentation.as_deref(), Some("Looks things up."));
+        assert_eq!(
+            service.methods[0].documentation.as_deref(),
+            Some("Finds one thing.\n\nReturns NOT_FOUND when missing.")
+        );
+        assert_eq!(result.documentation["docs.v1.Query"], "A search query.");
+        assert_eq!(result.documentation["docs.v1.Query.text"], "Free text.");
+        assert_eq!(result.documentation["docs.v1.Mode.MODE_EXACT"], "Match whole words only.");
+        assert!(!result.documentation.contains_key("docs.v1.Query.mode"));
+    }
+
     #[test]
     fn test_oneof_sample_has_single_variant() {
         let proto = r#"
@@ -901,7 +1309,7 @@ message Req {
 "#;
         let pool = compile_single_file(proto).expect("compile should succeed");
         let msg = pool.get_message_by_name("test.Req").expect("Req should exist");
-        let sample = generate_sample_json(msg, 0);
+        let sample = generate_sample_json(msg, 0, None);
         let obj = sample.as_object().expect("should be object");
 
         // "name" must be present; exactly one of text/number should appear (not both)
@@ -916,6 +1324,73 @@ message Req {
         );
     }
 
+    #[test]
+    fn test_any_sample_embeds_chosen_type() {
+        let proto = r#"
+syntax = "proto3";
+package test;
+import "google/protobuf/any.proto";
+import "google/protobuf/timestamp.proto";
+
+service Svc { rpc Do (Req) returns (Req); }
+
+message Req { google.protobuf.Any payload = 1; }
+message Item { string sku = 1; int32 count = 2; }
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let req = pool.get_message_by_name("test.Req").unwrap();
+
+        for (packed, expected) in [
+            ("test.Item", serde_json::json!({"@type": "type.googleapis.com/test.Item", "sku": "", "count": 0})),
+            (
+                "google.protobuf.Timestamp",
+                serde_json::json!({"@type": "type.googleapis.com/google.protobuf.Timestamp", "value": "1970-01-01T00:00:00Z"}),
+            ),
+        ] {
+            let sample = sample_request(&req, pool.get_message_by_name(packed).as_ref());
+            assert_eq!(sample["payload"], expected);
+            // The sample must be a request the JSON mapping accepts.
+            prost_reflect::DynamicMessage::deserialize(req.clone(), sample).expect("sample should deserialize");
+        }
+    }
+
+    #[test]
+    fn test_merge_types_ignores_file_order_and_skips_clashes() {
+        let file = |name: &str, package: &str, message: &str, deps: &[&str]| FileDescriptorProto {
+            name: Some(name.to_string()),
+            package: Some(package.to_string()),
+            dependency: deps.iter().map(|d| d.to_string()).collect(),
+            message_type: vec![prost_types::DescriptorProto {
+                name: Some(message.to_string()),
+                ..Default::default()
+            }],
+            syntax: Some("proto3".to_string()),
+            ..Default::default()
+        };
+        let mut base = DescriptorPool::new();
+        base.add_file_descriptor_protos([file("base.proto", "base", "Known", &[])]).unwrap();
+
+        // Reflection yields files in arbitrary order: importers before their imports,
+        // and a copy of `base.Known` under another file name.
+        let set = prost_types::FileDescriptorSet {
+            file: vec![
+                file("pay/b.proto", "pay", "Receipt", &["pay/a.proto"]),
+                file("dup/known.proto", "base", "Known", &[]),
+                file("dup/user.proto", "dup", "User", &["dup/known.proto"]),
+                file("pay/a.proto", "pay", "Order", &[]),
+            ],
+        };
+        let other = DescriptorPool::decode(set.encode_to_vec().as_slice()).unwrap();
+
+        let merged = merge_types(&base, [&other]).unwrap().expect("files should be added");
+        assert!(merged.get_message_by_name("pay.Receipt").is_some());
+        assert!(merged.get_message_by_name("pay.Order").is_some());
+        assert!(merged.get_file_by_name("dup/known.proto").is_none());
+        assert!(merged.get_message_by_name("dup.User").is_none());
+
+        assert!(merge_types(&merged, [&other]).unwrap().is_none());
+    }
+
     #[test]
     fn test_stub_fallback_generates_samples() {
         let proto_content = r#"
@@ -946,8 +1421,10 @@ message DoResponse { string result = 1; }
                 is_server_streaming: false,
                 method_type: "unary".to_string(),
                 sample_request: None,
+                documentation: None,
             }],
             source_proto: None,
+            documentation: None,
         }];
 
         let mut warnings = Vec::new();
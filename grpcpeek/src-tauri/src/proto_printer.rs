@@ -0,0 +1,725 @@
+use crate::schema::kind_name;
+use prost_reflect::{
+    DynamicMessage, EnumDescriptor, ExtensionDescriptor, FieldDescriptor, FileDescriptor, Kind, MapKey, MessageDescriptor,
+    MethodDescriptor, OneofDescriptor, ServiceDescriptor, Value,
+};
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::source_code_info::Location;
+use prost_types::FieldDescriptorProto;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+/// Largest valid field number, written as `max` in reserved and extension ranges.
+const MAX_FIELD_NUMBER: u32 = 536_870_911;
+
+/// Regenerate `.proto` source for one file of a descriptor pool. The output compiles
+/// back to an equivalent descriptor: type references are fully qualified, custom
+/// options are kept, and comments are carried over when the descriptor has source
+/// info (protox and `protoc --include_source_info`; reflection usually strips it).
+pub fn print_file(file: &FileDescriptor) -> String {
+    let proto = file.file_descriptor_proto();
+    let comments = proto
+        .source_code_info
+        .iter()
+        .flat_map(|info| info.location.iter())
+        .map(|loc| (loc.path.as_slice(), loc))
+        .collect();
+    let mut printer = Printer {
+        comments,
+        syntax: match proto.syntax() {
+            "" => "proto2",
+            other => other,
+        },
+        out: String::new(),
+        indent: 0,
+    };
+    printer.file(file);
+    printer.out
+}
+
+struct Printer<'a> {
+    comments: HashMap<&'a [i32], &'a Location>,
+    syntax: &'a str,
+    out: String,
+    indent: usize,
+}
+
+impl Printer<'_> {
+    fn file(&mut self, file: &FileDescriptor) {
+        let proto = file.file_descriptor_proto();
+
+        self.leading(&[12]);
+        self.line(&format!("syntax = \"{}\";", self.syntax));
+
+        if !proto.package().is_empty() {
+            self.blank();
+            self.decl(&[2], &format!("package {};", proto.package()));
+        }
+
+        if !proto.dependency.is_empty() {
+            self.blank();
+        }
+        for (i, dep) in proto.dependency.iter().enumerate() {
+            let modifier = if proto.public_dependency.contains(&(i as i32)) {
+                "public "
+            } else if proto.weak_dependency.contains(&(i as i32)) {
+                "weak "
+            } else {
+                ""
+            };
+            self.decl(&[3, i as i32], &format!("import {}{};", modifier, quote_str(dep)));
+        }
+
+        let options = option_entries(&file.options());
+        if !options.is_empty() {
+            self.blank();
+            self.option_lines(options);
+        }
+
+        let groups = group_types(file.extensions());
+        for service in file.services() {
+            self.service(&service);
+        }
+        for message in file.messages().filter(|m| !groups.contains(m.full_name())) {
+            self.message(&message);
+        }
+        for enumeration in file.enums() {
+            self.enumeration(&enumeration);
+        }
+        self.extensions(file.extensions());
+    }
+
+    fn service(&mut self, service: &ServiceDescriptor) {
+        self.blank();
+        self.open(service.path(), &format!("service {} {{", service.name()));
+        self.option_lines(option_entries(&service.options()));
+        for method in service.methods() {
+            self.method(&method);
+        }
+        self.close();
+    }
+
+    fn method(&mut self, method: &MethodDescriptor) {
+        let signature = format!(
+            "rpc {}({}.{}) returns ({}.{})",
+            method.name(),
+            if method.is_client_streaming() { "stream " } else { "" },
+            method.input().full_name(),
+            if method.is_server_streaming() { "stream " } else { "" },
+            method.output().full_name(),
+        );
+        let options = option_entries(&method.options());
+        if options.is_empty() {
+            self.decl(method.path(), &format!("{};", signature));
+        } else {
+            self.open(method.path(), &format!("{} {{", signature));
+            self.option_lines(options);
+            self.close();
+        }
+    }
+
+    fn message(&mut self, message: &MessageDescriptor) {
+        self.blank();
+        self.open(message.path(), &format!("message {} {{", message.name()));
+        self.message_body(message);
+        self.close();
+    }
+
+    fn message_body(&mut self, message: &MessageDescriptor) {
+        self.option_lines(option_entries(&message.options()));
+
+        // Nested messages keep their descriptor order. Map entries and groups are
+        // declared by a field, so printing the fields up to that one places them.
+        let fields: Vec<FieldDescriptor> = message.fields().collect();
+        let mut next_field = 0;
+        let mut printed_oneofs = HashSet::new();
+        let extension_groups = group_types(message.child_extensions());
+        for child in message.child_messages() {
+            match fields.iter().position(|f| declares(f, &child)) {
+                Some(index) => {
+                    for field in &fields[next_field.min(index + 1)..=index] {
+                        self.member(field, &mut printed_oneofs);
+                    }
+                    next_field = next_field.max(index + 1);
+                }
+                None if !extension_groups.contains(child.full_name()) => self.message(&child),
+                None => {}
+            }
+        }
+        for field in &fields[next_field..] {
+            self.member(field, &mut printed_oneofs);
+        }
+        for enumeration in message.child_enums() {
+            self.enumeration(&enumeration);
+        }
+        self.extensions(message.child_extensions());
+
+        let extension_ranges: Vec<String> = message.extension_ranges().map(|r| field_range(r.start, r.end)).collect();
+        if !extension_ranges.is_empty() {
+            self.line(&format!("extensions {};", extension_ranges.join(", ")));
+        }
+        let reserved: Vec<String> = message.reserved_ranges().map(|r| field_range(r.start, r.end)).collect();
+        if !reserved.is_empty() {
+            self.line(&format!("reserved {};", reserved.join(", ")));
+        }
+        let names: Vec<String> = message.reserved_names().map(quote_str).collect();
+        if !names.is_empty() {
+            self.line(&format!("reserved {};", names.join(", ")));
+        }
+    }
+
+    /// A field of a message body, or the whole oneof it's the first member of.
+    fn member(&mut self, field: &FieldDescriptor, printed_oneofs: &mut HashSet<String>) {
+        match field.containing_oneof().filter(|o| !o.is_synthetic()) {
+            Some(oneof) => {
+                if printed_oneofs.insert(oneof.name().to_string()) {
+                    self.oneof(&oneof);
+                }
+            }
+            None => self.field(field.field_descriptor_proto(), field.kind(), &field.options(), field.path()),
+        }
+    }
+
+    fn oneof(&mut self, oneof: &OneofDescriptor) {
+        self.open(oneof.path(), &format!("oneof {} {{", oneof.name()));
+        self.option_lines(option_entries(&oneof.options()));
+        for field in oneof.fields() {
+            self.field(field.field_descriptor_proto(), field.kind(), &field.options(), field.path());
+        }
+        self.close();
+    }
+
+    /// A message field or extension. Groups print their message inline.
+    fn field(&mut self, proto: &FieldDescriptorProto, kind: Kind, options: &DynamicMessage, path: &[i32]) {
+        let mut inline = Vec::new();
+        if let Some(default) = &proto.default_value {
+            let value = match kind {
+                Kind::String => quote_str(default),
+                // protoc stores bytes defaults already C-escaped.
+                Kind::Bytes => format!("\"{}\"", default),
+                _ => default.clone(),
+            };
+            inline.push(("default".to_string(), value));
+        }
+        if proto.json_name.as_deref().is_some_and(|j| j != default_json_name(proto.name())) {
+            inline.push(("json_name".to_string(), quote_str(proto.json_name())));
+        }
+        inline.extend(option_entries(options));
+        let inline = if inline.is_empty() {
+            String::new()
+        } else {
+            let entries: Vec<String> = inline.iter().map(|(k, v)| format!("{} = {}", k, v)).collect();
+            format!(" [{}]", entries.join(", "))
+        };
+
+        let map_entry = match &kind {
+            Kind::Message(m) if m.is_map_entry() && proto.label() == Label::Repeated => Some(m.clone()),
+            _ => None,
+        };
+        if let Some(entry) = map_entry {
+            let key = type_name(&entry.map_entry_key_field().kind());
+            let value = type_name(&entry.map_entry_value_field().kind());
+            let line = format!("map<{}, {}> {} = {}{};", key, value, proto.name(), proto.number(), inline);
+            self.decl(path, &line);
+            return;
+        }
+
+        let label = self.label(proto);
+        match kind {
+            Kind::Message(group) if proto.r#type() == Type::Group => {
+                let line = format!("{}group {} = {}{} {{", label, group.name(), proto.number(), inline);
+                self.open(path, &line);
+                self.message_body(&group);
+                self.close();
+            }
+            _ => {
+                let line = format!("{}{} {} = {}{};", label, type_name(&kind), proto.name(), proto.number(), inline);
+                self.decl(path, &line);
+            }
+        }
+    }
+
+    fn label(&self, proto: &FieldDescriptorProto) -> &'static str {
+        match proto.label() {
+            Label::Repeated => "repeated ",
+            Label::Required if self.syntax == "proto2" => "required ",
+            Label::Optional if proto.proto3_optional() => "optional ",
+            // Members of a oneof take no label.
+            Label::Optional if self.syntax == "proto2" && proto.oneof_index.is_none() => "optional ",
+            _ => "",
+        }
+    }
+
+    fn enumeration(&mut self, enumeration: &EnumDescriptor) {
+        self.blank();
+        self.open(enumeration.path(), &format!("enum {} {{", enumeration.name()));
+        self.option_lines(option_entries(&enumeration.options()));
+        // `values()` is ordered by number; aliases keep their declaration order here.
+        let declared = &enumeration.enum_descriptor_proto().value;
+        for value in declared.iter().filter_map(|v| enumeration.get_value_by_name(v.name())) {
+            let options: Vec<String> =
+                option_entries(&value.options()).iter().map(|(k, v)| format!("{} = {}", k, v)).collect();
+            let inline = if options.is_empty() { String::new() } else { format!(" [{}]", options.join(", ")) };
+            self.decl(value.path(), &format!("{} = {}{};", value.name(), value.number(), inline));
+        }
+
+        let reserved: Vec<String> = enumeration
+            .reserved_ranges()
+            .map(|r| match (*r.start(), *r.end()) {
+                (start, end) if start == end => start.to_string(),
+                (start, i32::MAX) => format!("{} to max", start),
+                (start, end) => format!("{} to {}", start, end),
+            })
+            .collect();
+        if !reserved.is_empty() {
+            self.line(&format!("reserved {};", reserved.join(", ")));
+        }
+        let names: Vec<String> = enumeration.reserved_names().map(quote_str).collect();
+        if !names.is_empty() {
+            self.line(&format!("reserved {};", names.join(", ")));
+        }
+        self.close();
+    }
+
+    /// `extend` blocks, one per extended message, in declaration order.
+    fn extensions(&mut self, extensions: impl Iterator<Item = ExtensionDescriptor>) {
+        let mut by_extendee: Vec<(String, Vec<ExtensionDescriptor>)> = Vec::new();
+        for ext in extensions {
+            let extendee = ext.containing_message().full_name().to_string();
+            match by_extendee.iter_mut().find(|(name, _)| *name == extendee) {
+                Some((_, exts)) => exts.push(ext),
+                None => by_extendee.push((extendee, vec![ext])),
+            }
+        }
+        for (extendee, exts) in by_extendee {
+            self.blank();
+            self.line(&format!("extend .{} {{", extendee));
+            self.indent += 1;
+            for ext in exts {
+                self.field(ext.field_descriptor_proto(), ext.kind(), &ext.options(), ext.path());
+            }
+            self.close();
+        }
+    }
+
+    fn option_lines(&mut self, options: Vec<(String, String)>) {
+        for (name, value) in options {
+            self.line(&format!("option {} = {};", name, value));
+        }
+    }
+
+    // -- Output helpers -------------------------------------------------------
+
+    fn line(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.out.push_str(&"  ".repeat(self.indent));
+            self.out.push_str(text);
+        }
+        self.out.push('\n');
+    }
+
+    /// Separate blocks with one empty line, but not right after an opening brace.
+    fn blank(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with("{\n") && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    /// A one-line declaration; a one-line trailing comment stays on the same line.
+    fn decl(&mut self, path: &[i32], text: &str) {
+        self.leading(path);
+        match self.comments.get(path).and_then(|loc| loc.trailing_comments.as_deref()) {
+            Some(comment) if !comment.trim_end().contains('\n') => {
+                self.line(&format!("{} //{}", text, comment.trim_end()));
+            }
+            Some(comment) => {
+                self.line(text);
+                self.comment(comment);
+            }
+            None => self.line(text),
+        }
+    }
+
+    /// Start a block. protoc attaches a block's trailing comment after its `{`.
+    fn open(&mut self, path: &[i32], text: &str) {
+        self.leading(path);
+        self.line(text);
+        self.indent += 1;
+        if let Some(comment) = self.comments.get(path).and_then(|loc| loc.trailing_comments.as_deref()) {
+            self.comment(comment);
+        }
+    }
+
+    fn close(&mut self) {
+        self.indent -= 1;
+        self.line("}");
+    }
+
+    fn leading(&mut self, path: &[i32]) {
+        let Some(loc) = self.comments.get(path).copied() else {
+            return;
+        };
+        for detached in &loc.leading_detached_comments {
+            self.comment(detached);
+            self.line("");
+        }
+        if let Some(comment) = &loc.leading_comments {
+            self.comment(comment);
+        }
+    }
+
+    fn comment(&mut self, text: &str) {
+        for line in text.strip_suffix('\n').unwrap_or(text).split('\n') {
+            self.line(&format!("//{}", line.trim_end()));
+        }
+    }
+}
+
+/// Whether `field` is the map or group field that declares the nested message `child`.
+fn declares(field: &FieldDescriptor, child: &MessageDescriptor) -> bool {
+    match field.kind() {
+        Kind::Message(m) => {
+            m.full_name() == child.full_name()
+                && (child.is_map_entry() || field.field_descriptor_proto().r#type() == Type::Group)
+        }
+        _ => false,
+    }
+}
+
+/// Full names of messages that are the type of a group field, which print inline
+/// with the field rather than as nested messages.
+fn group_types<T: GroupField>(fields: impl Iterator<Item = T>) -> HashSet<String> {
+    fields
+        .filter_map(|f| match f.kind() {
+            Kind::Message(m) if f.proto_type() == Type::Group => Some(m.full_name().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+trait GroupField {
+    fn kind(&self) -> Kind;
+    fn proto_type(&self) -> Type;
+}
+
+impl GroupField for ExtensionDescriptor {
+    fn kind(&self) -> Kind {
+        ExtensionDescriptor::kind(self)
+    }
+    fn proto_type(&self) -> Type {
+        self.field_descriptor_proto().r#type()
+    }
+}
+
+impl GroupField for FieldDescriptor {
+    fn kind(&self) -> Kind {
+        FieldDescriptor::kind(self)
+    }
+    fn proto_type(&self) -> Type {
+        self.field_descriptor_proto().r#type()
+    }
+}
+
+/// `(name, value)` for each option set on a descriptor, custom options included.
+/// Repeated options produce one entry per element.
+fn option_entries(options: &DynamicMessage) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+    for (field, value) in options.fields() {
+        if field.name() != "uninterpreted_option" {
+            push_option(&mut entries, field.name().to_string(), &field.kind(), value);
+        }
+    }
+    for (ext, value) in options.extensions() {
+        push_option(&mut entries, format!("({})", ext.full_name()), &ext.kind(), value);
+    }
+    entries
+}
+
+fn push_option(entries: &mut Vec<(String, String)>, name: String, kind: &Kind, value: &Value) {
+    match value {
+        Value::List(items) => {
+            entries.extend(items.iter().map(|item| (name.clone(), format_value(kind, item))));
+        }
+        Value::Map(map) => {
+            let value_kind = match kind {
+                Kind::Message(entry) => entry.map_entry_value_field().kind(),
+                _ => kind.clone(),
+            };
+            for (key, value) in map {
+                let key = match key {
+                    MapKey::Bool(b) => b.to_string(),
+                    MapKey::I32(n) => n.to_string(),
+                    MapKey::I64(n) => n.to_string(),
+                    MapKey::U32(n) => n.to_string(),
+                    MapKey::U64(n) => n.to_string(),
+                    MapKey::String(s) => quote_str(s),
+                };
+                let value = format_value(&value_kind, value);
+                entries.push((name.clone(), format!("{{ key: {} value: {} }}", key, value)));
+            }
+        }
+        _ => entries.push((name, format_value(kind, value))),
+    }
+}
+
+fn format_value(kind: &Kind, value: &Value) -> String {
+    match value {
+        Value::Bool(b) => b.to_string(),
+        Value::I32(n) => n.to_string(),
+        Value::I64(n) => n.to_string(),
+        Value::U32(n) => n.to_string(),
+        Value::U64(n) => n.to_string(),
+        Value::F32(f) => format_float(f64::from(*f)),
+        Value::F64(f) => format_float(*f),
+        Value::String(s) => quote_str(s),
+        Value::Bytes(b) => quote_bytes(b),
+        Value::EnumNumber(n) => match kind {
+            Kind::Enum(e) => e.get_value(*n).map(|v| v.name().to_string()).unwrap_or_else(|| n.to_string()),
+            _ => n.to_string(),
+        },
+        Value::Message(m) => format!("{{ {} }}", m.to_text_format()),
+        // Only reachable for nested lists/maps, which options can't contain.
+        Value::List(_) | Value::Map(_) => String::new(),
+    }
+}
+
+fn format_float(f: f64) -> String {
+    if f.is_nan() {
+        "nan".to_string()
+    } else {
+        f.to_string()
+    }
+}
+
+fn type_name(kind: &Kind) -> String {
+    match kind {
+        Kind::Message(m) => format!(".{}", m.full_name()),
+        Kind::Enum(e) => format!(".{}", e.full_name()),
+        scalar => kind_name(scalar).to_string(),
+    }
+}
+
+/// `reserved`/`extensions` range from a half-open descriptor range.
+fn field_range(start: u32, end: u32) -> String {
+    match end - 1 {
+        last if last == start => start.to_string(),
+        MAX_FIELD_NUMBER => format!("{} to max", start),
+        last => format!("{} to {}", start, last),
+    }
+}
+
+/// The JSON name protoc derives from a field name; only differing names are printed.
+fn default_json_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn quote_str(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let mut buf = [0; 4];
+                for b in c.encode_utf8(&mut buf).bytes() {
+                    let _ = write!(out, "\\{:03o}", b);
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn quote_bytes(bytes: &[u8]) -> String {
+    let mut out = String::from("\"");
+    for &b in bytes {
+        match b {
+            b'"' => out.push_str("\\\""),
+            b'\\' => out.push_str("\\\\"),
+            0x20..=0x7e => out.push(b as char),
+            _ => {
+                let _ = write!(out, "\\{:03o}", b);
+            }
+        }
+    }
+    out.push('"');
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto_parser::compile_single_file;
+
+    #[test]
+    fn test_printed_file_recompiles_to_same_schema() {
+        let proto = r#"
+// Greeting API.
+syntax = "proto3";
+package demo.v1;
+
+option go_package = "example.com/demo";
+
+// Says hello.
+service Greeter {
+  rpc Hello (HelloRequest) returns (stream HelloReply) { option deprecated = true; }
+}
+
+message HelloRequest {
+  string name = 1; // who to greet
+  optional int32 times = 2;
+  map<string, Mood> moods = 3;
+  oneof target {
+    string email = 4;
+    int64 user_id = 5 [json_name = "uid"];
+  }
+  reserved 8, 10 to max;
+  reserved "legacy";
+}
+
+message HelloReply { repeated string lines = 1 [deprecated = true]; }
+
+enum Mood {
+  MOOD_UNSPECIFIED = 0;
+  MOOD_HAPPY = 1;
+}
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let file = pool.files().find(|f| f.package_name() == "demo.v1").unwrap();
+        let printed = print_file(&file);
+
+        assert!(printed.contains("// Says hello.\nservice Greeter {"), "{}", printed);
+        assert!(printed.contains("string name = 1; // who to greet"), "{}", printed);
+        assert!(printed.contains("map<string, .demo.v1.Mood> moods = 3;"), "{}", printed);
+        assert!(printed.contains("reserved 8, 10 to max;"), "{}", printed);
+
+        let reparsed = compile_single_file(&printed).expect("printed source should compile");
+        let before = pool.get_message_by_name("demo.v1.HelloRequest").unwrap();
+        let after = reparsed.get_message_by_name("demo.v1.HelloRequest").unwrap();
+        let mut before_fields = before.descriptor_proto().field.clone();
+        let mut after_fields = after.descriptor_proto().field.clone();
+        before_fields.sort_by_key(|f| f.number());
+        after_fields.sort_by_key(|f| f.number());
+        assert_eq!(before_fields, after_fields);
+        let method = reparsed.get_service_by_name("demo.v1.Greeter").unwrap().methods().next().unwrap();
+        assert!(method.is_server_streaming());
+        assert_eq!(method.options().get_field_by_name("deprecated").unwrap().as_bool(), Some(true));
+    }
+
+    /// Print the file of `pool` in `package`, compile the output and compare the
+    /// two descriptors, ignoring the (temporary) file name and comment locations.
+    fn assert_round_trips(proto: &str, package: &str) {
+        let descriptor = |pool: &prost_reflect::DescriptorPool| {
+            let file = pool.files().find(|f| f.package_name() == package).unwrap();
+            let mut proto = file.file_descriptor_proto().clone();
+            proto.name = None;
+            proto.source_code_info = None;
+            proto
+        };
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let file = pool.files().find(|f| f.package_name() == package).unwrap();
+        let printed = print_file(&file);
+        let reparsed = compile_single_file(&printed).unwrap_or_else(|e| panic!("{}\n{}", e, printed));
+        assert_eq!(descriptor(&pool), descriptor(&reparsed), "{}", printed);
+    }
+
+    #[test]
+    fn test_proto3_round_trip() {
+        assert_round_trips(
+            r#"
+syntax = "proto3";
+package shop.v1;
+
+option java_multiple_files = true;
+
+service Orders {
+  rpc Get (Order) returns (Order);
+  rpc Watch (stream Order) returns (stream Order.Line) { option idempotency_level = NO_SIDE_EFFECTS; }
+}
+
+message Order {
+  message Line {
+    enum State { option allow_alias = true; STATE_UNSPECIFIED = 0; OPEN = 1; ACTIVE = 1; DONE = 2 [deprecated = true]; }
+    string sku = 1;
+    State state = 2;
+    reserved 3, 5 to 7;
+  }
+  string id = 1;
+  repeated Line lines = 2;
+  map<string, Line> by_sku = 3;
+  map<int64, Line.State> states = 4;
+  optional string note = 5;
+  oneof payment {
+    string card = 6;
+    bytes token = 7 [json_name = "tok"];
+    Line gift = 8;
+  }
+  repeated int32 codes = 9 [packed = false];
+  // Declared after the map fields, so after their entry types.
+  message Receipt { string url = 1; }
+  Receipt receipt = 10;
+  reserved 20 to max;
+  reserved "legacy", "old";
+}
+
+enum Kind {
+  KIND_UNSPECIFIED = 0;
+  KIND_DIGITAL = 1;
+  reserved 2, 10 to 12;
+  reserved "KIND_GONE";
+}
+"#,
+            "shop.v1",
+        );
+    }
+
+    #[test]
+    fn test_proto2_round_trip() {
+        assert_round_trips(
+            r#"
+syntax = "proto2";
+package legacy;
+
+message Account {
+  required int64 id = 1;
+  optional string name = 2 [default = "anon \"x\"\n"];
+  optional int32 limit = 3 [default = -5];
+  optional double rate = 4 [default = inf];
+  optional bool active = 5 [default = true];
+  optional Tier tier = 6 [default = GOLD];
+  optional bytes salt = 7 [default = "\001\377"];
+  repeated int32 scores = 8 [packed = true];
+  optional group Extra = 9 { optional string note = 10; }
+  extensions 100 to 199;
+  reserved 50 to 60;
+}
+
+enum Tier { BRONZE = 1; GOLD = 2; }
+
+extend Account { optional string nickname = 100; }
+"#,
+            "legacy",
+        );
+    }
+}
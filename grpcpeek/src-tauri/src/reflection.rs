@@ -0,0 +1,532 @@
+use crate::compression::{self, Encoding};
+use hyper::body::HttpBody;
+use hyper::client::connect::Connect;
+use hyper::{Body, Client};
+use prost::Message as ProstMessage;
+use prost_reflect::DescriptorPool;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+const REFLECTION_V1: &str = "/grpc.reflection.v1.ServerReflection/ServerReflectionInfo";
+const REFLECTION_V1ALPHA: &str = "/grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo";
+
+/// gRPC status code returned by servers that don't implement a reflection version.
+const UNIMPLEMENTED: &str = "12";
+
+// ---------------------------------------------------------------------------
+// Reflection protocol messages. v1 and v1alpha share the same wire format, so
+// one set of definitions serves both services.
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, PartialEq, ProstMessage)]
+struct ServerReflectionRequest {
+    #[prost(string, tag = "1")]
+    host: String,
+    #[prost(oneof = "MessageRequest", tags = "3, 4, 7")]
+    message_request: Option<MessageRequest>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum MessageRequest {
+    #[prost(string, tag = "3")]
+    FileByFilename(String),
+    #[prost(string, tag = "4")]
+    FileContainingSymbol(String),
+    #[prost(string, tag = "7")]
+    ListServices(String),
+}
+
+#[derive(Clone, PartialEq, ProstMessage)]
+struct ServerReflectionResponse {
+    #[prost(oneof = "MessageResponse", tags = "4, 6, 7")]
+    message_response: Option<MessageResponse>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum MessageResponse {
+    #[prost(message, tag = "4")]
+    FileDescriptors(FileDescriptorResponse),
+    #[prost(message, tag = "6")]
+    Services(ListServiceResponse),
+    #[prost(message, tag = "7")]
+    Error(ErrorResponse),
+}
+
+#[derive(Clone, PartialEq, ProstMessage)]
+struct FileDescriptorResponse {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    file_descriptor_proto: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, ProstMessage)]
+struct ListServiceResponse {
+    #[prost(message, repeated, tag = "1")]
+    service: Vec<ServiceResponse>,
+}
+
+#[derive(Clone, PartialEq, ProstMessage)]
+struct ServiceResponse {
+    #[prost(string, tag = "1")]
+    name: String,
+}
+
+#[derive(Clone, PartialEq, ProstMessage)]
+struct ErrorResponse {
+    #[prost(int32, tag = "1")]
+    error_code: i32,
+    #[prost(string, tag = "2")]
+    error_message: String,
+}
+
+/// Wire-compatible with `google.protobuf.FileDescriptorSet`, but keeps each file as
+/// the raw bytes the server sent so custom options survive `DescriptorPool::decode`.
+#[derive(Clone, PartialEq, ProstMessage)]
+struct RawFileDescriptorSet {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    file: Vec<Vec<u8>>,
+}
+
+// ---------------------------------------------------------------------------
+// Client
+// ---------------------------------------------------------------------------
+
+/// Fetch every service the server exposes through reflection and build a
+/// descriptor pool from the returned file descriptors and their dependencies.
+///
+/// `new_request` must return a request builder (URI, content-type, auth and
+/// metadata already applied) for the given gRPC path. Each reflection query is
+/// sent on its own HTTP/2 stream so the server can answer it and close.
+///
+/// Returns the pool together with any non-fatal warnings.
+pub async fn fetch_descriptor_pool<C, F>(
+    client: &Client<C, Body>,
+    new_request: F,
+) -> Result<(DescriptorPool, Vec<String>), String>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+    F: Fn(&str) -> Result<hyper::http::request::Builder, String>,
+{
+    let mut warnings = Vec::new();
+
+    // Prefer v1 and fall back to v1alpha for servers that predate it.
+    let mut path = REFLECTION_V1;
+    let services = match query(client, &new_request, path, MessageRequest::ListServices(String::new())).await {
+        Ok(resp) => resp,
+        Err(QueryError::Unimplemented) => {
+            path = REFLECTION_V1ALPHA;
+            query(client, &new_request, path, MessageRequest::ListServices(String::new()))
+                .await
+                .map_err(|e| match e {
+                    QueryError::Unimplemented => {
+                        "Server does not support gRPC reflection (grpc.reflection.v1 or v1alpha)".to_string()
+                    }
+                    QueryError::Failed(msg) => msg,
+                })?
+        }
+        Err(QueryError::Failed(msg)) => return Err(msg),
+    };
+
+    let service_names: Vec<String> = match services {
+        MessageResponse::Services(list) => list
+            .service
+            .into_iter()
+            .map(|s| s.name)
+            .filter(|name| !name.starts_with("grpc.reflection."))
+            .collect(),
+        other => return Err(unexpected_response("ListServices", &other)),
+    };
+
+    if service_names.is_empty() {
+        return Err("Server reflection returned no services".to_string());
+    }
+
+    // file name -> raw FileDescriptorProto bytes
+    let mut files: HashMap<String, Vec<u8>> = HashMap::new();
+    let mut pending: VecDeque<String> = VecDeque::new();
+    let mut requested: HashSet<String> = HashSet::new();
+
+    for service in &service_names {
+        match query(client, &new_request, path, MessageRequest::FileContainingSymbol(service.clone())).await {
+            Ok(MessageResponse::FileDescriptors(resp)) => {
+                collect_files(resp, &mut files, &mut pending, &mut warnings);
+            }
+            Ok(other) => warnings.push(format!(
+                "Skipping service '{}': {}",
+                service,
+                unexpected_response("FileContainingSymbol", &other)
+            )),
+            Err(e) => warnings.push(format!("Skipping service '{}': {}", service, e)),
+        }
+    }
+
+    // Resolve transitive imports the server didn't include up front.
+    while let Some(name) = pending.pop_front() {
+        if files.contains_key(&name) || !requested.insert(name.clone()) {
+            continue;
+        }
+        match query(client, &new_request, path, MessageRequest::FileByFilename(name.clone())).await {
+            Ok(MessageResponse::FileDescriptors(resp)) => {
+                collect_files(resp, &mut files, &mut pending, &mut warnings);
+            }
+            Ok(other) => warnings.push(format!(
+                "Could not fetch '{}': {}",
+                name,
+                unexpected_response("FileByFilename", &other)
+            )),
+            Err(e) => warnings.push(format!("Could not fetch '{}': {}", name, e)),
+        }
+    }
+
+    if files.is_empty() {
+        return Err("Server reflection returned no file descriptors".to_string());
+    }
+
+    let set = RawFileDescriptorSet { file: files.into_values().collect() };
+    let pool = DescriptorPool::decode(set.encode_to_vec().as_slice())
+        .map_err(|e| format!("Failed to build descriptor pool from reflection: {}", e))?;
+
+    Ok((pool, warnings))
+}
+
+enum QueryError {
+    Unimplemented,
+    Failed(String),
+}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::Unimplemented => write!(f, "reflection is not implemented by the server"),
+            QueryError::Failed(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// Send a single reflection request and return the server's one response.
+async fn query<C, F>(
+    client: &Client<C, Body>,
+    new_request: &F,
+    path: &str,
+    request: MessageRequest,
+) -> Result<MessageResponse, QueryError>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+    F: Fn(&str) -> Result<hyper::http::request::Builder, String>,
+{
+    let msg = ServerReflectionRequest { host: String::new(), message_request: Some(request) };
+    let req = new_request(path)
+        .map_err(QueryError::Failed)?
+        .body(Body::from(crate::engine::grpc_frame(&msg.encode_to_vec())))
+        .map_err(|e| QueryError::Failed(format!("Failed to build request: {}", e)))?;
+
+    let response = client
+        .request(req)
+        .await
+        .map_err(|e| QueryError::Failed(e.to_string()))?;
+
+    let (parts, mut body) = response.into_parts();
+    let encoding = Encoding::from_headers(&parts.headers).map_err(QueryError::Failed)?;
+    let mut buf = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| QueryError::Failed(format!("Failed to read reflection response: {}", e)))?;
+        buf.extend_from_slice(&chunk);
+    }
+    let trailers = body
+        .trailers()
+        .await
+        .map_err(|e| QueryError::Failed(format!("Failed to read trailers: {}", e)))?;
+
+    // Servers without the service answer UNIMPLEMENTED, either trailers-only or, as
+    // some proxies do, in trailers after the headers.
+    let (status, message) = crate::engine::grpc_status(&parts.headers, trailers.as_ref());
+    if status == UNIMPLEMENTED {
+        return Err(QueryError::Unimplemented);
+    }
+
+    if buf.len() < 5 {
+        return Err(match status.as_str() {
+            "0" | "unknown" => QueryError::Failed("Empty reflection response".to_string()),
+            code if message.is_empty() => {
+                QueryError::Failed(format!("Reflection request failed with gRPC status {}", code))
+            }
+            code => QueryError::Failed(format!("Reflection request failed with gRPC status {}: {}", code, message)),
+        });
+    }
+    let (compressed, data) = crate::engine::next_frame(&mut bytes::BytesMut::from(&buf[..]))
+        .ok_or_else(|| QueryError::Failed("Incomplete reflection response".to_string()))?;
+    let msg_bytes = compression::decode_frame(compressed, data, encoding).map_err(QueryError::Failed)?;
+
+    let decoded = ServerReflectionResponse::decode(msg_bytes)
+        .map_err(|e| QueryError::Failed(format!("Failed to decode reflection response: {}", e)))?;
+
+    match decoded.message_response {
+        Some(MessageResponse::Error(err)) => Err(QueryError::Failed(format!(
+            "Reflection error {}: {}",
+            err.error_code, err.error_message
+        ))),
+        Some(resp) => Ok(resp),
+        None => Err(QueryError::Failed("Reflection response was empty".to_string())),
+    }
+}
+
+fn collect_files(
+    resp: FileDescriptorResponse,
+    files: &mut HashMap<String, Vec<u8>>,
+    pending: &mut VecDeque<String>,
+    warnings: &mut Vec<String>,
+) {
+    for raw in resp.file_descriptor_proto {
+        match prost_types::FileDescriptorProto::decode(raw.as_slice()) {
+            Ok(fd) => {
+                let name = fd.name.clone().unwrap_or_default();
+                pending.extend(fd.dependency.iter().filter(|d| !files.contains_key(*d)).cloned());
+                files.entry(name).or_insert(raw);
+            }
+            Err(e) => warnings.push(format!("Skipping undecodable file descriptor: {}", e)),
+        }
+    }
+}
+
+fn unexpected_response(request: &str, resp: &MessageResponse) -> String {
+    let kind = match resp {
+        MessageResponse::FileDescriptors(_) => "file_descriptor_response",
+        MessageResponse::Services(_) => "list_services_response",
+        MessageResponse::Error(_) => "error_response",
+    };
+    format!("unexpected {} for {} request", kind, request)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server;
+    use hyper::{Request, Response};
+    use prost_types::{DescriptorProto, FileDescriptorProto, MethodDescriptorProto, ServiceDescriptorProto};
+    use std::net::SocketAddr;
+    use std::sync::{Arc, Mutex};
+
+    fn dep_file() -> FileDescriptorProto {
+        FileDescriptorProto {
+            name: Some("shop/dep.proto".to_string()),
+            package: Some("shop".to_string()),
+            message_type: vec![DescriptorProto { name: Some("Item".to_string()), ..Default::default() }],
+            syntax: Some("proto3".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn service_file() -> FileDescriptorProto {
+        FileDescriptorProto {
+            name: Some("shop/cart.proto".to_string()),
+            package: Some("shop".to_string()),
+            dependency: vec!["shop/dep.proto".to_string()],
+            service: vec![ServiceDescriptorProto {
+                name: Some("Cart".to_string()),
+                method: vec![MethodDescriptorProto {
+                    name: Some("Add".to_string()),
+                    input_type: Some(".shop.Item".to_string()),
+                    output_type: Some(".shop.Item".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            syntax: Some("proto3".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn files(files: &[FileDescriptorProto]) -> MessageResponse {
+        MessageResponse::FileDescriptors(FileDescriptorResponse {
+            file_descriptor_proto: files.iter().map(|f| f.encode_to_vec()).collect(),
+        })
+    }
+
+    fn error(code: i32, message: &str) -> MessageResponse {
+        MessageResponse::Error(ErrorResponse { error_code: code, error_message: message.to_string() })
+    }
+
+    /// A trailers-only UNIMPLEMENTED, as most servers send it.
+    fn unimplemented() -> Response<Body> {
+        Response::builder()
+            .header("content-type", "application/grpc")
+            .header("grpc-status", UNIMPLEMENTED)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    /// (path, request) pairs a test server received.
+    type RequestLog = Arc<Mutex<Vec<(String, MessageRequest)>>>;
+
+    /// A reflection server answering on the paths `answer` accepts. Returns its
+    /// address and the requests it receives.
+    fn serve(
+        answer: impl Fn(&str, MessageRequest) -> Option<MessageResponse> + Send + Sync + 'static,
+    ) -> (SocketAddr, RequestLog) {
+        serve_with(unimplemented, answer)
+    }
+
+    /// `serve`, answering other paths with `refuse`.
+    fn serve_with(
+        refuse: fn() -> Response<Body>,
+        answer: impl Fn(&str, MessageRequest) -> Option<MessageResponse> + Send + Sync + 'static,
+    ) -> (SocketAddr, RequestLog) {
+        let answer = Arc::new(answer);
+        let log = RequestLog::default();
+        let log_c = Arc::clone(&log);
+        let addr = test_server::serve(move |req: Request<Body>| {
+            let answer = Arc::clone(&answer);
+            let log = Arc::clone(&log_c);
+            async move {
+                let path = req.uri().path().to_string();
+                let frames = test_server::read_messages(req).await;
+                let request = ServerReflectionRequest::decode(frames[0].as_slice())
+                    .unwrap()
+                    .message_request
+                    .unwrap();
+                log.lock().unwrap().push((path.clone(), request.clone()));
+                match answer(&path, request) {
+                    Some(resp) => {
+                        let msg = ServerReflectionResponse { message_response: Some(resp) };
+                        test_server::grpc_response(vec![msg.encode_to_vec()], 0, "")
+                    }
+                    None => refuse(),
+                }
+            }
+        });
+        (addr, log)
+    }
+
+    /// A well-behaved shop server, on the v1 service or only on v1alpha.
+    fn shop(path: &str, request: MessageRequest, v1: bool) -> Option<MessageResponse> {
+        if (path == REFLECTION_V1) != v1 {
+            return None;
+        }
+        Some(match request {
+            MessageRequest::ListServices(_) => MessageResponse::Services(ListServiceResponse {
+                service: ["shop.Cart", "grpc.reflection.v1.ServerReflection"]
+                    .iter()
+                    .map(|name| ServiceResponse { name: name.to_string() })
+                    .collect(),
+            }),
+            // Only the service's own file, so its import has to be fetched by name.
+            MessageRequest::FileContainingSymbol(symbol) if symbol == "shop.Cart" => files(&[service_file()]),
+            MessageRequest::FileByFilename(name) if name == "shop/dep.proto" => files(&[dep_file()]),
+            other => error(5, &format!("not found: {:?}", other)),
+        })
+    }
+
+    async fn fetch(addr: SocketAddr) -> Result<(DescriptorPool, Vec<String>), String> {
+        let client = Client::builder().http2_only(true).build_http();
+        fetch_descriptor_pool(&client, |path| {
+            Ok(hyper::http::Request::builder()
+                .method("POST")
+                .uri(format!("http://{}{}", addr, path))
+                .header("content-type", "application/grpc")
+                .header("te", "trailers"))
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_walks_services_and_fetches_dependencies() {
+        let (addr, log) = serve(|path, request| shop(path, request, true));
+        let (pool, warnings) = fetch(addr).await.unwrap();
+
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        let service = pool.get_service_by_name("shop.Cart").unwrap();
+        assert_eq!(service.methods().next().unwrap().input().full_name(), "shop.Item");
+        // The reflection service itself isn't walked.
+        assert!(pool.get_service_by_name("grpc.reflection.v1.ServerReflection").is_none());
+
+        let requests: Vec<MessageRequest> = log.lock().unwrap().iter().map(|(_, r)| r.clone()).collect();
+        assert_eq!(
+            requests,
+            [
+                MessageRequest::ListServices(String::new()),
+                MessageRequest::FileContainingSymbol("shop.Cart".to_string()),
+                MessageRequest::FileByFilename("shop/dep.proto".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_v1alpha() {
+        let (addr, log) = serve(|path, request| shop(path, request, false));
+        let (pool, _) = fetch(addr).await.unwrap();
+
+        assert!(pool.get_service_by_name("shop.Cart").is_some());
+        let paths: Vec<String> = log.lock().unwrap().iter().map(|(p, _)| p.clone()).collect();
+        assert_eq!(paths[0], REFLECTION_V1);
+        assert!(paths[1..].iter().all(|p| p == REFLECTION_V1ALPHA), "{:?}", paths);
+    }
+
+    #[tokio::test]
+    async fn test_status_in_trailers() {
+        // UNIMPLEMENTED after the headers still falls back to v1alpha.
+        let (addr, _) = serve_with(
+            || test_server::grpc_response(Vec::new(), 12, "unknown service"),
+            |path, request| shop(path, request, false),
+        );
+        assert!(fetch(addr).await.unwrap().0.get_service_by_name("shop.Cart").is_some());
+
+        // Any other status in the trailers is reported as it is.
+        let (addr, _) = serve_with(|| test_server::grpc_response(Vec::new(), 7, "not allowed"), |_, _| None);
+        assert_eq!(
+            fetch(addr).await.unwrap_err(),
+            "Reflection request failed with gRPC status 7: not allowed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_server_without_reflection() {
+        let (addr, _) = serve(|_, _| None);
+        assert_eq!(
+            fetch(addr).await.unwrap_err(),
+            "Server does not support gRPC reflection (grpc.reflection.v1 or v1alpha)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_responses() {
+        // A failing ListServices fails the whole fetch.
+        let (addr, _) = serve(|_, _| Some(error(7, "reflection is disabled")));
+        assert_eq!(fetch(addr).await.unwrap_err(), "Reflection error 7: reflection is disabled");
+
+        // A service the server can't describe is skipped with a warning.
+        let (addr, _) = serve(|path, request| match request {
+            MessageRequest::ListServices(_) => Some(MessageResponse::Services(ListServiceResponse {
+                service: vec![
+                    ServiceResponse { name: "shop.Cart".to_string() },
+                    ServiceResponse { name: "shop.Hidden".to_string() },
+                ],
+            })),
+            other => shop(path, other, true),
+        });
+        let (pool, warnings) = fetch(addr).await.unwrap();
+        assert!(pool.get_service_by_name("shop.Cart").is_some());
+        assert_eq!(
+            warnings,
+            ["Skipping service 'shop.Hidden': Reflection error 5: not found: FileContainingSymbol(\"shop.Hidden\")"]
+        );
+
+        // An import that can't be fetched leaves the pool unbuildable.
+        let (addr, _) = serve(|path, request| match request {
+            MessageRequest::FileByFilename(_) => Some(error(5, "file not found")),
+            other => shop(path, other, true),
+        });
+        let err = fetch(addr).await.unwrap_err();
+        assert!(err.starts_with("Failed to build descriptor pool from reflection"), "{}", err);
+
+        // Nothing describable at all is an error rather than an empty pool.
+        let (addr, _) = serve(|_, request| match request {
+            MessageRequest::ListServices(_) => Some(MessageResponse::Services(ListServiceResponse {
+                service: vec![ServiceResponse { name: "shop.Hidden".to_string() }],
+            })),
+            _ => Some(error(5, "not found")),
+        });
+        assert_eq!(fetch(addr).await.unwrap_err(), "Server reflection returned no file descriptors");
+    }
+}
@@ -0,0 +1,855 @@
+use crate::assertions::{self, Assertion, AssertionResult};
+use crate::connector::Connector;
+use crate::engine::{self, AuthConfig, Call, CallOptions, TlsConfig};
+use crate::grpc_web::Transport;
+use crate::message_format::MessageFormat;
+use crate::proto_parser::{self, ImportPath};
+use hyper::{Body, Client};
+use lazy_static::lazy_static;
+use prost_reflect::DescriptorPool;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+/// Endpoint used when no environment is selected, as in the app.
+const DEFAULT_ENDPOINT: &str = "localhost:50051";
+
+lazy_static! {
+    static ref VARIABLE_PATTERN: Regex = Regex::new(r"\{\{(env|global)\.([a-zA-Z0-9_]+)\}\}").unwrap();
+}
+
+// ---------------------------------------------------------------------------
+// Workspace export
+// ---------------------------------------------------------------------------
+
+// The subset of the frontend's `Workspace` (types/workspace.ts) a run needs. Other
+// fields in an exported workspace are ignored.
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    pub name: String,
+    #[serde(default)]
+    pub import_paths: Vec<ImportPath>,
+    #[serde(default)]
+    pub globals: Vec<Variable>,
+    #[serde(default)]
+    pub environments: Vec<Environment>,
+    #[serde(default)]
+    pub collections: Vec<Collection>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Variable {
+    pub key: String,
+    pub value: String,
+    pub enabled: bool,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Environment {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub variables: Vec<Variable>,
+    pub auth: Option<AuthConfig>,
+    #[serde(default, deserialize_with = "metadata_entries")]
+    pub metadata: Vec<(String, String)>,
+    pub tls: Option<TlsConfig>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub folders: Vec<Folder>,
+    #[serde(default)]
+    pub requests: Vec<SavedRequest>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Folder {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub folders: Vec<Folder>,
+    #[serde(default)]
+    pub requests: Vec<SavedRequest>,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedRequest {
+    pub id: String,
+    pub name: String,
+    pub service: String,
+    pub method: String,
+    /// JSON body. For client and bidi streams, an array of messages or a single one.
+    #[serde(default)]
+    pub request_body: String,
+    /// Headers in send order. Repeated names are sent as repeated headers.
+    #[serde(default, deserialize_with = "metadata_entries")]
+    pub metadata: Vec<(String, String)>,
+    pub auth: Option<AuthConfig>,
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    /// Message types to decode `-bin` response metadata as, keyed by header name.
+    pub bin_metadata_types: Option<HashMap<String, String>>,
+}
+
+/// Metadata as `[[name, value], ...]`, which keeps order and repeated names, or as
+/// the `{name: value}` object the app exports.
+fn metadata_entries<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<(String, String)>, D::Error> {
+    struct Entries;
+
+    impl<'de> serde::de::Visitor<'de> for Entries {
+        type Value = Vec<(String, String)>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("an object or an array of [name, value] pairs")
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut entries = Vec::new();
+            while let Some(entry) = map.next_entry()? {
+                entries.push(entry);
+            }
+            Ok(entries)
+        }
+
+        fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut entries = Vec::new();
+            while let Some(entry) = seq.next_element()? {
+                entries.push(entry);
+            }
+            Ok(entries)
+        }
+    }
+
+    deserializer.deserialize_any(Entries)
+}
+
+/// Read an exported workspace. The file may hold one workspace or an array of them,
+/// in which case `name` (a workspace name or id) picks one.
+pub fn load_workspace(json: &str, name: Option<&str>) -> Result<Workspace, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| format!("Invalid workspace JSON: {}", e))?;
+    let value = match value {
+        Value::Array(items) => {
+            let names: Vec<&str> = items.iter().filter_map(|w| w["name"].as_str()).collect();
+            let found = match name {
+                Some(name) => items.iter().find(|w| w["name"] == name || w["id"] == name),
+                None if items.len() == 1 => items.first(),
+                None => {
+                    return Err(format!(
+                        "The file holds {} workspaces; choose one of: {}",
+                        items.len(),
+                        names.join(", ")
+                    ))
+                }
+            };
+            found
+                .cloned()
+                .ok_or_else(|| format!("Workspace '{}' not found", name.unwrap_or_default()))?
+        }
+        value => value,
+    };
+    serde_json::from_value(value).map_err(|e| format!("Invalid workspace: {}", e))
+}
+
+// ---------------------------------------------------------------------------
+// Variables
+// ---------------------------------------------------------------------------
+
+/// `{{env.key}}` and `{{global.key}}` lookups, as the app resolves them.
+struct Variables<'a> {
+    env: &'a [Variable],
+    globals: &'a [Variable],
+}
+
+impl Variables<'_> {
+    /// Substitute variables in a request body or metadata value. JSON is substituted
+    /// value by value, so quotes in a variable can't break its structure. Unknown
+    /// variables are left in place and reported.
+    fn resolve(&self, text: &str, unresolved: &mut Vec<String>) -> String {
+        match serde_json::from_str::<Value>(text) {
+            Ok(json @ (Value::Object(_) | Value::Array(_))) => {
+                serde_json::to_string_pretty(&self.resolve_json(json, unresolved)).unwrap_or_default()
+            }
+            _ => self.resolve_str(text, unresolved),
+        }
+    }
+
+    fn resolve_json(&self, value: Value, unresolved: &mut Vec<String>) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.resolve_str(&s, unresolved)),
+            Value::Array(items) => Value::Array(items.into_iter().map(|v| self.resolve_json(v, unresolved)).collect()),
+            Value::Object(obj) => Value::Object(
+                obj.into_iter()
+                    .map(|(k, v)| (k, self.resolve_json(v, unresolved)))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    fn resolve_str(&self, text: &str, unresolved: &mut Vec<String>) -> String {
+        VARIABLE_PATTERN
+            .replace_all(text, |caps: &regex::Captures| {
+                let vars = if &caps[1] == "env" { self.env } else { self.globals };
+                match vars.iter().find(|v| v.enabled && v.key == caps[2]) {
+                    Some(var) => var.value.clone(),
+                    None => {
+                        if !unresolved.iter().any(|u| u == &caps[0]) {
+                            unresolved.push(caps[0].to_string());
+                        }
+                        caps[0].to_string()
+                    }
+                }
+            })
+            .into_owned()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Running
+// ---------------------------------------------------------------------------
+
+#[derive(Default)]
+pub struct RunOptions {
+    /// Environment name or id. Without one, requests go to localhost:50051.
+    pub environment: Option<String>,
+    /// Only run the collection with this name or id.
+    pub collection: Option<String>,
+    /// Use this endpoint instead of the environment's host and port.
+    pub endpoint: Option<String>,
+    pub timeout_ms: Option<u64>,
+    /// Stop at the first failing request.
+    pub bail: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReport {
+    pub workspace: String,
+    pub environment: Option<String>,
+    pub endpoint: String,
+    pub started_at: String,
+    pub duration_ms: f64,
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub results: Vec<RequestReport>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestReport {
+    pub collection: String,
+    /// Names of the folders the request sits in, outermost first.
+    pub folders: Vec<String>,
+    pub name: String,
+    pub service: String,
+    pub method: String,
+    pub passed: bool,
+    pub grpc_status: Option<String>,
+    pub grpc_message: Option<String>,
+    pub duration_ms: f64,
+    /// Why the request couldn't be made; assertions aren't evaluated then.
+    pub error: Option<String>,
+    pub assertions: Vec<AssertionResult>,
+    pub response: Value,
+}
+
+/// A saved request with the place it was found.
+struct Entry<'a> {
+    collection: &'a str,
+    folders: Vec<String>,
+    request: &'a SavedRequest,
+}
+
+/// Requests in the order the collections tree shows them: a folder's subfolders
+/// come before its own requests.
+fn collect_entries<'a>(
+    collection: &'a str,
+    folders: &'a [Folder],
+    requests: &'a [SavedRequest],
+    path: &[String],
+    out: &mut Vec<Entry<'a>>,
+) {
+    for folder in folders {
+        let mut nested = path.to_vec();
+        nested.push(folder.name.clone());
+        collect_entries(collection, &folder.folders, &folder.requests, &nested, out);
+    }
+    out.extend(requests.iter().map(|request| Entry {
+        collection,
+        folders: path.to_vec(),
+        request,
+    }));
+}
+
+/// Run a workspace's saved requests in order against one environment. Requests
+/// that don't assert a status are expected to return OK.
+pub async fn run(workspace: &Workspace, options: &RunOptions) -> Result<RunReport, String> {
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let start = Instant::now();
+
+    let env = match &options.environment {
+        Some(name) => Some(
+            workspace
+                .environments
+                .iter()
+                .find(|e| &e.name == name || &e.id == name)
+                .ok_or_else(|| format!("Environment '{}' not found", name))?,
+        ),
+        None => None,
+    };
+    let collections: Vec<&Collection> = workspace
+        .collections
+        .iter()
+        .filter(|c| options.collection.as_ref().is_none_or(|name| &c.name == name || &c.id == name))
+        .collect();
+    if let Some(name) = options.collection.as_ref().filter(|_| collections.is_empty()) {
+        return Err(format!("Collection '{}' not found", name));
+    }
+
+    let endpoint = endpoint(options, env);
+    let tls = env.and_then(|e| e.tls.clone()).filter(|t| t.enabled);
+    let client = engine::build_client(&endpoint, tls.as_ref(), engine::http2_preference(Transport::Grpc, None))?;
+    let variables = Variables {
+        env: env.map(|e| e.variables.as_slice()).unwrap_or_default(),
+        globals: &workspace.globals,
+    };
+
+    let mut entries = Vec::new();
+    for collection in &collections {
+        collect_entries(&collection.name, &collection.folders, &collection.requests, &[], &mut entries);
+    }
+
+    let mut run = Run {
+        workspace,
+        env,
+        endpoint: &endpoint,
+        client: &client,
+        variables,
+        options,
+        pool: None,
+    };
+    let mut results = Vec::new();
+    for entry in entries {
+        let report = run.request(&entry).await;
+        let stop = options.bail && !report.passed;
+        results.push(report);
+        if stop {
+            break;
+        }
+    }
+
+    let passed = results.iter().filter(|r| r.passed).count();
+    Ok(RunReport {
+        workspace: workspace.name.clone(),
+        environment: env.map(|e| e.name.clone()),
+        endpoint,
+        started_at,
+        duration_ms: start.elapsed().as_secs_f64() * 1000.0,
+        total: results.len(),
+        passed,
+        failed: results.len() - passed,
+        results,
+    })
+}
+
+/// Where a run's requests go: the override, else the environment's host and port,
+/// else the app's default. Hosts may be written with a scheme, as in the app.
+fn endpoint(options: &RunOptions, env: Option<&Environment>) -> String {
+    match (&options.endpoint, env) {
+        (Some(endpoint), _) => engine::clean_endpoint(endpoint),
+        (None, Some(env)) => engine::clean_endpoint(&format!("{}:{}", env.host, env.port)),
+        (None, None) => DEFAULT_ENDPOINT.to_string(),
+    }
+}
+
+/// State shared by the requests of a run.
+struct Run<'a> {
+    workspace: &'a Workspace,
+    env: Option<&'a Environment>,
+    endpoint: &'a str,
+    client: &'a Client<Connector, Body>,
+    variables: Variables<'a>,
+    options: &'a RunOptions,
+    /// Loaded on the first request, with that request's metadata for reflection.
+    pool: Option<Arc<DescriptorPool>>,
+}
+
+impl Run<'_> {
+    async fn request(&mut self, entry: &Entry<'_>) -> RequestReport {
+        let request = entry.request;
+        let mut report = RequestReport {
+            collection: entry.collection.to_string(),
+            folders: entry.folders.clone(),
+            name: request.name.clone(),
+            service: request.service.clone(),
+            method: request.method.clone(),
+            passed: false,
+            grpc_status: None,
+            grpc_message: None,
+            duration_ms: 0.0,
+            error: None,
+            assertions: Vec::new(),
+            response: Value::Null,
+        };
+
+        // Request metadata replaces environment entries of the same name; request auth
+        // overrides the environment's unless it is "none".
+        let mut unresolved = Vec::new();
+        let body = self.variables.resolve(&request.request_body, &mut unresolved);
+        let overrides = |name: &str| request.metadata.iter().any(|(k, _)| k.eq_ignore_ascii_case(name));
+        let metadata: Vec<(String, String)> = self
+            .env
+            .map(|e| e.metadata.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(k, _)| !overrides(k))
+            .chain(request.metadata.clone())
+            .map(|(k, v)| {
+                let v = self.variables.resolve(&v, &mut unresolved);
+                (k, v)
+            })
+            .collect();
+        let auth = request
+            .auth
+            .clone()
+            .filter(|a| a.auth_type != "none")
+            .or_else(|| self.env.and_then(|e| e.auth.clone()));
+        if !unresolved.is_empty() {
+            report.error = Some(format!("Unresolved variables: {}", unresolved.join(", ")));
+            return report;
+        }
+
+        let call_options = CallOptions {
+            metadata: Some(metadata),
+            auth,
+            tls: self.env.and_then(|e| e.tls.clone()).filter(|t| t.enabled),
+            timeout_ms: self.options.timeout_ms,
+            bin_metadata_types: request.bin_metadata_types.clone(),
+            ..Default::default()
+        };
+
+        let pool = match &self.pool {
+            Some(pool) => Arc::clone(pool),
+            None => match load_pool(self.workspace, self.endpoint, self.client, &call_options).await {
+                Ok(loaded) => Arc::clone(self.pool.insert(loaded)),
+                Err(e) => {
+                    report.error = Some(e);
+                    return report;
+                }
+            },
+        };
+
+        let start = Instant::now();
+        let outcome = match Call::new(pool, self.endpoint, &request.service, &request.method, call_options) {
+            Ok(call) => send(&call, self.client, &body).await,
+            Err(e) => Err(e),
+        };
+        report.duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        // Connection failures come back as a JSON result with a status; keep those so
+        // status assertions can expect them.
+        let result = match outcome {
+            Ok(result) => result,
+            Err(e) => match serde_json::from_str::<Value>(&e) {
+                Ok(result @ Value::Object(_)) => result,
+                _ => {
+                    report.error = Some(e);
+                    return report;
+                }
+            },
+        };
+
+        let mut checks = request.assertions.clone();
+        if !checks.iter().any(|a| matches!(a, Assertion::Status { .. })) {
+            checks.insert(0, Assertion::Status { code: Value::from(0) });
+        }
+        report.assertions = assertions::evaluate(&checks, &result);
+        report.passed = report.assertions.iter().all(|a| a.passed);
+        report.grpc_status = result["grpc_status"].as_str().map(str::to_string);
+        report.grpc_message = result["grpc_message"].as_str().map(str::to_string);
+        report.response = result["response"].clone();
+        report
+    }
+}
+
+/// The workspace's protos, or the server's descriptors by reflection when it has
+/// no import paths enabled.
+async fn load_pool(
+    workspace: &Workspace,
+    endpoint: &str,
+    client: &Client<Connector, Body>,
+    options: &CallOptions,
+) -> Result<Arc<DescriptorPool>, String> {
+    if workspace.import_paths.iter().any(|p| p.enabled) {
+        return proto_parser::compile_descriptor_pool(&workspace.import_paths).map(Arc::new);
+    }
+    let (pool, _warnings) = engine::reflect(client, endpoint, options).await?;
+    Ok(Arc::new(pool))
+}
+
+/// Make the call. Bidi responses are collected into `response` so they can be
+/// asserted on like server-streaming ones.
+async fn send(call: &Call, client: &Client<Connector, Body>, body: &str) -> Result<Value, String> {
+    let format = MessageFormat::default();
+    if !call.method.is_client_streaming() {
+        let request = format.decode(call.method.input(), body)?;
+        let echo = serde_json::from_str(body).unwrap_or(Value::Null);
+        return call.unary(client, &request, echo, |_, _| {}).await;
+    }
+
+    let (frames_tx, frames_rx) = mpsc::unbounded_channel();
+    for message in engine::split_messages(body, format)? {
+        let msg = format.decode(call.method.input(), &message)?;
+        let _ = frames_tx.send(call.encode(&msg)?);
+    }
+    drop(frames_tx);
+
+    let mut responses = Vec::new();
+    let mut result = call
+        .stream(client, frames_rx, |_, inspected| responses.push(inspected.data.clone()))
+        .await?;
+    if call.method.is_server_streaming() {
+        result["response"] = Value::Array(responses);
+    }
+    Ok(result)
+}
+
+// ---------------------------------------------------------------------------
+// Reports
+// ---------------------------------------------------------------------------
+
+/// JUnit XML with a test suite per collection. Requests that couldn't be made are
+/// errors; failed assertions are failures.
+pub fn junit_xml(report: &RunReport) -> String {
+    let mut suites: Vec<(&str, Vec<&RequestReport>)> = Vec::new();
+    for result in &report.results {
+        match suites.iter_mut().find(|(name, _)| *name == result.collection) {
+            Some((_, cases)) => cases.push(result),
+            None => suites.push((&result.collection, vec![result])),
+        }
+    }
+
+    let errors = report.results.iter().filter(|r| r.error.is_some()).count();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">\n",
+        escape_xml(&report.workspace),
+        report.total,
+        report.failed - errors,
+        errors,
+        report.duration_ms / 1000.0
+    ));
+
+    for (name, cases) in suites {
+        let errors = cases.iter().filter(|c| c.error.is_some()).count();
+        let failures = cases.iter().filter(|c| !c.passed).count() - errors;
+        let time: f64 = cases.iter().map(|c| c.duration_ms).sum::<f64>() / 1000.0;
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\" timestamp=\"{}\">\n",
+            escape_xml(name),
+            cases.len(),
+            failures,
+            errors,
+            time,
+            escape_xml(&report.started_at)
+        ));
+
+        for case in cases {
+            let classname = std::iter::once(case.collection.as_str())
+                .chain(case.folders.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(".");
+            xml.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape_xml(&case.name),
+                escape_xml(&classname),
+                case.duration_ms / 1000.0
+            ));
+
+            let failed: Vec<&AssertionResult> = case.assertions.iter().filter(|a| !a.passed).collect();
+            if let Some(error) = &case.error {
+                xml.push_str(&format!(
+                    ">\n      <error message=\"{}\"/>\n    </testcase>\n",
+                    escape_xml(error)
+                ));
+            } else if !failed.is_empty() {
+                let details: Vec<&str> = failed.iter().map(|a| a.message.as_str()).collect();
+                xml.push_str(&format!(
+                    ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                    escape_xml(details[0]),
+                    escape_xml(&details.join("\n"))
+                ));
+            } else {
+                xml.push_str("/>\n");
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // XML 1.0 can't carry other control characters, even escaped.
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => out.push('\u{fffd}'),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server;
+    use prost::Message;
+    use serde_json::json;
+
+    const PROTO: &str = r#"
+syntax = "proto3";
+package users.v1;
+service Users { rpc Get (GetUser) returns (User); }
+message GetUser { string id = 1; }
+message User { string id = 1; string name = 2; }
+"#;
+
+    fn variable(key: &str, value: &str, enabled: bool) -> Variable {
+        Variable { key: key.to_string(), value: value.to_string(), enabled }
+    }
+
+    fn environment(host: &str, port: u16) -> Environment {
+        serde_json::from_value(json!({ "id": "e1", "name": "Local", "host": host, "port": port })).unwrap()
+    }
+
+    fn import_path(path: &std::path::Path, path_type: &str, enabled: bool) -> ImportPath {
+        ImportPath {
+            id: path.display().to_string(),
+            path: path.display().to_string(),
+            path_type: path_type.to_string(),
+            enabled,
+        }
+    }
+
+    #[test]
+    fn test_variable_substitution() {
+        let env = [variable("id", "42", true), variable("region", "eu", false)];
+        let globals = [variable("tenant", "acme \"co\"", true), variable("id", "global", true)];
+        let variables = Variables { env: &env, globals: &globals };
+
+        // JSON is substituted value by value, so quotes in a variable stay quoted.
+        let mut unresolved = Vec::new();
+        let body = variables.resolve(r#"{"tenant": "{{global.tenant}}", "ids": ["{{env.id}}", "{{global.id}}"]}"#, &mut unresolved);
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body, json!({ "tenant": "acme \"co\"", "ids": ["42", "global"] }));
+        assert!(unresolved.is_empty());
+
+        // Other text is substituted as written; disabled and unknown variables are
+        // left in place and reported once each.
+        let text = variables.resolve("Bearer {{env.region}}/{{env.missing}}/{{env.region}}", &mut unresolved);
+        assert_eq!(text, "Bearer {{env.region}}/{{env.missing}}/{{env.region}}");
+        assert_eq!(unresolved, ["{{env.region}}", "{{env.missing}}"]);
+    }
+
+    #[test]
+    fn test_endpoint() {
+        let env = environment("https://api.example.com", 8443);
+        assert_eq!(endpoint(&RunOptions::default(), Some(&env)), "api.example.com:8443");
+        assert_eq!(endpoint(&RunOptions::default(), None), DEFAULT_ENDPOINT);
+        let options = RunOptions { endpoint: Some("http://override:1".to_string()), ..Default::default() };
+        assert_eq!(endpoint(&options, Some(&env)), "override:1");
+    }
+
+    #[test]
+    fn test_workspace_selection_and_order() {
+        let export = json!([
+            { "id": "ws1", "name": "Main", "collections": [{
+                "id": "c1",
+                "name": "Users",
+                "requests": [{ "id": "r2", "name": "Top", "service": "s", "method": "m" }],
+                "folders": [{ "id": "f1", "name": "Admin", "folders": [], "requests": [
+                    { "id": "r1", "name": "Nested", "service": "s", "method": "m" },
+                ]}],
+            }]},
+            { "id": "ws2", "name": "Other" },
+        ])
+        .to_string();
+
+        let err = load_workspace(&export, None).err().unwrap();
+        assert_eq!(err, "The file holds 2 workspaces; choose one of: Main, Other");
+        assert_eq!(load_workspace(&export, Some("ws2")).unwrap().name, "Other");
+        assert!(load_workspace(&export, Some("Nope")).is_err());
+
+        let workspace = load_workspace(&export, Some("Main")).unwrap();
+        let mut entries = Vec::new();
+        let c = &workspace.collections[0];
+        collect_entries(&c.name, &c.folders, &c.requests, &[], &mut entries);
+        let names: Vec<(&str, &[String])> = entries.iter().map(|e| (e.request.name.as_str(), e.folders.as_slice())).collect();
+        assert_eq!(names, [("Nested", &["Admin".to_string()][..]), ("Top", &[][..])]);
+    }
+
+    #[tokio::test]
+    async fn test_import_path_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let proto = dir.path().join("users.proto");
+        std::fs::write(&proto, PROTO).unwrap();
+        let set = dir.path().join("users.pb");
+        std::fs::write(&set, proto_parser::compile_single_file(PROTO).unwrap().encode_to_vec()).unwrap();
+
+        let client = engine::build_client("127.0.0.1:1", None, Some(true)).unwrap();
+        let options = CallOptions::default();
+        let missing = dir.path().join("missing");
+        for paths in [
+            vec![import_path(dir.path(), "directory", true)],
+            vec![import_path(&proto, "file", true)],
+            vec![import_path(&set, "file", true), import_path(&missing, "directory", false)],
+        ] {
+            let workspace = Workspace {
+                name: "w".to_string(),
+                import_paths: paths,
+                globals: Vec::new(),
+                environments: Vec::new(),
+                collections: Vec::new(),
+            };
+            let pool = load_pool(&workspace, "127.0.0.1:1", &client, &options).await.unwrap();
+            assert!(pool.get_service_by_name("users.v1.Users").is_some());
+        }
+
+        // With every path disabled, descriptors come from reflection instead.
+        let workspace = Workspace {
+            name: "w".to_string(),
+            import_paths: vec![import_path(dir.path(), "directory", false)],
+            globals: Vec::new(),
+            environments: Vec::new(),
+            collections: Vec::new(),
+        };
+        let err = load_pool(&workspace, "127.0.0.1:1", &client, &options).await.err().unwrap();
+        assert!(err.contains("connection refused") || err.contains("Connection refused"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_report_output() {
+        let addr = test_server::serve(|_req| async {
+            let user = prost_reflect::DynamicMessage::deserialize(
+                proto_parser::compile_single_file(PROTO).unwrap().get_message_by_name("users.v1.User").unwrap(),
+                &mut serde_json::Deserializer::from_str(r#"{"id": "7", "name": "Ada"}"#),
+            )
+            .unwrap();
+            test_server::grpc_response(vec![user.encode_to_vec()], 0, "")
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let set = dir.path().join("users.pb");
+        std::fs::write(&set, proto_parser::compile_single_file(PROTO).unwrap().encode_to_vec()).unwrap();
+
+        let workspace: Workspace = serde_json::from_value(json!({
+            "name": "Main",
+            "importPaths": [{ "id": "p", "path": set, "type": "file", "enabled": true }],
+            "collections": [{ "id": "c1", "name": "Users & co", "requests": [
+                { "id": "r1", "name": "Get", "service": "users.v1.Users", "method": "Get",
+                  "requestBody": "{\"id\": \"7\"}",
+                  "assertions": [{ "type": "jsonPath", "path": "$.name", "equals": "Ada" }] },
+                { "id": "r2", "name": "Wrong <name>", "service": "users.v1.Users", "method": "Get",
+                  "requestBody": "{}",
+                  "assertions": [{ "type": "jsonPath", "path": "$.name", "equals": "Bob" }] },
+                { "id": "r3", "name": "Missing", "service": "users.v1.Users", "method": "Delete" },
+            ]}],
+        }))
+        .unwrap();
+        let options = RunOptions { endpoint: Some(addr.to_string()), ..Default::default() };
+        let report = run(&workspace, &options).await.unwrap();
+
+        assert_eq!((report.total, report.passed, report.failed), (3, 1, 2));
+        let [get, wrong, missing] = &report.results[..] else { panic!("three results") };
+        assert!(get.passed && get.error.is_none());
+        assert_eq!(get.grpc_status.as_deref(), Some("0"));
+        assert_eq!(get.response, json!({ "id": "7", "name": "Ada" }));
+        assert_eq!(get.assertions.len(), 2, "an implicit OK status check plus the saved one");
+        assert!(!wrong.passed && wrong.error.is_none());
+        assert_eq!(missing.error.as_deref(), Some("Method 'Delete' not found in service 'users.v1.Users'"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["results"][0]["grpcStatus"], "0");
+        assert_eq!(json["endpoint"], addr.to_string());
+
+        let xml = junit_xml(&report);
+        assert!(xml.contains("<testsuites name=\"Main\" tests=\"3\" failures=\"1\" errors=\"1\""), "{}", xml);
+        assert!(xml.contains("<testsuite name=\"Users &amp; co\" tests=\"3\" failures=\"1\" errors=\"1\""), "{}", xml);
+        assert!(xml.contains("<testcase name=\"Wrong &lt;name&gt;\" classname=\"Users &amp; co\""), "{}", xml);
+        assert!(xml.contains("<failure message=\""), "{}", xml);
+        assert!(xml.contains("<error message=\"Method &apos;Delete&apos; not found"), "{}", xml);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_order_and_overrides() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen_c = Arc::clone(&seen);
+        let addr = test_server::serve(move |req| {
+            let seen = Arc::clone(&seen_c);
+            async move {
+                let headers = req.headers();
+                let mut seen = seen.lock().unwrap();
+                for name in ["x-env", "x-tag", "x-id"] {
+                    seen.extend(headers.get_all(name).iter().map(|v| format!("{}: {}", name, v.to_str().unwrap())));
+                }
+                test_server::grpc_response(vec![Vec::new()], 0, "")
+            }
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let set = dir.path().join("users.pb");
+        std::fs::write(&set, proto_parser::compile_single_file(PROTO).unwrap().encode_to_vec()).unwrap();
+
+        let workspace: Workspace = serde_json::from_value(json!({
+            "name": "Main",
+            "importPaths": [{ "id": "p", "path": set, "type": "file", "enabled": true }],
+            "environments": [{ "id": "e1", "name": "Local", "host": addr.ip().to_string(), "port": addr.port(),
+                "variables": [{ "key": "id", "value": "42", "enabled": true }],
+                "metadata": { "x-env": "env", "X-Tag": "from-env" } }],
+            "collections": [{ "id": "c1", "name": "Users", "requests": [
+                { "id": "r1", "name": "Get", "service": "users.v1.Users", "method": "Get", "requestBody": "{}",
+                  "metadata": [["x-tag", "a"], ["x-id", "{{env.id}}"], ["x-tag", "b"]] },
+            ]}],
+        }))
+        .unwrap();
+        let options = RunOptions { environment: Some("Local".to_string()), ..Default::default() };
+        let report = run(&workspace, &options).await.unwrap();
+
+        assert_eq!(report.passed, 1, "{:?}", report.results[0].error);
+        assert_eq!(*seen.lock().unwrap(), ["x-env: env", "x-tag: a", "x-tag: b", "x-id: 42"]);
+
+        // The object form the app exports still loads.
+        let request: SavedRequest = serde_json::from_value(json!({
+            "id": "r", "name": "n", "service": "s", "method": "m", "metadata": { "x-id": "1" },
+        }))
+        .unwrap();
+        assert_eq!(request.metadata, [("x-id".to_string(), "1".to_string())]);
+    }
+}
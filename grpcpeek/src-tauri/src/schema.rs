@@ -0,0 +1,271 @@
+use crate::proto_parser::comments;
+use base64::{engine::general_purpose, Engine as _};
+use prost_reflect::{Cardinality, EnumDescriptor, FieldDescriptor, Kind, MessageDescriptor, Value};
+use serde::Serialize;
+
+/// Messages with a special JSON mapping. They are marked rather than expanded, since
+/// their JSON form doesn't follow their fields.
+const WELL_KNOWN_TYPES: &[&str] = &[
+    "google.protobuf.Any",
+    "google.protobuf.BoolValue",
+    "google.protobuf.BytesValue",
+    "google.protobuf.DoubleValue",
+    "google.protobuf.Duration",
+    "google.protobuf.Empty",
+    "google.protobuf.FieldMask",
+    "google.protobuf.FloatValue",
+    "google.protobuf.Int32Value",
+    "google.protobuf.Int64Value",
+    "google.protobuf.ListValue",
+    "google.protobuf.StringValue",
+    "google.protobuf.Struct",
+    "google.protobuf.Timestamp",
+    "google.protobuf.UInt32Value",
+    "google.protobuf.UInt64Value",
+    "google.protobuf.Value",
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageSchema {
+    pub name: String,
+    pub fields: Vec<FieldSchema>,
+    pub oneofs: Vec<OneofSchema>,
+    pub documentation: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldSchema {
+    pub name: String,
+    pub json_name: String,
+    pub number: u32,
+    /// Scalar type name (`int64`, `string`, ...), or `message` / `enum`.
+    pub kind: String,
+    /// Full name of the message or enum type.
+    pub type_name: Option<String>,
+    /// "optional", "required" or "repeated". Map fields are "repeated" with `map` set.
+    pub cardinality: String,
+    /// Whether an unset field is distinguishable from its default.
+    pub has_presence: bool,
+    /// Name of the containing oneof, including proto3 `optional` synthetic oneofs.
+    pub oneof: Option<String>,
+    pub map: Option<MapSchema>,
+    pub enum_values: Option<Vec<EnumValueSchema>>,
+    /// Proto3 JSON form of the default value.
+    pub default_value: serde_json::Value,
+    /// Full name of a well-known type; `message` is not expanded for these.
+    pub well_known_type: Option<String>,
+    /// The message type already appears higher in the tree; `message` is omitted
+    /// and can be fetched separately by `type_name`.
+    pub recursive: bool,
+    pub message: Option<Box<MessageSchema>>,
+    pub documentation: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapSchema {
+    pub key_kind: String,
+    pub value: Box<FieldSchema>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OneofSchema {
+    pub name: String,
+    pub fields: Vec<String>,
+    /// Generated for a proto3 `optional` field rather than declared.
+    pub synthetic: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnumValueSchema {
+    pub name: String,
+    pub number: i32,
+    pub documentation: Option<String>,
+}
+
+/// Describe a message and, recursively, the messages its fields use.
+pub fn describe_message(desc: &MessageDescriptor) -> MessageSchema {
+    describe(desc, &mut vec![desc.full_name().to_string()])
+}
+
+fn describe(desc: &MessageDescriptor, ancestors: &mut Vec<String>) -> MessageSchema {
+    MessageSchema {
+        name: desc.full_name().to_string(),
+        fields: desc.fields().map(|f| describe_field(&f, ancestors)).collect(),
+        oneofs: desc
+            .oneofs()
+            .map(|o| OneofSchema {
+                name: o.name().to_string(),
+                fields: o.fields().map(|f| f.name().to_string()).collect(),
+                synthetic: o.is_synthetic(),
+            })
+            .collect(),
+        documentation: comments(desc.parent_file_descriptor_proto(), desc.path()),
+    }
+}
+
+fn describe_field(field: &FieldDescriptor, ancestors: &mut Vec<String>) -> FieldSchema {
+    let map = match field.kind() {
+        Kind::Message(entry) if field.is_map() => Some(MapSchema {
+            key_kind: kind_name(&entry.map_entry_key_field().kind()).to_string(),
+            value: Box::new(describe_field(&entry.map_entry_value_field(), ancestors)),
+        }),
+        _ => None,
+    };
+    let (type_name, enum_values, well_known_type, recursive, message) = match field.kind() {
+        Kind::Message(_) if map.is_some() => (None, None, None, false, None),
+        Kind::Message(m) => {
+            let name = m.full_name().to_string();
+            if WELL_KNOWN_TYPES.contains(&m.full_name()) {
+                (Some(name.clone()), None, Some(name), false, None)
+            } else if ancestors.contains(&name) {
+                (Some(name), None, None, true, None)
+            } else {
+                ancestors.push(name.clone());
+                let schema = describe(&m, ancestors);
+                ancestors.pop();
+                (Some(name), None, None, false, Some(Box::new(schema)))
+            }
+        }
+        Kind::Enum(e) => (Some(e.full_name().to_string()), Some(enum_values(&e)), None, false, None),
+        _ => (None, None, None, false, None),
+    };
+
+    FieldSchema {
+        name: field.name().to_string(),
+        json_name: field.json_name().to_string(),
+        number: field.number(),
+        kind: kind_name(&field.kind()).to_string(),
+        type_name,
+        cardinality: match field.cardinality() {
+            Cardinality::Optional => "optional",
+            Cardinality::Required => "required",
+            Cardinality::Repeated => "repeated",
+        }
+        .to_string(),
+        has_presence: field.supports_presence(),
+        oneof: field.containing_oneof().map(|o| o.name().to_string()),
+        map,
+        enum_values,
+        default_value: default_json(field),
+        well_known_type,
+        recursive,
+        message,
+        documentation: comments(field.parent_file().file_descriptor_proto(), field.path()),
+    }
+}
+
+fn enum_values(desc: &EnumDescriptor) -> Vec<EnumValueSchema> {
+    let file = desc.parent_file_descriptor_proto();
+    desc.values()
+        .map(|v| EnumValueSchema {
+            name: v.name().to_string(),
+            number: v.number(),
+            documentation: comments(file, v.path()),
+        })
+        .collect()
+}
+
+/// Default value as it would appear in proto3 JSON: 64-bit integers are strings,
+/// bytes are base64 and enums use the value name. Messages default to `null`.
+fn default_json(field: &FieldDescriptor) -> serde_json::Value {
+    if field.is_map() {
+        return serde_json::json!({});
+    }
+    if field.is_list() {
+        return serde_json::json!([]);
+    }
+    match field.default_value() {
+        Value::Bool(b) => b.into(),
+        Value::I32(n) => n.into(),
+        Value::U32(n) => n.into(),
+        Value::I64(n) => n.to_string().into(),
+        Value::U64(n) => n.to_string().into(),
+        Value::F32(f) => f.into(),
+        Value::F64(f) => f.into(),
+        Value::String(s) => s.into(),
+        Value::Bytes(b) => general_purpose::STANDARD.encode(b).into(),
+        Value::EnumNumber(n) => match field.kind() {
+            Kind::Enum(e) => e.get_value(n).map(|v| v.name().into()).unwrap_or_else(|| n.into()),
+            _ => n.into(),
+        },
+        Value::Message(_) | Value::List(_) | Value::Map(_) => serde_json::Value::Null,
+    }
+}
+
+/// Proto type name for scalars; `message` or `enum` otherwise.
+pub fn kind_name(kind: &Kind) -> &'static str {
+    match kind {
+        Kind::Double => "double",
+        Kind::Float => "float",
+        Kind::Int32 => "int32",
+        Kind::Int64 => "int64",
+        Kind::Uint32 => "uint32",
+        Kind::Uint64 => "uint64",
+        Kind::Sint32 => "sint32",
+        Kind::Sint64 => "sint64",
+        Kind::Fixed32 => "fixed32",
+        Kind::Fixed64 => "fixed64",
+        Kind::Sfixed32 => "sfixed32",
+        Kind::Sfixed64 => "sfixed64",
+        Kind::Bool => "bool",
+        Kind::String => "string",
+        Kind::Bytes => "bytes",
+        Kind::Message(_) => "message",
+        Kind::Enum(_) => "enum",
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto_parser::compile_single_file;
+
+    #[test]
+    fn test_describe_message_tree() {
+        let proto = r#"
+syntax = "proto3";
+package tree.v1;
+import "google/protobuf/timestamp.proto";
+
+service Svc { rpc Do (Node) returns (Node); }
+
+message Node {
+  string id = 1;
+  repeated Node children = 2;
+  map<string, Status> labels = 3;
+  optional int64 weight = 4;
+  oneof payload {
+    string text = 5;
+    google.protobuf.Timestamp at = 6;
+  }
+}
+
+enum Status { STATUS_UNSPECIFIED = 0; STATUS_OK = 1; }
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let schema = describe_message(&pool.get_message_by_name("tree.v1.Node").unwrap());
+        let field = |name: &str| schema.fields.iter().find(|f| f.name == name).unwrap();
+
+        assert!(field("children").recursive && field("children").message.is_none());
+        assert_eq!(field("children").cardinality, "repeated");
+
+        let labels = field("labels").map.as_ref().expect("map schema");
+        assert_eq!(labels.key_kind, "string");
+        assert_eq!(labels.value.enum_values.as_ref().unwrap().len(), 2);
+
+        assert_eq!(field("weight").default_value, serde_json::json!("0"));
+        assert!(field("weight").has_presence);
+        assert_eq!(field("at").well_known_type.as_deref(), Some("google.protobuf.Timestamp"));
+        assert_eq!(field("text").oneof.as_deref(), Some("payload"));
+        assert!(schema.oneofs.iter().any(|o| o.name == "_weight" && o.synthetic));
+    }
+}
@@ -0,0 +1,178 @@
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine as _;
+use http::HeaderMap;
+use lazy_static::lazy_static;
+use prost::Message as ProstMessage;
+use prost_reflect::{DescriptorPool, DynamicMessage, SerializeOptions};
+use protox::file::{ChainFileResolver, File, FileResolver, GoogleFileResolver};
+use serde_json::Value;
+
+const STATUS_DETAILS_HEADER: &str = "grpc-status-details-bin";
+
+/// The standard error model protos, bundled so details decode even when the
+/// user's import paths don't include googleapis.
+const BUNDLED_PROTOS: &[(&str, &str)] = &[
+    ("google/rpc/status.proto", include_str!("../proto/google/rpc/status.proto")),
+    ("google/rpc/error_details.proto", include_str!("../proto/google/rpc/error_details.proto")),
+];
+
+/// `-bin` metadata is base64 and senders may or may not pad it.
+const BIN_HEADER_ENGINE: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+lazy_static! {
+    static ref RPC_POOL: Result<DescriptorPool, String> = compile_bundled_protos();
+}
+
+/// `google.rpc.Status`, decoded statically since its shape never changes.
+#[derive(Clone, PartialEq, ProstMessage)]
+struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    code: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<prost_types::Any>,
+}
+
+struct BundledFileResolver;
+
+impl FileResolver for BundledFileResolver {
+    fn open_file(&self, name: &str) -> Result<File, protox::Error> {
+        match BUNDLED_PROTOS.iter().find(|(n, _)| *n == name) {
+            Some((n, source)) => File::from_source(n, source),
+            None => Err(protox::Error::file_not_found(name)),
+        }
+    }
+}
+
+fn compile_bundled_protos() -> Result<DescriptorPool, String> {
+    let mut resolver = ChainFileResolver::new();
+    resolver.add(BundledFileResolver);
+    resolver.add(GoogleFileResolver::new());
+
+    let mut compiler = protox::Compiler::with_file_resolver(resolver);
+    compiler
+        .include_imports(true)
+        .open_files(BUNDLED_PROTOS.iter().map(|(name, _)| *name))
+        .map_err(|e| format!("Failed to compile bundled google.rpc protos: {:?}", e))?;
+
+    DescriptorPool::decode(compiler.encode_file_descriptor_set().as_slice())
+        .map_err(|e| format!("Failed to decode bundled google.rpc protos: {}", e))
+}
+
+/// Decode a `-bin` metadata value, accepting padded and unpadded base64.
+pub fn decode_bin_value(value: &[u8]) -> Result<Vec<u8>, base64::DecodeError> {
+    BIN_HEADER_ENGINE.decode(value)
+}
+
+/// Decode `grpc-status-details-bin` (trailers first, then headers for trailers-only
+/// responses) into a list of rich error details. Each `google.protobuf.Any` is
+/// resolved against `pool` first and the bundled `google.rpc` types second; details
+/// whose type can't be found are returned as their type URL plus base64 bytes.
+///
+/// Returns `None` when the server didn't send status details.
+pub fn error_details(
+    headers: &HeaderMap,
+    trailers: Option<&HeaderMap>,
+    pool: &DescriptorPool,
+) -> Option<Vec<Value>> {
+    let raw = trailers
+        .and_then(|t| t.get(STATUS_DETAILS_HEADER))
+        .or_else(|| headers.get(STATUS_DETAILS_HEADER))?;
+
+    let status = match decode_bin_value(raw.as_bytes())
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| RpcStatus::decode(bytes.as_slice()).map_err(|e| e.to_string()))
+    {
+        Ok(status) => status,
+        Err(e) => {
+            return Some(vec![serde_json::json!({
+                "error": format!("Failed to decode {}: {}", STATUS_DETAILS_HEADER, e),
+                "raw": String::from_utf8_lossy(raw.as_bytes()),
+            })])
+        }
+    };
+
+    Some(status.details.iter().map(|any| resolve_any(any, pool)).collect())
+}
+
+fn resolve_any(any: &prost_types::Any, pool: &DescriptorPool) -> Value {
+    let type_name = any.type_url.rsplit('/').next().unwrap_or(&any.type_url);
+    let desc = pool.get_message_by_name(type_name).or_else(|| {
+        RPC_POOL
+            .as_ref()
+            .ok()
+            .and_then(|p| p.get_message_by_name(type_name))
+    });
+
+    let decoded = desc
+        .and_then(|d| DynamicMessage::decode(d, any.value.as_slice()).ok())
+        .and_then(|msg| crate::any::to_json(&msg, &SerializeOptions::new()).ok());
+
+    match decoded {
+        Some(Value::Object(fields)) => {
+            let mut obj = serde_json::Map::new();
+            obj.insert("@type".to_string(), Value::String(any.type_url.clone()));
+            obj.extend(fields);
+            Value::Object(obj)
+        }
+        // Well-known types serialize to a bare value rather than an object
+        Some(other) => serde_json::json!({ "@type": any.type_url, "value": other }),
+        None => crate::any::unresolved(&any.type_url, &any.value),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose;
+
+    #[test]
+    fn test_decodes_bad_request_details() {
+        let rpc_pool = RPC_POOL.as_ref().expect("bundled protos should compile");
+        let bad_request = rpc_pool.get_message_by_name("google.rpc.BadRequest").unwrap();
+        let detail = DynamicMessage::deserialize(
+            bad_request,
+            serde_json::json!({
+                "fieldViolations": [{ "field": "email", "description": "must not be empty" }]
+            }),
+        )
+        .unwrap();
+
+        let status = RpcStatus {
+            code: 3,
+            message: "invalid request".to_string(),
+            details: vec![
+                prost_types::Any {
+                    type_url: "type.googleapis.com/google.rpc.BadRequest".to_string(),
+                    value: detail.encode_to_vec(),
+                },
+                prost_types::Any {
+                    type_url: "type.googleapis.com/acme.Unknown".to_string(),
+                    value: vec![1, 2, 3],
+                },
+            ],
+        };
+        // Servers commonly omit base64 padding on -bin metadata
+        let encoded = general_purpose::STANDARD_NO_PAD.encode(status.encode_to_vec());
+        let mut trailers = HeaderMap::new();
+        trailers.insert(STATUS_DETAILS_HEADER, encoded.parse().unwrap());
+
+        let details = error_details(&HeaderMap::new(), Some(&trailers), &DescriptorPool::new())
+            .expect("details should be present");
+
+        assert_eq!(details.len(), 2);
+        assert_eq!(details[0]["@type"], "type.googleapis.com/google.rpc.BadRequest");
+        assert_eq!(details[0]["fieldViolations"][0]["field"], "email");
+        assert_eq!(details[1]["unresolved"], true);
+        assert_eq!(details[1]["value"], "AQID");
+    }
+}
@@ -0,0 +1,70 @@
+//! In-process gRPC servers for tests: plain hyper services speaking just enough of
+//! the protocol for the code under test.
+
+use crate::compression::Encoding;
+use crate::engine;
+use hyper::header::{HeaderMap, HeaderValue};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+
+/// Serve `handler` over cleartext HTTP/2 on a free local port for as long as the
+/// test's runtime lives. Returns the address to call.
+pub fn serve<F, Fut>(handler: F) -> SocketAddr
+where
+    F: Fn(Request<Body>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Response<Body>> + Send + 'static,
+{
+    let make_service = make_service_fn(move |_| {
+        let handler = handler.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let response = handler(req);
+                async move { Ok::<_, Infallible>(response.await) }
+            }))
+        }
+    });
+    let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+        .http2_only(true)
+        .serve(make_service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+/// The protobuf payloads of a request's frames.
+pub async fn read_messages(req: Request<Body>) -> Vec<Vec<u8>> {
+    let body = hyper::body::to_bytes(req.into_body()).await.expect("request body");
+    let mut buf = bytes::BytesMut::from(&body[..]);
+    let mut messages = Vec::new();
+    while let Some((_, data)) = engine::next_frame(&mut buf) {
+        messages.push(data.to_vec());
+    }
+    messages
+}
+
+/// A gRPC response carrying `messages` (protobuf-encoded), then `status` and
+/// `message` in the trailers.
+pub fn grpc_response(messages: Vec<Vec<u8>>, status: u32, message: &str) -> Response<Body> {
+    let (mut sender, body) = Body::channel();
+    let mut trailers = HeaderMap::new();
+    trailers.insert("grpc-status", HeaderValue::from(status));
+    if !message.is_empty() {
+        trailers.insert("grpc-message", HeaderValue::from_str(message).expect("ASCII message"));
+    }
+    tokio::spawn(async move {
+        for msg in messages {
+            let frame = engine::encode_message(&msg, Encoding::Identity).expect("frame");
+            if sender.send_data(frame.into()).await.is_err() {
+                return;
+            }
+        }
+        let _ = sender.send_trailers(trailers).await;
+    });
+    Response::builder()
+        .header("content-type", "application/grpc")
+        .body(body)
+        .expect("response")
+}
@@ -0,0 +1,93 @@
+use crate::connector::ConnectTimings;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// Records when each phase of a call happened, relative to the moment the request
+/// was handed to the client.
+pub struct CallTimer {
+    start: Instant,
+    connect: Option<ConnectTimings>,
+    headers: Option<Duration>,
+    messages: Vec<Duration>,
+    trailers: Option<Duration>,
+}
+
+impl CallTimer {
+    pub fn start() -> Self {
+        CallTimer {
+            start: Instant::now(),
+            connect: None,
+            headers: None,
+            messages: Vec::new(),
+            trailers: None,
+        }
+    }
+
+    /// Mark the response headers as received. `extensions` are the response's; the
+    /// connector stores the connection's timings there.
+    pub fn headers(&mut self, extensions: &http::Extensions) {
+        self.headers = Some(self.start.elapsed());
+        self.connect = extensions.get::<ConnectTimings>().copied();
+    }
+
+    pub fn message(&mut self) {
+        self.messages.push(self.start.elapsed());
+    }
+
+    pub fn trailers(&mut self) {
+        self.trailers = Some(self.start.elapsed());
+    }
+
+    /// Breakdown for the result JSON, in milliseconds. Connection phases are only
+    /// reported when this call opened the connection; a pooled connection that was
+    /// already up shows `connection_reused: true` instead.
+    pub fn to_json(&self) -> Value {
+        let new_connection = self.connect.filter(|c| c.established_at >= self.start);
+        serde_json::json!({
+            "total_ms": ms(self.start.elapsed()),
+            "connection_reused": self.connect.is_some() && new_connection.is_none(),
+            "dns_ms": new_connection.and_then(|c| c.dns).map(ms),
+            "tcp_connect_ms": new_connection.map(|c| ms(c.connect)),
+            "tls_handshake_ms": new_connection.and_then(|c| c.tls).map(ms),
+            "response_headers_ms": self.headers.map(ms),
+            "first_message_ms": self.messages.first().copied().map(ms),
+            "message_offsets_ms": self.messages.iter().copied().map(ms).collect::<Vec<_>>(),
+            "trailers_ms": self.trailers.map(ms),
+        })
+    }
+}
+
+/// Milliseconds with microsecond precision.
+fn ms(d: Duration) -> f64 {
+    (d.as_micros() as f64) / 1000.0
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reused_connection_omits_connect_phases() {
+        let mut extensions = http::Extensions::new();
+        extensions.insert(ConnectTimings {
+            established_at: Instant::now(),
+            dns: Some(Duration::from_millis(2)),
+            connect: Duration::from_millis(3),
+            tls: None,
+        });
+
+        let mut timer = CallTimer::start();
+        timer.headers(&extensions);
+        timer.message();
+        timer.trailers();
+        let json = timer.to_json();
+        assert_eq!(json["connection_reused"], true);
+        assert!(json["dns_ms"].is_null());
+        assert_eq!(json["message_offsets_ms"].as_array().unwrap().len(), 1);
+        assert!(json["trailers_ms"].as_f64().unwrap() >= json["first_message_ms"].as_f64().unwrap());
+    }
+}
@@ -0,0 +1,658 @@
+use crate::schema::kind_name;
+use base64::{engine::general_purpose, Engine as _};
+use prost_reflect::{DynamicMessage, FieldDescriptor, Kind, MessageDescriptor};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Largest `google.protobuf.Duration` magnitude in seconds (10,000 years).
+const MAX_DURATION_SECONDS: i64 = 315_576_000_000;
+
+/// One problem found in a request body.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    /// JSON pointer (RFC 6901) to the offending value; "" is the whole document.
+    pub path: String,
+    /// "invalid_json", "unknown_field", "duplicate_field", "wrong_type",
+    /// "invalid_enum", "out_of_range", "invalid_format", "oneof_conflict" or "invalid".
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Check a JSON request body against `desc` using the proto3 JSON mapping and
+/// return every problem found, rather than stopping at the first like
+/// `DynamicMessage::deserialize`.
+pub fn validate_request(desc: &MessageDescriptor, request: &str) -> Vec<Diagnostic> {
+    let json: Value = match serde_json::from_str(request) {
+        Ok(json) => json,
+        Err(e) => {
+            return vec![Diagnostic { path: String::new(), code: "invalid_json", message: e.to_string() }];
+        }
+    };
+
+    let mut validator = Validator { diagnostics: Vec::new() };
+    validator.message(desc, &json, "");
+
+    // Anything the checks above miss still surfaces, just without a precise path.
+    if validator.diagnostics.is_empty() {
+        if let Err(e) = DynamicMessage::deserialize(desc.clone(), &json) {
+            validator.report("", "invalid", e.to_string());
+        }
+    }
+    validator.diagnostics
+}
+
+struct Validator {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator {
+    fn report(&mut self, path: &str, code: &'static str, message: String) {
+        self.diagnostics.push(Diagnostic { path: path.to_string(), code, message });
+    }
+
+    fn message(&mut self, desc: &MessageDescriptor, value: &Value, path: &str) {
+        if self.well_known(desc, value, path) {
+            return;
+        }
+        let Value::Object(obj) = value else {
+            self.report(path, "wrong_type", format!("Expected an object for {}, got {}", desc.full_name(), type_of(value)));
+            return;
+        };
+        self.fields(desc, obj, path, &[]);
+    }
+
+    /// Validate an object's members as fields of `desc`, skipping `ignored` keys.
+    fn fields(&mut self, desc: &MessageDescriptor, obj: &Map<String, Value>, path: &str, ignored: &[&str]) {
+        let mut seen: Vec<(u32, &str)> = Vec::new();
+        for (key, value) in obj.iter().filter(|(k, _)| !ignored.contains(&k.as_str())) {
+            let field_path = format!("{}/{}", path, escape_pointer(key));
+            let field = desc.get_field_by_json_name(key).or_else(|| desc.get_field_by_name(key));
+            let Some(field) = field else {
+                self.report(&field_path, "unknown_field", format!("Unknown field '{}' in {}", key, desc.full_name()));
+                continue;
+            };
+            if let Some((_, other)) = seen.iter().find(|(n, _)| *n == field.number()) {
+                let message = format!("Field '{}' is set twice (also as '{}')", field.name(), other);
+                self.report(&field_path, "duplicate_field", message);
+                continue;
+            }
+            seen.push((field.number(), key));
+            self.field(&field, value, &field_path);
+        }
+
+        for oneof in desc.oneofs().filter(|o| !o.is_synthetic()) {
+            let set: Vec<&str> = seen
+                .iter()
+                .filter(|(n, key)| {
+                    oneof.fields().any(|f| f.number() == *n) && (!obj[*key].is_null() || accepts_null(desc, *n))
+                })
+                .map(|(_, key)| *key)
+                .collect();
+            if set.len() > 1 {
+                let message = format!("Only one member of oneof '{}' may be set, found: {}", oneof.name(), set.join(", "));
+                self.report(path, "oneof_conflict", message);
+            }
+        }
+    }
+
+    fn field(&mut self, field: &FieldDescriptor, value: &Value, path: &str) {
+        // null means "default" for every field except google.protobuf.Value.
+        if value.is_null() {
+            return;
+        }
+        if field.is_map() {
+            let Value::Object(entries) = value else {
+                self.report(path, "wrong_type", format!("Expected an object for map field, got {}", type_of(value)));
+                return;
+            };
+            let Kind::Message(entry) = field.kind() else {
+                return;
+            };
+            let key_kind = entry.map_entry_key_field().kind();
+            let value_field = entry.map_entry_value_field();
+            for (key, item) in entries {
+                let item_path = format!("{}/{}", path, escape_pointer(key));
+                self.map_key(&key_kind, key, &item_path);
+                self.single(&value_field.kind(), item, &item_path);
+            }
+        } else if field.is_list() {
+            let Value::Array(items) = value else {
+                self.report(path, "wrong_type", format!("Expected an array for repeated field, got {}", type_of(value)));
+                return;
+            };
+            for (i, item) in items.iter().enumerate() {
+                let item_path = format!("{}/{}", path, i);
+                if item.is_null() && !accepts_null_kind(&field.kind()) {
+                    self.report(&item_path, "wrong_type", "Repeated field elements cannot be null".to_string());
+                } else {
+                    self.single(&field.kind(), item, &item_path);
+                }
+            }
+        } else {
+            self.single(&field.kind(), value, path);
+        }
+    }
+
+    /// Validate one value of the given type (a singular field, list element or map value).
+    fn single(&mut self, kind: &Kind, value: &Value, path: &str) {
+        if value.is_null() && accepts_null_kind(kind) {
+            return;
+        }
+        let result = match kind {
+            Kind::Message(desc) => {
+                self.message(desc, value, path);
+                return;
+            }
+            Kind::Enum(desc) => match value {
+                Value::String(name) if desc.get_value_by_name(name).is_some() => Ok(()),
+                Value::String(name) => {
+                    let names: Vec<String> = desc.values().map(|v| v.name().to_string()).collect();
+                    let message = format!("'{}' is not a value of {} (expected one of {})", name, desc.full_name(), names.join(", "));
+                    self.report(path, "invalid_enum", message);
+                    return;
+                }
+                Value::Number(_) => check_int(value, i32::MIN as i128, i32::MAX as i128, "enum"),
+                _ => Err(("wrong_type", format!("Expected an enum name or number, got {}", type_of(value)))),
+            },
+            Kind::Float => check_float(value, true),
+            Kind::Double => check_float(value, false),
+            Kind::Bool => match value {
+                Value::Bool(_) => Ok(()),
+                _ => Err(("wrong_type", format!("Expected a boolean, got {}", type_of(value)))),
+            },
+            Kind::String => match value {
+                Value::String(_) => Ok(()),
+                _ => Err(("wrong_type", format!("Expected a string, got {}", type_of(value)))),
+            },
+            Kind::Int32
+            | Kind::Sint32
+            | Kind::Sfixed32
+            | Kind::Uint32
+            | Kind::Fixed32
+            | Kind::Int64
+            | Kind::Sint64
+            | Kind::Sfixed64
+            | Kind::Uint64
+            | Kind::Fixed64 => check_int(value, kind_min(kind), kind_max(kind), kind_name(kind)),
+            Kind::Bytes => match value {
+                Value::String(s) if decode_base64(s) => Ok(()),
+                Value::String(_) => Err(("invalid_format", "Expected base64-encoded bytes".to_string())),
+                _ => Err(("wrong_type", format!("Expected a base64 string, got {}", type_of(value)))),
+            },
+        };
+        if let Err((code, message)) = result {
+            self.report(path, code, message);
+        }
+    }
+
+    fn map_key(&mut self, kind: &Kind, key: &str, path: &str) {
+        let result = match kind {
+            Kind::Bool if key != "true" && key != "false" => Err("Map key must be \"true\" or \"false\"".to_string()),
+            Kind::String | Kind::Bool => Ok(()),
+            _ => check_int(&Value::String(key.to_string()), kind_min(kind), kind_max(kind), kind_name(kind))
+                .map_err(|(_, m)| format!("Invalid map key '{}': {}", key, m)),
+        };
+        if let Err(message) = result {
+            self.report(path, "wrong_type", message);
+        }
+    }
+
+    /// Validate the special JSON forms of well-known types. Returns false for
+    /// ordinary messages.
+    fn well_known(&mut self, desc: &MessageDescriptor, value: &Value, path: &str) -> bool {
+        let result = match desc.full_name() {
+            "google.protobuf.Timestamp" => match value {
+                Value::String(s) => check_timestamp(s),
+                _ => Err(("wrong_type", format!("Expected an RFC 3339 timestamp string, got {}", type_of(value)))),
+            },
+            "google.protobuf.Duration" => match value {
+                Value::String(s) => check_duration(s),
+                _ => Err(("wrong_type", format!("Expected a duration string like \"1.5s\", got {}", type_of(value)))),
+            },
+            "google.protobuf.FieldMask" => match value {
+                Value::String(_) => Ok(()),
+                _ => Err(("wrong_type", format!("Expected a comma-separated field mask string, got {}", type_of(value)))),
+            },
+            "google.protobuf.Struct" => match value {
+                Value::Object(_) => Ok(()),
+                _ => Err(("wrong_type", format!("Expected an object, got {}", type_of(value)))),
+            },
+            "google.protobuf.ListValue" => match value {
+                Value::Array(_) => Ok(()),
+                _ => Err(("wrong_type", format!("Expected an array, got {}", type_of(value)))),
+            },
+            "google.protobuf.Value" => Ok(()),
+            "google.protobuf.Any" => {
+                self.any(desc, value, path);
+                return true;
+            }
+            name if is_wrapper(name) => {
+                if let Some(inner) = desc.get_field_by_name("value") {
+                    self.single(&inner.kind(), value, path);
+                }
+                return true;
+            }
+            _ => return false,
+        };
+        if let Err((code, message)) = result {
+            self.report(path, code, message);
+        }
+        true
+    }
+
+    /// `Any` is `{"@type": "type.googleapis.com/pkg.Msg", ...fields}`, or `"value"`
+    /// holding the special form when the packed type is itself well-known.
+    fn any(&mut self, desc: &MessageDescriptor, value: &Value, path: &str) {
+        let Value::Object(obj) = value else {
+            self.report(path, "wrong_type", format!("Expected an object for google.protobuf.Any, got {}", type_of(value)));
+            return;
+        };
+        let type_url = match obj.get("@type") {
+            Some(Value::String(url)) => url,
+            _ => {
+                self.report(path, "invalid_format", "google.protobuf.Any requires an \"@type\" string".to_string());
+                return;
+            }
+        };
+        let type_name = type_url.rsplit('/').next().unwrap_or(type_url);
+        let Some(packed) = desc.parent_pool().get_message_by_name(type_name) else {
+            // Unknown packed types are left to the server.
+            return;
+        };
+        if self.well_known(&packed, obj.get("value").unwrap_or(&Value::Null), &format!("{}/value", path)) {
+            if let Some(extra) = obj.keys().find(|k| *k != "@type" && *k != "value") {
+                let message = format!("Unexpected field '{}' next to \"value\" for {}", extra, type_name);
+                self.report(&format!("{}/{}", path, escape_pointer(extra)), "unknown_field", message);
+            }
+        } else {
+            self.fields(&packed, obj, path, &["@type"]);
+        }
+    }
+}
+
+fn check_int(value: &Value, min: i128, max: i128, type_name: &str) -> Result<(), (&'static str, String)> {
+    let parsed: Option<i128> = match value {
+        Value::Number(n) => n
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+            .or_else(|| n.as_f64().filter(|f| f.fract() == 0.0 && f.abs() < 1e39).map(|f| f as i128)),
+        // Quoted integers are decoded as plain digits, without exponents.
+        Value::String(s) => s.trim().parse::<i128>().ok(),
+        _ => return Err(("wrong_type", format!("Expected an integer, got {}", type_of(value)))),
+    };
+    match parsed {
+        None => Err(("wrong_type", format!("Expected an integer, got {}", value))),
+        Some(n) if n < min || n > max => Err(("out_of_range", format!("{} is out of range for {}", value, type_name))),
+        Some(_) => Ok(()),
+    }
+}
+
+fn check_float(value: &Value, single: bool) -> Result<(), (&'static str, String)> {
+    let parsed = match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) if matches!(s.as_str(), "NaN" | "Infinity" | "-Infinity") => return Ok(()),
+        Value::String(s) => s.trim().parse::<f64>().ok(),
+        _ => return Err(("wrong_type", format!("Expected a number, got {}", type_of(value)))),
+    };
+    match parsed {
+        None => Err(("wrong_type", format!("Expected a number, got {}", value))),
+        Some(f) if single && f.is_finite() && f.abs() > f32::MAX as f64 => {
+            Err(("out_of_range", format!("{} is out of range for float", value)))
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+fn check_timestamp(s: &str) -> Result<(), (&'static str, String)> {
+    let parsed = chrono::DateTime::parse_from_rfc3339(s)
+        .map_err(|e| ("invalid_format", format!("Invalid timestamp '{}': {} (expected e.g. 2024-01-31T12:00:00Z)", s, e)))?;
+    let year = chrono::Datelike::year(&parsed.naive_utc());
+    if !(1..=9999).contains(&year) {
+        return Err(("out_of_range", format!("Timestamp '{}' must be between years 0001 and 9999", s)));
+    }
+    Ok(())
+}
+
+fn check_duration(s: &str) -> Result<(), (&'static str, String)> {
+    let invalid = || ("invalid_format", format!("Invalid duration '{}' (expected seconds with an 's' suffix, e.g. \"1.5s\")", s));
+    let body = s.strip_suffix('s').ok_or_else(invalid)?;
+    let unsigned = body.strip_prefix('-').unwrap_or(body);
+    let (whole, frac) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let digits = |p: &str| p.chars().all(|c| c.is_ascii_digit());
+    if whole.is_empty() || !digits(whole) || !digits(frac) || frac.len() > 9 || (unsigned.contains('.') && frac.is_empty()) {
+        return Err(invalid());
+    }
+    match whole.parse::<i64>() {
+        Ok(secs) if secs <= MAX_DURATION_SECONDS => Ok(()),
+        _ => Err(("out_of_range", format!("Duration '{}' exceeds ±{}s", s, MAX_DURATION_SECONDS))),
+    }
+}
+
+fn decode_base64(s: &str) -> bool {
+    general_purpose::STANDARD.decode(s).is_ok()
+        || general_purpose::STANDARD_NO_PAD.decode(s).is_ok()
+        || general_purpose::URL_SAFE.decode(s).is_ok()
+        || general_purpose::URL_SAFE_NO_PAD.decode(s).is_ok()
+}
+
+fn is_wrapper(name: &str) -> bool {
+    matches!(
+        name,
+        "google.protobuf.DoubleValue"
+            | "google.protobuf.FloatValue"
+            | "google.protobuf.Int64Value"
+            | "google.protobuf.UInt64Value"
+            | "google.protobuf.Int32Value"
+            | "google.protobuf.UInt32Value"
+            | "google.protobuf.BoolValue"
+            | "google.protobuf.StringValue"
+            | "google.protobuf.BytesValue"
+    )
+}
+
+/// `google.protobuf.Value` and `NullValue` take `null` as a real value.
+fn accepts_null_kind(kind: &Kind) -> bool {
+    match kind {
+        Kind::Message(m) => m.full_name() == "google.protobuf.Value",
+        Kind::Enum(e) => e.full_name() == "google.protobuf.NullValue",
+        _ => false,
+    }
+}
+
+fn accepts_null(desc: &MessageDescriptor, number: u32) -> bool {
+    desc.get_field(number).is_some_and(|f| accepts_null_kind(&f.kind()))
+}
+
+/// Range of an integer kind.
+fn kind_min(kind: &Kind) -> i128 {
+    match kind {
+        Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => i32::MIN as i128,
+        Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => i64::MIN as i128,
+        _ => 0,
+    }
+}
+
+fn kind_max(kind: &Kind) -> i128 {
+    match kind {
+        Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => i32::MAX as i128,
+        Kind::Uint32 | Kind::Fixed32 => u32::MAX as i128,
+        Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => i64::MAX as i128,
+        _ => u64::MAX as i128,
+    }
+}
+
+fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Escape a key for use as a JSON pointer segment.
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto_parser::compile_single_file;
+
+    const SHAPES: &str = r#"
+syntax = "proto3";
+package v.v1;
+import "google/protobuf/any.proto";
+import "google/protobuf/duration.proto";
+import "google/protobuf/timestamp.proto";
+import "google/protobuf/wrappers.proto";
+
+enum Color { COLOR_UNSPECIFIED = 0; RED = 1; }
+
+message Contact {
+  oneof target { string email = 1; string phone = 2; }
+}
+
+message Shape {
+  string display_name = 1;
+  bool enabled = 2;
+  int32 small = 3;
+  uint64 big = 4;
+  int64 signed = 5;
+  Color color = 6;
+  repeated string tags = 7;
+  map<bool, string> flags = 8;
+  map<int64, string> by_id = 9;
+  map<string, int32> counts = 10;
+  float ratio = 11;
+  double exact = 12;
+  google.protobuf.Timestamp at = 13;
+  google.protobuf.Duration ttl = 14;
+  google.protobuf.Any detail = 15;
+  google.protobuf.Int32Value limit = 16;
+  Contact contact = 17;
+}
+"#;
+
+    /// (path, code) of each diagnostic for a `v.v1.Shape` request, sorted by path.
+    fn diagnostics(request: &str) -> Vec<(String, &'static str)> {
+        let pool = compile_single_file(SHAPES).expect("compile should succeed");
+        let desc = pool.get_message_by_name("v.v1.Shape").unwrap();
+        let mut found: Vec<_> = validate_request(&desc, request).into_iter().map(|d| (d.path, d.code)).collect();
+        found.sort();
+        found
+    }
+
+    fn expect(found: Vec<(String, &'static str)>, expected: &[(&str, &str)]) {
+        let found: Vec<(&str, &str)> = found.iter().map(|(p, c)| (p.as_str(), *c)).collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        expect(diagnostics("{\"displayName\": "), &[("", "invalid_json")]);
+    }
+
+    #[test]
+    fn test_unknown_and_duplicate_fields() {
+        // JSON and proto names are both accepted, but not for the same field at once.
+        expect(
+            diagnostics(r#"{"displayName": "a", "display_name": "b", "dispalyName": "c", "enabled": true}"#),
+            &[("/dispalyName", "unknown_field"), ("/display_name", "duplicate_field")],
+        );
+        expect(diagnostics(r#"{"a/b": 1, "c~d": 2}"#), &[("/a~1b", "unknown_field"), ("/c~0d", "unknown_field")]);
+        assert!(diagnostics(r#"{"display_name": "a", "byId": {}}"#).is_empty());
+    }
+
+    #[test]
+    fn test_wrong_types() {
+        expect(
+            diagnostics(
+                r#"{"displayName": 5, "enabled": "true", "tags": {"a": "b"}, "flags": ["x"],
+                    "contact": "a@b.c", "color": true, "ratio": {}}"#,
+            ),
+            &[
+                ("/color", "wrong_type"),
+                ("/contact", "wrong_type"),
+                ("/displayName", "wrong_type"),
+                ("/enabled", "wrong_type"),
+                ("/flags", "wrong_type"),
+                ("/ratio", "wrong_type"),
+                ("/tags", "wrong_type"),
+            ],
+        );
+        expect(diagnostics(r#"{"tags": ["a", null, 3]}"#), &[("/tags/1", "wrong_type"), ("/tags/2", "wrong_type")]);
+        // null leaves any other field at its default.
+        assert!(diagnostics(r#"{"displayName": null, "contact": null, "tags": null}"#).is_empty());
+    }
+
+    #[test]
+    fn test_enums() {
+        expect(diagnostics(r#"{"color": "BLUE"}"#), &[("/color", "invalid_enum")]);
+        expect(diagnostics(r#"{"color": 4294967296}"#), &[("/color", "out_of_range")]);
+        // Unknown numbers are valid in proto3.
+        assert!(diagnostics(r#"{"color": 7}"#).is_empty());
+        assert!(diagnostics(r#"{"color": "RED"}"#).is_empty());
+    }
+
+    #[test]
+    fn test_integer_ranges() {
+        expect(diagnostics(r#"{"small": "2147483648"}"#), &[("/small", "out_of_range")]);
+        expect(diagnostics(r#"{"small": -2147483649}"#), &[("/small", "out_of_range")]);
+        expect(diagnostics(r#"{"big": "18446744073709551616"}"#), &[("/big", "out_of_range")]);
+        expect(diagnostics(r#"{"big": "-1"}"#), &[("/big", "out_of_range")]);
+        expect(diagnostics(r#"{"signed": "9223372036854775808"}"#), &[("/signed", "out_of_range")]);
+        expect(diagnostics(r#"{"signed": "1.5"}"#), &[("/signed", "wrong_type")]);
+        expect(diagnostics(r#"{"small": 1.5}"#), &[("/small", "wrong_type")]);
+        assert!(diagnostics(
+            r#"{"small": "-2147483648", "big": "18446744073709551615", "signed": -9223372036854775808}"#
+        )
+        .is_empty());
+        // Integral exponents are integers in the JSON mapping.
+        // Integral JSON numbers may use exponents; quoted integers may not.
+        assert!(diagnostics(r#"{"small": 1e3, "big": 2e3}"#).is_empty());
+        expect(diagnostics(r#"{"big": "2e3"}"#), &[("/big", "wrong_type")]);
+    }
+
+    #[test]
+    fn test_floats() {
+        assert!(diagnostics(r#"{"ratio": "NaN", "exact": "-Infinity"}"#).is_empty());
+        assert!(diagnostics(r#"{"ratio": "Infinity", "exact": "1.5"}"#).is_empty());
+        expect(diagnostics(r#"{"ratio": "half"}"#), &[("/ratio", "wrong_type")]);
+        expect(diagnostics(r#"{"ratio": 1e39}"#), &[("/ratio", "out_of_range")]);
+        assert!(diagnostics(r#"{"exact": 1e39}"#).is_empty());
+    }
+
+    #[test]
+    fn test_map_keys() {
+        expect(diagnostics(r#"{"flags": {"true": "a", "false": "b", "yes": "c"}}"#), &[("/flags/yes", "wrong_type")]);
+        expect(
+            diagnostics(r#"{"byId": {"-12": "a", "twelve": "b", "9223372036854775808": "c"}}"#),
+            &[("/byId/9223372036854775808", "wrong_type"), ("/byId/twelve", "wrong_type")],
+        );
+        // Values are checked under their (escaped) key.
+        expect(diagnostics(r#"{"counts": {"a/b": "x", "c": 1}}"#), &[("/counts/a~1b", "wrong_type")]);
+    }
+
+    #[test]
+    fn test_timestamps_and_durations() {
+        expect(diagnostics(r#"{"at": "2024-13-01T00:00:00Z"}"#), &[("/at", "invalid_format")]);
+        expect(diagnostics(r#"{"at": "2024-01-31"}"#), &[("/at", "invalid_format")]);
+        expect(diagnostics(r#"{"at": "0000-01-01T00:00:00Z"}"#), &[("/at", "out_of_range")]);
+        expect(diagnostics(r#"{"at": 1706702400}"#), &[("/at", "wrong_type")]);
+        assert!(diagnostics(r#"{"at": "2024-01-31T12:00:00.123+02:00"}"#).is_empty());
+
+        for bad in ["1h", "1.s", "1.0000000001s", "s", "+1s", "1,5s"] {
+            let request = format!(r#"{{"ttl": "{}"}}"#, bad);
+            expect(diagnostics(&request), &[("/ttl", "invalid_format")]);
+        }
+        expect(diagnostics(r#"{"ttl": "315576000001s"}"#), &[("/ttl", "out_of_range")]);
+        expect(diagnostics(r#"{"ttl": 5}"#), &[("/ttl", "wrong_type")]);
+        assert!(diagnostics(r#"{"ttl": "-0.000000001s"}"#).is_empty());
+    }
+
+    #[test]
+    fn test_oneof_conflicts() {
+        expect(
+            diagnostics(r#"{"contact": {"email": "a@b.c", "phone": "123"}}"#),
+            &[("/contact", "oneof_conflict")],
+        );
+        // A member set to null doesn't count as set.
+        assert!(diagnostics(r#"{"contact": {"email": "a@b.c", "phone": null}}"#).is_empty());
+    }
+
+    #[test]
+    fn test_wrappers_and_any() {
+        expect(diagnostics(r#"{"limit": "ten"}"#), &[("/limit", "wrong_type")]);
+        assert!(diagnostics(r#"{"limit": 10}"#).is_empty());
+
+        // A resolvable Any is checked as its packed type.
+        expect(
+            diagnostics(r#"{"detail": {"@type": "type.googleapis.com/v.v1.Contact", "email": 5}}"#),
+            &[("/detail/email", "wrong_type")],
+        );
+        expect(
+            diagnostics(r#"{"detail": {"@type": "type.googleapis.com/google.protobuf.Duration", "value": "5m", "x": 1}}"#),
+            &[("/detail/value", "invalid_format"), ("/detail/x", "unknown_field")],
+        );
+        expect(diagnostics(r#"{"detail": {"email": "a@b.c"}}"#), &[("/detail", "invalid_format")]);
+        expect(diagnostics(r#"{"detail": []}"#), &[("/detail", "wrong_type")]);
+
+        // An unresolvable @type isn't checked field by field, but the request still
+        // can't be built.
+        expect(
+            diagnostics(r#"{"detail": {"@type": "type.googleapis.com/other.Missing", "id": 1}}"#),
+            &[("", "invalid")],
+        );
+    }
+
+    #[test]
+    fn test_reports_every_problem_with_pointer() {
+        let proto = r#"
+syntax = "proto3";
+package v.v1;
+import "google/protobuf/timestamp.proto";
+import "google/protobuf/duration.proto";
+
+service Svc { rpc Do (Req) returns (Req); }
+
+enum Color { COLOR_UNSPECIFIED = 0; RED = 1; }
+
+message Item { int64 id = 1; Color color = 2; }
+
+message Req {
+  string name = 1;
+  repeated Item items = 2;
+  map<int32, string> labels = 3;
+  google.protobuf.Timestamp at = 4;
+  google.protobuf.Duration ttl = 5;
+  oneof target {
+    string email = 6;
+    string phone = 7;
+  }
+}
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let desc = pool.get_message_by_name("v.v1.Req").unwrap();
+
+        let request = r#"{
+            "name": 5,
+            "nmae": "x",
+            "items": [{"id": "99999999999999999999", "color": "BLUE"}, {"id": 1}],
+            "labels": {"abc": "x"},
+            "at": "yesterday",
+            "ttl": "5m",
+            "email": "a@b.c",
+            "phone": "123"
+        }"#;
+        let mut found: Vec<(String, &str)> =
+            validate_request(&desc, request).into_iter().map(|d| (d.path, d.code)).collect();
+        found.sort();
+
+        let mut expected = vec![
+            ("".to_string(), "oneof_conflict"),
+            ("/name".to_string(), "wrong_type"),
+            ("/nmae".to_string(), "unknown_field"),
+            ("/items/0/id".to_string(), "out_of_range"),
+            ("/items/0/color".to_string(), "invalid_enum"),
+            ("/labels/abc".to_string(), "wrong_type"),
+            ("/at".to_string(), "invalid_format"),
+            ("/ttl".to_string(), "invalid_format"),
+        ];
+        expected.sort();
+        assert_eq!(found, expected);
+
+        let valid = r#"{"name": "n", "items": [{"id": "12", "color": "RED"}], "labels": {"-3": "x"},
+            "at": "2024-01-31T12:00:00.5Z", "ttl": "-1.250s", "email": "a@b.c", "phone": null}"#;
+        assert!(validate_request(&desc, valid).is_empty(), "{:?}", validate_request(&desc, valid));
+    }
+}
@@ -0,0 +1,458 @@
+use crate::message_format::{encode_hex, MessageFormat};
+use prost_reflect::{DynamicMessage, Kind, MapKey, MessageDescriptor};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Largest valid field number.
+const MAX_FIELD_NUMBER: u64 = 536_870_911;
+
+/// How deep nested messages are guessed inside length-delimited values.
+const MAX_DEPTH: usize = 32;
+
+/// One field as it appears on the wire, decoded without a schema.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RawField {
+    pub number: u32,
+    /// "varint", "fixed64", "len", "group" or "fixed32".
+    pub wire_type: &'static str,
+    /// The descriptor used to decode the frame has no field with this number.
+    pub unknown: bool,
+    /// Possible readings of the value: `uint`/`int`/`sint` for varints,
+    /// `uint`/`int`/`float`/`double` for fixed-width values, and `length`/`hex`
+    /// plus `string` when the bytes are valid UTF-8 for length-delimited values.
+    pub value: Value,
+    /// Fields of a group, or of a length-delimited value that parses as a message.
+    pub message: Option<Vec<RawField>>,
+}
+
+/// A response message decoded against its descriptor, with the raw wire view
+/// attached when it's needed to see what the server actually sent.
+#[derive(Debug)]
+pub struct Inspected {
+    /// The rendered message, or `null` when the frame doesn't match the descriptor.
+    pub data: Value,
+    pub raw: Option<Value>,
+    pub decode_error: Option<String>,
+    /// Paths of fields on the wire that the descriptor doesn't define, such as
+    /// `items[2].9` or `labels["env"].4`.
+    pub unknown_fields: Vec<String>,
+}
+
+/// Decode a message frame. The raw tree is built when `include_raw` is set or when
+/// the frame fails to decode against the descriptor. Unknown fields are always
+/// listed. Only frames that aren't protobuf are errors.
+pub fn inspect(
+    desc: &MessageDescriptor,
+    bytes: &[u8],
+    format: MessageFormat,
+    include_raw: bool,
+) -> Result<Inspected, String> {
+    match DynamicMessage::decode(desc.clone(), bytes) {
+        Ok(msg) => {
+            let data = format.render(&msg)?;
+            let mut unknown_fields = Vec::new();
+            collect_unknown(&msg, "", &mut unknown_fields);
+            let raw = if include_raw {
+                decode_raw(bytes).ok().map(|mut fields| {
+                    mark_unknown(&mut fields, desc);
+                    to_json(fields)
+                })
+            } else {
+                None
+            };
+            Ok(Inspected {
+                data,
+                raw,
+                decode_error: None,
+                unknown_fields,
+            })
+        }
+        Err(e) => {
+            let mut raw =
+                decode_raw(bytes).map_err(|raw_err| format!("Failed to decode response: {} ({})", e, raw_err))?;
+            mark_unknown(&mut raw, desc);
+            Ok(Inspected {
+                data: Value::Null,
+                raw: Some(to_json(raw)),
+                decode_error: Some(e.to_string()),
+                unknown_fields: Vec::new(),
+            })
+        }
+    }
+}
+
+/// A stream frame that isn't protobuf at all (see `inspect`), kept as hex so it is
+/// still shown and counted in its place in the stream.
+pub fn undecodable(bytes: &[u8], error: String) -> Inspected {
+    Inspected {
+        data: Value::Null,
+        raw: Some(serde_json::json!({ "length": bytes.len(), "hex": encode_hex(bytes) })),
+        decode_error: Some(error),
+        unknown_fields: Vec::new(),
+    }
+}
+
+/// Append the paths of `msg`'s unknown fields to `out`, descending into set message
+/// fields, list items and map values.
+fn collect_unknown(msg: &DynamicMessage, prefix: &str, out: &mut Vec<String>) {
+    out.extend(msg.unknown_fields().map(|f| format!("{}{}", prefix, f.number())));
+    for (field, value) in msg.fields() {
+        match value {
+            prost_reflect::Value::Message(m) => collect_unknown(m, &format!("{}{}.", prefix, field.name()), out),
+            prost_reflect::Value::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if let prost_reflect::Value::Message(m) = item {
+                        collect_unknown(m, &format!("{}{}[{}].", prefix, field.name(), i), out);
+                    }
+                }
+            }
+            prost_reflect::Value::Map(entries) => {
+                for (key, entry) in entries {
+                    if let prost_reflect::Value::Message(m) = entry {
+                        let key = match key {
+                            MapKey::String(s) => format!("{:?}", s),
+                            MapKey::Bool(b) => b.to_string(),
+                            MapKey::I32(n) => n.to_string(),
+                            MapKey::I64(n) => n.to_string(),
+                            MapKey::U32(n) => n.to_string(),
+                            MapKey::U64(n) => n.to_string(),
+                        };
+                        collect_unknown(m, &format!("{}{}[{}].", prefix, field.name(), key), out);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Decode wire-format bytes without a schema.
+pub fn decode_raw(bytes: &[u8]) -> Result<Vec<RawField>, String> {
+    let mut pos = 0;
+    parse_fields(bytes, &mut pos, None, 0)
+}
+
+/// Flag fields `desc` doesn't define, descending into known message fields. Returns
+/// whether any were found.
+pub fn mark_unknown(fields: &mut [RawField], desc: &MessageDescriptor) -> bool {
+    let mut found = false;
+    for field in fields.iter_mut() {
+        let kind = desc
+            .get_field(field.number)
+            .map(|f| f.kind())
+            .or_else(|| desc.get_extension(field.number).map(|e| e.kind()));
+        match kind {
+            None => {
+                field.unknown = true;
+                found = true;
+            }
+            Some(Kind::Message(nested)) => {
+                if let Some(children) = &mut field.message {
+                    found |= mark_unknown(children, &nested);
+                }
+            }
+            // Bytes may hold an embedded message; strings and packed scalars never do,
+            // however they parse.
+            Some(Kind::Bytes) => {}
+            Some(_) => field.message = None,
+        }
+    }
+    found
+}
+
+fn parse_fields(buf: &[u8], pos: &mut usize, group: Option<u32>, depth: usize) -> Result<Vec<RawField>, String> {
+    let mut fields = Vec::new();
+    while *pos < buf.len() {
+        let key = read_varint(buf, pos)?;
+        let number = key >> 3;
+        if number == 0 || number > MAX_FIELD_NUMBER {
+            return Err(format!("invalid field number {} at byte {}", number, *pos));
+        }
+        let number = number as u32;
+
+        let (wire_type, value, message) = match key & 7 {
+            0 => {
+                let v = read_varint(buf, pos)?;
+                let zigzag = ((v >> 1) as i64) ^ -((v & 1) as i64);
+                let value = serde_json::json!({
+                    "uint": v.to_string(),
+                    "int": (v as i64).to_string(),
+                    "sint": zigzag.to_string(),
+                });
+                ("varint", value, None)
+            }
+            1 => {
+                let bytes: [u8; 8] = take(buf, pos, 8)?.try_into().expect("8 bytes");
+                let v = u64::from_le_bytes(bytes);
+                let value = serde_json::json!({
+                    "uint": v.to_string(),
+                    "int": (v as i64).to_string(),
+                    "double": f64::from_bits(v),
+                });
+                ("fixed64", value, None)
+            }
+            2 => {
+                let len = read_varint(buf, pos)?;
+                let data = take(
+                    buf,
+                    pos,
+                    usize::try_from(len).map_err(|_| "length overflows".to_string())?,
+                )?;
+                let nested = if depth < MAX_DEPTH {
+                    decode_nested(data, depth)
+                } else {
+                    None
+                };
+                let mut value = serde_json::json!({ "length": len, "hex": encode_hex(data) });
+                if let Some(text) = std::str::from_utf8(data).ok().filter(|s| is_printable(s)) {
+                    value["string"] = Value::String(text.to_string());
+                }
+                ("len", value, nested)
+            }
+            3 => {
+                if depth >= MAX_DEPTH {
+                    return Err("groups nested too deeply".to_string());
+                }
+                let children = parse_fields(buf, pos, Some(number), depth + 1)?;
+                ("group", serde_json::json!({}), Some(children))
+            }
+            4 => {
+                return match group {
+                    Some(open) if open == number => Ok(fields),
+                    _ => Err(format!("unexpected end of group {} at byte {}", number, *pos)),
+                };
+            }
+            5 => {
+                let bytes: [u8; 4] = take(buf, pos, 4)?.try_into().expect("4 bytes");
+                let v = u32::from_le_bytes(bytes);
+                let value = serde_json::json!({
+                    "uint": v,
+                    "int": v as i32,
+                    "float": f32::from_bits(v),
+                });
+                ("fixed32", value, None)
+            }
+            other => return Err(format!("invalid wire type {} at byte {}", other, *pos)),
+        };
+        fields.push(RawField {
+            number,
+            wire_type,
+            unknown: false,
+            value,
+            message,
+        });
+    }
+    match group {
+        Some(open) => Err(format!("group {} is not terminated", open)),
+        None => Ok(fields),
+    }
+}
+
+/// Guess whether a length-delimited value is an embedded message.
+fn decode_nested(data: &[u8], depth: usize) -> Option<Vec<RawField>> {
+    if data.is_empty() {
+        return None;
+    }
+    let mut pos = 0;
+    parse_fields(data, &mut pos, None, depth + 1).ok()
+}
+
+fn is_printable(s: &str) -> bool {
+    s.chars()
+        .all(|c| !c.is_control() || c == '\n' || c == '\r' || c == '\t')
+}
+
+fn read_varint(buf: &[u8], pos: &mut usize) -> Result<u64, String> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *buf.get(*pos).ok_or_else(|| "truncated varint".to_string())?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("varint longer than 10 bytes".to_string())
+}
+
+fn take<'a>(buf: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], String> {
+    let end = pos.checked_add(len).filter(|&end| end <= buf.len()).ok_or_else(|| {
+        format!(
+            "value of {} bytes at byte {} runs past the end of the message",
+            len, *pos
+        )
+    })?;
+    let data = &buf[*pos..end];
+    *pos = end;
+    Ok(data)
+}
+
+fn to_json(fields: Vec<RawField>) -> Value {
+    serde_json::to_value(fields).unwrap_or(Value::Null)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto_parser::compile_single_file;
+
+    #[test]
+    fn test_raw_tree_flags_unknown_fields() {
+        let proto = r#"
+syntax = "proto3";
+package wire.v1;
+service Svc { rpc Do (Outer) returns (Outer); }
+message Inner { string label = 1; }
+message Outer { int32 id = 1; Inner inner = 2; }
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let desc = pool.get_message_by_name("wire.v1.Outer").unwrap();
+
+        // id = 150, inner { label = "hi", 9: 1 (unknown) }, 7: fixed32 (unknown)
+        let bytes = [
+            0x08, 0x96, 0x01, 0x12, 0x06, 0x0a, 0x02, b'h', b'i', 0x48, 0x01, 0x3d, 1, 0, 0, 0,
+        ];
+        let inspected = inspect(&desc, &bytes, MessageFormat::default(), false).unwrap();
+        assert_eq!(inspected.data["id"], 150);
+        assert_eq!(inspected.unknown_fields, ["7", "inner.9"]);
+        assert!(inspected.raw.is_none(), "the raw tree is only built on request");
+
+        let raw = inspect(&desc, &bytes, MessageFormat::default(), true)
+            .unwrap()
+            .raw
+            .expect("raw tree is attached when requested");
+        assert_eq!(raw[0]["value"]["uint"], "150");
+        assert_eq!(raw[1]["message"][0]["value"]["string"], "hi");
+        assert!(
+            raw[1]["message"][0]["message"].is_null(),
+            "known string fields aren't guessed as messages"
+        );
+        assert_eq!(raw[1]["message"][1]["unknown"], true);
+        assert_eq!(raw[2]["wireType"], "fixed32");
+        assert_eq!(raw[2]["unknown"], true);
+
+        // A frame that doesn't match the schema still renders raw instead of failing.
+        let mismatched = [0x0a, 0x02, b'h', b'i'];
+        let inspected = inspect(&desc, &mismatched, MessageFormat::default(), false).unwrap();
+        assert!(inspected.data.is_null() && inspected.decode_error.is_some());
+        let error = inspect(&desc, &[0x0f], MessageFormat::default(), false).unwrap_err();
+
+        let kept = undecodable(&[0x0f], error);
+        assert!(kept.data.is_null() && kept.decode_error.is_some());
+        assert_eq!(kept.raw.unwrap(), serde_json::json!({ "length": 1, "hex": "0f" }));
+    }
+
+    #[test]
+    fn test_unknown_fields_in_lists_and_maps() {
+        let proto = r#"
+syntax = "proto3";
+package wire.v1;
+service Svc { rpc Do (Outer) returns (Outer); }
+message Inner { string label = 1; }
+message Outer { repeated Inner items = 3; map<string, Inner> by_name = 4; }
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let desc = pool.get_message_by_name("wire.v1.Outer").unwrap();
+
+        // items [{}, {9: 1}], by_name {"k": {9: 1}}
+        let bytes = [
+            0x1a, 0x00, 0x1a, 0x02, 0x48, 0x01, 0x22, 0x07, 0x0a, 0x01, b'k', 0x12, 0x02, 0x48, 0x01,
+        ];
+        let inspected = inspect(&desc, &bytes, MessageFormat::default(), true).unwrap();
+        assert_eq!(inspected.unknown_fields, ["items[1].9", "by_name[\"k\"].9"]);
+
+        let raw = inspected.raw.unwrap();
+        assert_eq!(raw[1]["message"][0]["unknown"], true);
+        assert_eq!(raw[2]["message"][1]["message"][0]["unknown"], true);
+        assert_eq!(raw[2]["message"][0]["unknown"], false, "map entry keys are known");
+    }
+
+    #[test]
+    fn test_groups() {
+        // 1: group { 2: 5 }, 3: 1
+        let fields = to_json(decode_raw(&[0x0b, 0x10, 0x05, 0x0c, 0x18, 0x01]).unwrap());
+        assert_eq!(fields[0]["wireType"], "group");
+        assert_eq!(fields[0]["message"][0]["number"], 2);
+        assert_eq!(fields[0]["message"][0]["value"]["uint"], "5");
+        assert_eq!(fields[1]["number"], 3);
+
+        let err = decode_raw(&[0x0b, 0x10, 0x05]).unwrap_err();
+        assert!(err.contains("group 1 is not terminated"), "{}", err);
+        let err = decode_raw(&[0x0b, 0x14]).unwrap_err();
+        assert!(err.contains("unexpected end of group 2"), "{}", err);
+        let err = decode_raw(&[0x0c]).unwrap_err();
+        assert!(err.contains("unexpected end of group 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_fixed_width_and_varint_readings() {
+        let mut bytes = vec![0x0d, 0xff, 0xff, 0xff, 0xff];
+        bytes.push(0x11);
+        bytes.extend_from_slice(&1.5f64.to_le_bytes());
+        bytes.push(0x1d);
+        bytes.extend_from_slice(&2.5f32.to_le_bytes());
+        bytes.extend_from_slice(&[0x20, 0x03]);
+
+        let fields = to_json(decode_raw(&bytes).unwrap());
+        assert_eq!(fields[0]["wireType"], "fixed32");
+        assert_eq!(fields[0]["value"]["uint"], 4294967295u32);
+        assert_eq!(fields[0]["value"]["int"], -1);
+        assert_eq!(fields[1]["wireType"], "fixed64");
+        assert_eq!(fields[1]["value"]["double"], 1.5);
+        assert_eq!(fields[2]["value"]["float"], 2.5);
+        assert_eq!(fields[3]["wireType"], "varint");
+        assert_eq!(fields[3]["value"]["sint"], "-2");
+    }
+
+    #[test]
+    fn test_packed_field_is_not_guessed_as_message() {
+        let proto = r#"
+syntax = "proto3";
+package wire.v1;
+service Svc { rpc Do (Packed) returns (Packed); }
+message Packed { repeated int32 nums = 1; bytes blob = 2; }
+"#;
+        let pool = compile_single_file(proto).expect("compile should succeed");
+        let desc = pool.get_message_by_name("wire.v1.Packed").unwrap();
+
+        // nums [8, 1] packed, which also parses as { 1: 1 }; blob holds { 1: 1 }
+        let bytes = [0x0a, 0x02, 0x08, 0x01, 0x12, 0x02, 0x08, 0x01];
+        let inspected = inspect(&desc, &bytes, MessageFormat::default(), true).unwrap();
+        assert_eq!(inspected.data["nums"], serde_json::json!([8, 1]));
+        assert!(inspected.unknown_fields.is_empty());
+
+        let raw = inspected.raw.unwrap();
+        assert_eq!(raw[0]["wireType"], "len");
+        assert_eq!(raw[0]["unknown"], false);
+        assert_eq!(raw[0]["value"]["hex"], "0801");
+        assert!(raw[0]["message"].is_null());
+        assert_eq!(raw[1]["message"][0]["value"]["uint"], "1", "bytes may hold a message");
+    }
+
+    #[test]
+    fn test_truncated_input() {
+        let err = decode_raw(&[0x08]).unwrap_err();
+        assert_eq!(err, "truncated varint");
+        let err = decode_raw(&[0x08, 0x96]).unwrap_err();
+        assert_eq!(err, "truncated varint");
+        let err = decode_raw(&[0x0a, 0x05, b'a']).unwrap_err();
+        assert!(err.contains("runs past the end"), "{}", err);
+        let err = decode_raw(&[0x0d, 0x01, 0x02]).unwrap_err();
+        assert!(err.contains("runs past the end"), "{}", err);
+        let err = decode_raw(&[0x09, 0x01]).unwrap_err();
+        assert!(err.contains("runs past the end"), "{}", err);
+
+        let pool = compile_single_file(
+            "syntax = \"proto3\"; package wire.v1; service Svc { rpc Do (M) returns (M); } message M { string s = 1; }",
+        )
+        .unwrap();
+        let desc = pool.get_message_by_name("wire.v1.M").unwrap();
+        let err = inspect(&desc, &[0x0a, 0x05, b'a'], MessageFormat::default(), false).unwrap_err();
+        assert!(err.starts_with("Failed to decode response"), "{}", err);
+    }
+}